@@ -2,8 +2,10 @@
 
 use std::cmp;
 
-use crate::enums::White;
-use crate::image::Image;
+use crate::enums::{GrayMethod, White};
+use crate::error;
+use crate::error::{ImgProcError, ImgProcResult};
+use crate::image::{BaseImage, Image, ImageInfo};
 use crate::util;
 use crate::util::constants::{GAMMA, SRGB_TO_XYZ_MAT, XYZ_TO_SRGB_MAT};
 
@@ -48,20 +50,50 @@ pub fn rgb_to_grayscale_f32(input: &Image<f32>) -> Image<f32> {
     }, |a| a)
 }
 
+/// Converts a u8 image to grayscale using `method`. Alpha channels are preserved
+///
+/// # Panics
+///
+/// Panics if `method` is `GrayMethod::Luminance709` or `GrayMethod::Luminance601` and the image
+/// does not have 3 non-alpha channels, or if `method` is `GrayMethod::SingleChannel` and the index
+/// is not less than the number of non-alpha channels
+pub fn to_grayscale(input: &Image<u8>, method: GrayMethod) -> Image<u8> {
+    let channels = input.info().channels_non_alpha();
+    match method {
+        GrayMethod::Luminance709 | GrayMethod::Luminance601 if channels != 3 => {
+            panic!("invalid number of channels: {} requires 3 non-alpha channels, but image has {}",
+                   method, channels);
+        },
+        GrayMethod::SingleChannel(index) if index >= channels => {
+            panic!("invalid channel index: image has {} non-alpha channels, but index is {}",
+                   channels, index);
+        },
+        _ => {},
+    }
+
+    input.map_pixels_if_alpha(|p, p_out| {
+        let max = *p.iter().max().unwrap() as f32;
+        let min = *p.iter().min().unwrap() as f32;
+
+        let val = match method {
+            GrayMethod::Average => p.iter().map(|&c| c as f32).sum::<f32>() / p.len() as f32,
+            GrayMethod::Luminance709 => 0.2126 * p[0] as f32 + 0.7152 * p[1] as f32 + 0.0722 * p[2] as f32,
+            GrayMethod::Luminance601 => 0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32,
+            GrayMethod::Lightness | GrayMethod::Desaturate => (max + min) / 2.0,
+            GrayMethod::SingleChannel(index) => p[index as usize] as f32,
+        };
+
+        p_out.push(val.round().min(255.0) as u8);
+    }, |a| a)
+}
+
 /// Linearizes an sRGB image
 ///
 /// * Input: u8 sRGB image with channels in range [0, 255]
 /// * Output: linearized f32 sRGB image with channels in range [0, 1]
 pub fn linearize_srgb_f32(input: &Image<u8>) -> Image<f32> {
     let mut lookup_table: [f32; 256] = [0.0; 256];
-    util::generate_lookup_table(&mut lookup_table, |i| {
-        let val = i as f32;
-        if val <= 10.0 {
-            val / 3294.0
-        } else {
-            ((val + 14.025) / 269.025).powf(GAMMA)
-        }
-    });
+    util::generate_lookup_table(&mut lookup_table, linearize_fn);
 
     input.map_channels_if_alpha(|i| lookup_table[i as usize], |a| a as f32)
 }
@@ -71,13 +103,57 @@ pub fn linearize_srgb_f32(input: &Image<u8>) -> Image<f32> {
 /// * Input: f32 linearized sRGB image with channels in range [0, 1]
 /// * Output: u8 sRGB image with channels in range [0, 255]
 pub fn unlinearize_srgb_f32(input: &Image<f32>) -> Image<u8> {
-    input.map_channels_if_alpha(|num| {
-        if num <= 0.0031308 {
-            (num * 3294.6) as u8
-        } else {
-            (269.025 * num.powf(1.0 / GAMMA) - 14.025) as u8
-        }
-    }, |a| a.round() as u8)
+    input.map_channels_if_alpha(|num| unlinearize_fn(num) as u8, |a| a.round() as u8)
+}
+
+/// Linearizes an sRGB image, keeping 8 bits per channel. Faster than
+/// [`linearize_srgb_f32()`](fn.linearize_srgb_f32.html), but imprecise: the dark tones are
+/// compressed into few linear values (e.g. sRGB values 0 to 12 all become 0 or 1), so converting
+/// back with [`linear_to_srgb_u8()`](fn.linear_to_srgb_u8.html) loses shadow detail, changing
+/// dark values by up to 6 levels. Alpha channels are unchanged
+///
+/// * Input: u8 sRGB image with channels in range [0, 255]
+/// * Output: u8 linearized sRGB image with channels in range [0, 255]
+pub fn srgb_to_linear_u8(input: &Image<u8>) -> Image<u8> {
+    let mut lookup_table: [u8; 256] = [0; 256];
+    util::generate_lookup_table(&mut lookup_table, |i| (linearize_fn(i) * 255.0).round() as u8);
+
+    input.map_channels_if_alpha(|i| lookup_table[i as usize], |a| a)
+}
+
+/// "Unlinearizes" an image linearized with [`srgb_to_linear_u8()`](fn.srgb_to_linear_u8.html),
+/// keeping 8 bits per channel. Since the linear values are quantized to 8 bits, the dark tones
+/// skip sRGB values (e.g. linear values 0, 1, and 2 become sRGB values 0, 8, and 16). Alpha
+/// channels are unchanged
+///
+/// * Input: u8 linearized sRGB image with channels in range [0, 255]
+/// * Output: u8 sRGB image with channels in range [0, 255]
+pub fn linear_to_srgb_u8(input: &Image<u8>) -> Image<u8> {
+    let mut lookup_table: [u8; 256] = [0; 256];
+    util::generate_lookup_table(&mut lookup_table, |i| {
+        unlinearize_fn(i as f32 / 255.0).round().clamp(0.0, 255.0) as u8
+    });
+
+    input.map_channels_if_alpha(|i| lookup_table[i as usize], |a| a)
+}
+
+/// Converts an sRGB value in the range [0, 255] to a linear value in the range [0, 1]
+fn linearize_fn(i: u8) -> f32 {
+    let val = i as f32;
+    if val <= 10.0 {
+        val / 3294.0
+    } else {
+        ((val + 14.025) / 269.025).powf(GAMMA)
+    }
+}
+
+/// Converts a linear value in the range [0, 1] to an sRGB value in the range [0, 255]
+fn unlinearize_fn(num: f32) -> f32 {
+    if num <= 0.0031308 {
+        num * 3294.6
+    } else {
+        269.025 * num.powf(1.0 / GAMMA) - 14.025
+    }
 }
 
 /// Converts an image from linearized sRGB to CIE XYZ
@@ -312,3 +388,38 @@ pub fn lab_to_srgb_f32(input: &Image<f32>, ref_white: &White) -> Image<u8> {
     let xyz = lab_to_xyz_f32(input, ref_white);
     xyz_to_srgb_f32(&xyz)
 }
+
+/// Returns a single-channel mask that is 255 where every channel of `input` is within the range
+/// `lower` to `upper` (inclusive), and 0 otherwise. Often used after a conversion to HSV for
+/// color segmentation
+///
+/// # Arguments
+///
+/// * `lower` - Must have one value for each channel of `input`
+/// * `upper` - Must have one value for each channel of `input`, each greater than or equal to
+///   the corresponding value in `lower`
+pub fn in_range(input: &Image<u8>, lower: &[u8], upper: &[u8]) -> ImgProcResult<Image<u8>> {
+    error::check_channel_count(input.info().channels, lower.len())?;
+    error::check_channel_count(input.info().channels, upper.len())?;
+
+    for (c, (low, up)) in lower.iter().zip(upper.iter()).enumerate() {
+        if low > up {
+            return Err(ImgProcError::InvalidArgError(format!("invalid range: lower[{}] is {}, \
+                but upper[{}] is {}", c, low, c, up)));
+        }
+    }
+
+    let (width, height) = input.info().wh();
+    let mut output = Image::blank(ImageInfo::new(width, height, 1, false));
+
+    for i in 0..(input.info().size() as usize) {
+        let in_range = input[i].iter().enumerate()
+            .all(|(c, channel)| *channel >= lower[c] && *channel <= upper[c]);
+
+        if in_range {
+            output.set_pixel_indexed(i, &[255]);
+        }
+    }
+
+    Ok(output)
+}
@@ -20,4 +20,36 @@ pub fn f32_to_u8_scale(input: &Image<f32>, scale: u32) -> Image<u8> {
 /// in range 0 to `scale`
 pub fn u8_to_f32_scale(input: &Image<u8>, scale: u32) -> Image<f32> {
     input.map_channels(|channel| ((channel as f32 / 255.0) * scale as f32))
-}
\ No newline at end of file
+}
+
+/// Counts of the samples that were clipped when converting an image to a narrower channel type
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ClipStats {
+    /// The number of samples below the minimum value (including NaN samples)
+    pub below: u64,
+
+    /// The number of samples above the maximum value
+    pub above: u64,
+}
+
+impl ClipStats {
+    /// Returns the total number of clipped samples
+    pub fn total(&self) -> u64 {
+        self.below + self.above
+    }
+}
+
+/// Converts an `Image<f32>` to an `Image<u8>` by rounding, like `Image::<u8>::from()`, and reports
+/// how many samples were outside of the range [0, 255] and therefore clipped
+pub fn to_u8_checked(input: &Image<f32>) -> (Image<u8>, ClipStats) {
+    let mut stats = ClipStats::default();
+    for &channel in input.data().iter() {
+        if channel < 0.0 || channel.is_nan() {
+            stats.below += 1;
+        } else if channel > 255.0 {
+            stats.above += 1;
+        }
+    }
+
+    (input.map_channels(|channel| channel.round() as u8), stats)
+}
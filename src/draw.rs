@@ -0,0 +1,281 @@
+//! A module for drawing shapes onto images
+//!
+//! All functions draw in place, and clip shapes at the boundaries of the image
+//!
+//! # Examples
+//! ```rust
+//! # use imgproc_rs::error::ImgProcResult;
+//! #
+//! # fn main() -> ImgProcResult<()> {
+//! use imgproc_rs::draw;
+//! use imgproc_rs::image::{Image, ImageInfo};
+//!
+//! let mut img: Image<u8> = Image::blank(ImageInfo::new(64, 64, 3, false));
+//!
+//! // Draw a red bounding box and a green circle
+//! draw::rect(&mut img, 8, 8, 32, 16, &[255, 0, 0])?;
+//! draw::circle(&mut img, 32, 40, 10, &[0, 255, 0])?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error;
+use crate::error::{ImgProcError, ImgProcResult};
+use crate::image::{BaseImage, Image};
+
+/// Draws a line from `(x0, y0)` to `(x1, y1)` (inclusive) using Bresenham's algorithm
+///
+/// # Arguments
+///
+/// * `color` - Must have one value for each channel of `img`
+pub fn line(img: &mut Image<u8>, x0: i32, y0: i32, x1: i32, y1: i32, color: &[u8]) -> ImgProcResult<()> {
+    error::check_channel_count(img.info().channels, color.len())?;
+
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let step_x = if x0 < x1 { 1 } else { -1 };
+    let step_y = if y0 < y1 { 1 } else { -1 };
+    let mut err = dx + dy;
+    let (mut x, mut y) = (x0, y0);
+
+    loop {
+        plot(img, x, y, color);
+        if x == x1 && y == y1 {
+            break;
+        }
+
+        let err_2 = 2 * err;
+        if err_2 >= dy {
+            err += dy;
+            x += step_x;
+        }
+        if err_2 <= dx {
+            err += dx;
+            y += step_y;
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws an anti-aliased line of width `thickness` from `(x0, y0)` to `(x1, y1)`, where pixel
+/// centers lie at integer coordinates
+///
+/// Each pixel is blended with `color` in proportion to how much of it is covered by the line,
+/// which allows sub-pixel endpoints and non-integer thicknesses
+///
+/// # Arguments
+///
+/// * `color` - Must have one value for each channel of `img`
+/// * `thickness` - The width of the line. Must be positive
+pub fn line_aa(img: &mut Image<u8>, x0: f32, y0: f32, x1: f32, y1: f32, color: &[u8], thickness: f32) -> ImgProcResult<()> {
+    error::check_channel_count(img.info().channels, color.len())?;
+    if thickness <= 0.0 {
+        return Err(ImgProcError::InvalidArgError("thickness must be positive".to_string()));
+    }
+
+    let half_width = thickness / 2.0;
+    let reach = half_width + 0.5;
+    let (dx, dy) = (x1 - x0, y1 - y0);
+    let len_sq = dx * dx + dy * dy;
+
+    let x_min = (x0.min(x1) - reach).floor().max(0.0) as i32;
+    let x_max = (x0.max(x1) + reach).ceil().min(img.info().width as f32 - 1.0) as i32;
+    let y_min = (y0.min(y1) - reach).floor().max(0.0) as i32;
+    let y_max = (y0.max(y1) + reach).ceil().min(img.info().height as f32 - 1.0) as i32;
+
+    for y in y_min..=y_max {
+        for x in x_min..=x_max {
+            // Distance from the pixel center to the closest point on the segment
+            let t = if len_sq > 0.0 {
+                (((x as f32 - x0) * dx + (y as f32 - y0) * dy) / len_sq).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let dist = (x as f32 - (x0 + t * dx)).hypot(y as f32 - (y0 + t * dy));
+
+            let coverage = (reach - dist).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                for (channel, &c) in img.get_pixel_mut(x as u32, y as u32).iter_mut().zip(color.iter()) {
+                    *channel = (c as f32 * coverage + *channel as f32 * (1.0 - coverage)).round() as u8;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws the outline of the rectangle of width `width` and height `height` with upper left hand
+/// corner located at `(x, y)`
+///
+/// # Arguments
+///
+/// * `color` - Must have one value for each channel of `img`
+pub fn rect(img: &mut Image<u8>, x: i32, y: i32, width: u32, height: u32, color: &[u8]) -> ImgProcResult<()> {
+    error::check_channel_count(img.info().channels, color.len())?;
+    if width == 0 || height == 0 {
+        return Ok(());
+    }
+
+    let x1 = x + width as i32 - 1;
+    let y1 = y + height as i32 - 1;
+
+    line(img, x, y, x1, y, color)?;
+    line(img, x, y1, x1, y1, color)?;
+    line(img, x, y, x, y1, color)?;
+    line(img, x1, y, x1, y1, color)
+}
+
+/// Fills the rectangle of width `width` and height `height` with upper left hand corner located
+/// at `(x, y)`
+///
+/// # Arguments
+///
+/// * `color` - Must have one value for each channel of `img`
+pub fn rect_filled(img: &mut Image<u8>, x: i32, y: i32, width: u32, height: u32, color: &[u8]) -> ImgProcResult<()> {
+    error::check_channel_count(img.info().channels, color.len())?;
+
+    let (x_0, x_1) = clip_range(x, width, img.info().width);
+    let (y_0, y_1) = clip_range(y, height, img.info().height);
+
+    for j in y_0..y_1 {
+        for i in x_0..x_1 {
+            img.set_pixel(i, j, color);
+        }
+    }
+
+    Ok(())
+}
+
+/// Blends a translucent rectangle of width `width` and height `height` with upper left hand
+/// corner located at `(x, y)` over the image, where each channel is set to
+/// `color * alpha + existing * (1 - alpha)`
+///
+/// # Arguments
+///
+/// * `color` - Must have one value for each channel of `img`
+/// * `alpha` - The opacity of the rectangle. Must be between 0 and 1 (inclusive)
+pub fn rect_alpha(img: &mut Image<u8>, x: i32, y: i32, width: u32, height: u32, color: &[u8], alpha: f32) -> ImgProcResult<()> {
+    error::check_channel_count(img.info().channels, color.len())?;
+    error::check_in_range(alpha, 0.0, 1.0, "alpha")?;
+
+    let (x_0, x_1) = clip_range(x, width, img.info().width);
+    let (y_0, y_1) = clip_range(y, height, img.info().height);
+
+    for j in y_0..y_1 {
+        for i in x_0..x_1 {
+            for (channel, &c) in img.get_pixel_mut(i, j).iter_mut().zip(color.iter()) {
+                *channel = (c as f32 * alpha + *channel as f32 * (1.0 - alpha)).round() as u8;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws the outline of the circle of radius `radius` centered at `(x, y)` using the midpoint
+/// circle algorithm
+///
+/// # Arguments
+///
+/// * `color` - Must have one value for each channel of `img`
+pub fn circle(img: &mut Image<u8>, x: i32, y: i32, radius: u32, color: &[u8]) -> ImgProcResult<()> {
+    error::check_channel_count(img.info().channels, color.len())?;
+
+    let mut dx = radius as i32;
+    let mut dy = 0;
+    let mut err = 1 - dx;
+
+    while dx >= dy {
+        for &(i, j) in [(dx, dy), (dy, dx), (-dy, dx), (-dx, dy),
+                        (-dx, -dy), (-dy, -dx), (dy, -dx), (dx, -dy)].iter() {
+            plot(img, x + i, y + j, color);
+        }
+
+        dy += 1;
+        if err < 0 {
+            err += 2 * dy + 1;
+        } else {
+            dx -= 1;
+            err += 2 * (dy - dx) + 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws the polygon with vertices `points`
+///
+/// If `filled` is `false`, only the closed outline of the polygon is drawn. Otherwise, the
+/// interior is filled by scanline rasterization using the even-odd rule, where a pixel is filled
+/// if its center lies inside the polygon
+///
+/// # Arguments
+///
+/// * `points` - The vertices of the polygon, in order
+/// * `color` - Must have one value for each channel of `img`
+/// * `filled` - If `true`, fills the polygon instead of drawing its outline
+pub fn polygon(img: &mut Image<u8>, points: &[(i32, i32)], color: &[u8], filled: bool) -> ImgProcResult<()> {
+    error::check_channel_count(img.info().channels, color.len())?;
+    if points.is_empty() {
+        return Ok(());
+    }
+
+    if !filled {
+        for (i, &(x0, y0)) in points.iter().enumerate() {
+            let (x1, y1) = points[(i + 1) % points.len()];
+            line(img, x0, y0, x1, y1, color)?;
+        }
+
+        return Ok(());
+    }
+
+    let y_min = points.iter().map(|p| p.1).min().unwrap().max(0);
+    let y_max = points.iter().map(|p| p.1).max().unwrap().min(img.info().height as i32 - 1);
+    let mut crossings = Vec::new();
+
+    for y in y_min..=y_max {
+        let scan_y = y as f64 + 0.5;
+        crossings.clear();
+
+        for (i, &(x0, y0)) in points.iter().enumerate() {
+            let (x1, y1) = points[(i + 1) % points.len()];
+            let (y0, y1) = (y0 as f64, y1 as f64);
+
+            // Half-open test so that shared vertices are only counted once
+            if (y0 <= scan_y) != (y1 <= scan_y) {
+                let t = (scan_y - y0) / (y1 - y0);
+                crossings.push(x0 as f64 + t * (x1 - x0) as f64);
+            }
+        }
+
+        crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        for pair in crossings.chunks_exact(2) {
+            let x_start = (pair[0] - 0.5).ceil() as i32;
+            let x_end = (pair[1] - 0.5).ceil() as i32;
+
+            for x in x_start.max(0)..x_end.min(img.info().width as i32) {
+                img.set_pixel(x as u32, y as u32, color);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sets the pixel located at `(x, y)` to `color` if it lies within the image
+fn plot(img: &mut Image<u8>, x: i32, y: i32, color: &[u8]) {
+    if x >= 0 && y >= 0 && (x as u32) < img.info().width && (y as u32) < img.info().height {
+        img.set_pixel(x as u32, y as u32, color);
+    }
+}
+
+/// Returns the part of the range `start..(start + len)` that lies within `0..max`
+fn clip_range(start: i32, len: u32, max: u32) -> (u32, u32) {
+    let end = (start as i64 + len as i64).clamp(0, max as i64) as u32;
+    let start = (start as i64).clamp(0, max as i64) as u32;
+
+    (start, end)
+}
@@ -1,12 +1,32 @@
 //! A module for image enums
+//!
+//! All enums implement `std::str::FromStr` and `std::fmt::Display` using case-insensitive
+//! snake_case variant names (e.g. `"bilinear"`, `"binary_inv"`), so they can be parsed directly
+//! from command line arguments
+//!
+//! # Examples
+//! ```rust
+//! use imgproc_rs::enums::Scale;
+//!
+//! let method: Scale = "Bilinear".parse().unwrap();
+//! assert_eq!(Scale::Bilinear, method);
+//! assert_eq!("bilinear", method.to_string());
+//! ```
+
+use crate::error::ImgProcError;
+
+use std::fmt;
+use std::str::FromStr;
 
 /// An enum for reference white values
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum White {
     D50,
     D65,
 }
 
 /// An enum for image thresholding operations
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Thresh {
     /// If pixel value is greater than `threshold`, it is set to `max`; otherwise, it is set to 0
     Binary,
@@ -25,6 +45,7 @@ pub enum Thresh {
 }
 
 /// An enum for different scaling algorithms
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Scale {
     /// Nearest neighbor interpolation
     NearestNeighbor,
@@ -37,9 +58,17 @@ pub enum Scale {
 
     /// Lanczos resampling with kernel size of 3
     Lanczos,
+
+    /// Mitchell-Netravali cubic filter with `B = C = 1/3`, which balances sharpness and ringing
+    Mitchell,
+
+    /// Catmull-Rom spline (the Mitchell-Netravali filter with `B = 0` and `C = 1/2`), which is
+    /// sharper than `Mitchell`
+    CatmullRom,
 }
 
 /// An enum for image reflection axes
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Refl {
     /// Reflection axis along the line x = 0
     Vertical,
@@ -48,9 +77,23 @@ pub enum Refl {
     Horizontal,
 }
 
+/// An enum for the handling of pixels beyond the edges of an image in neighborhood operations
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Border {
+    /// Edge pixels are repeated (`aaa|abcd|ddd`)
+    Replicate,
+
+    /// Pixels are reflected about the edge pixels (`dcb|abcd|cba`)
+    Reflect,
+
+    /// The image is tiled (`bcd|abcd|abc`)
+    Wrap,
+}
+
 /// An enum for bilateral filter algorithms
 // Read a description of the algorithms here:
 // https://people.csail.mit.edu/sparis/publi/2009/fntcgv/Paris_09_Bilateral_filtering.pdf
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Bilateral {
     /// Direct bilateral filter algorithm
     Direct,
@@ -60,4 +103,242 @@ pub enum Bilateral {
 //
 //     /// Local histogram algorithm
 //     LocalHistogram,
-}
\ No newline at end of file
+}
+
+/// An enum for false-color maps applied to single-channel images
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Colormap {
+    /// Black to white
+    Grayscale,
+
+    /// Blue to cyan to yellow to red (MATLAB's "jet")
+    Jet,
+
+    /// Perceptually uniform dark purple to blue to green to yellow (matplotlib's "viridis")
+    Viridis,
+
+    /// Black to red to yellow to white
+    Hot,
+}
+
+/// An enum for convolution output sizes, for an input of size `width x height` and a kernel of
+/// size `kw x kh`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConvMode {
+    /// Output is the same size as the input (`width x height`), with edge pixels repeated to pad
+    /// the input
+    Same,
+
+    /// Output contains only the pixels where the kernel fully overlaps the input
+    /// (`(width - kw + 1) x (height - kh + 1)`)
+    Valid,
+
+    /// Output contains every pixel where the kernel overlaps the input at all, with the input
+    /// zero-padded (`(width + kw - 1) x (height + kh - 1)`)
+    Full,
+}
+
+/// An enum for positioning a region within an image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    /// Centered horizontally and vertically
+    Center,
+
+    /// Aligned with the top edge and centered horizontally
+    Top,
+
+    /// Aligned with the bottom edge and centered horizontally
+    Bottom,
+
+    /// Aligned with the left edge and centered vertically
+    Left,
+
+    /// Aligned with the right edge and centered vertically
+    Right,
+}
+
+/// An enum for layer blend modes, where `a` is the back channel value and `b` is the front
+/// channel value, both normalized to [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendMode {
+    /// `a * b`
+    Multiply,
+
+    /// `1 - (1 - a) * (1 - b)`
+    Screen,
+
+    /// Multiply if `a < 0.5`, otherwise screen, with the results scaled by 2
+    Overlay,
+
+    /// `min(a, b)`
+    Darken,
+
+    /// `max(a, b)`
+    Lighten,
+
+    /// `|a - b|`
+    Difference,
+
+    /// `min(a + b, 1)`
+    Add,
+}
+
+/// An enum for methods of converting a color image to grayscale, where `r`, `g`, and `b` are the
+/// channel values of a pixel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GrayMethod {
+    /// The mean of the non-alpha channels
+    Average,
+
+    /// `0.2126 * r + 0.7152 * g + 0.0722 * b` (ITU-R BT.709)
+    Luminance709,
+
+    /// `0.299 * r + 0.587 * g + 0.114 * b` (ITU-R BT.601)
+    Luminance601,
+
+    /// The HSL lightness, `(max(r, g, b) + min(r, g, b)) / 2`
+    Lightness,
+
+    /// The mean of the largest and smallest non-alpha channels. Equal to `Lightness` for RGB images
+    Desaturate,
+
+    /// The non-alpha channel with the given index
+    SingleChannel(u8),
+}
+
+/// An enum for JPEG chroma subsampling ratios
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Subsampling {
+    /// No chroma subsampling
+    S444,
+
+    /// Chroma is sampled at half the horizontal resolution
+    S422,
+
+    /// Chroma is sampled at half the horizontal and vertical resolution
+    S420,
+}
+
+/// An enum for frequency domain filters, where `cutoff` is a normalized frequency (in cycles per
+/// pixel) and Butterworth filters are of order 2. Band-pass filters pass frequencies between
+/// `cutoff / 2` and `2 * cutoff`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FreqMode {
+    /// Removes frequencies above `cutoff`
+    IdealLowPass,
+
+    /// Removes frequencies below `cutoff`
+    IdealHighPass,
+
+    /// Removes frequencies outside of the band around `cutoff`
+    IdealBandPass,
+
+    /// Smoothly attenuates frequencies above `cutoff`
+    ButterworthLowPass,
+
+    /// Smoothly attenuates frequencies below `cutoff`
+    ButterworthHighPass,
+
+    /// Smoothly attenuates frequencies outside of the band around `cutoff`
+    ButterworthBandPass,
+}
+
+/// An enum for measuring the distance between two colors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorMetric {
+    /// Euclidean distance between the channel values
+    EuclideanRGB,
+
+    /// CIE76 color difference, the Euclidean distance in CIELAB (D65)
+    DeltaE,
+}
+
+/// Implements `Display` and `FromStr` for an enum using the given variant names
+macro_rules! impl_enum_str {
+    ($name:ident, $($variant:ident => $str:expr),+) => {
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let name = match self {
+                    $($name::$variant => $str,)+
+                };
+
+                write!(f, "{}", name)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ImgProcError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let lower = s.trim().to_lowercase().replace('-', "_");
+
+                $(if lower == $str {
+                    return Ok($name::$variant);
+                })+
+
+                let valid: &[&str] = &[$($str),+];
+                Err(ImgProcError::InvalidArgError(format!("invalid {}: \"{}\" (expected one of: {})",
+                                                           stringify!($name), s, valid.join(", "))))
+            }
+        }
+    };
+}
+
+impl_enum_str!(White, D50 => "d50", D65 => "d65");
+impl_enum_str!(Thresh, Binary => "binary", BinaryInv => "binary_inv", Trunc => "trunc",
+               ToZero => "to_zero", ToZeroInv => "to_zero_inv");
+impl_enum_str!(Scale, NearestNeighbor => "nearest_neighbor", Bilinear => "bilinear",
+               Bicubic => "bicubic", Lanczos => "lanczos", Mitchell => "mitchell", CatmullRom => "catmull_rom");
+impl_enum_str!(Refl, Vertical => "vertical", Horizontal => "horizontal");
+impl_enum_str!(Border, Replicate => "replicate", Reflect => "reflect", Wrap => "wrap");
+impl_enum_str!(Bilateral, Direct => "direct");
+impl_enum_str!(Colormap, Grayscale => "grayscale", Jet => "jet", Viridis => "viridis", Hot => "hot");
+impl_enum_str!(ConvMode, Same => "same", Valid => "valid", Full => "full");
+impl_enum_str!(BlendMode, Multiply => "multiply", Screen => "screen", Overlay => "overlay",
+               Darken => "darken", Lighten => "lighten", Difference => "difference", Add => "add");
+impl_enum_str!(Anchor, Center => "center", Top => "top", Bottom => "bottom", Left => "left", Right => "right");
+impl_enum_str!(FreqMode, IdealLowPass => "ideal_low_pass", IdealHighPass => "ideal_high_pass",
+               IdealBandPass => "ideal_band_pass", ButterworthLowPass => "butterworth_low_pass",
+               ButterworthHighPass => "butterworth_high_pass", ButterworthBandPass => "butterworth_band_pass");
+impl_enum_str!(ColorMetric, EuclideanRGB => "euclidean_rgb", DeltaE => "delta_e");
+impl_enum_str!(Subsampling, S444 => "4:4:4", S422 => "4:2:2", S420 => "4:2:0");
+
+// `GrayMethod::SingleChannel` holds an index, so it is written as e.g. `"single_channel(1)"`
+impl fmt::Display for GrayMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrayMethod::Average => write!(f, "average"),
+            GrayMethod::Luminance709 => write!(f, "luminance709"),
+            GrayMethod::Luminance601 => write!(f, "luminance601"),
+            GrayMethod::Lightness => write!(f, "lightness"),
+            GrayMethod::Desaturate => write!(f, "desaturate"),
+            GrayMethod::SingleChannel(index) => write!(f, "single_channel({})", index),
+        }
+    }
+}
+
+impl FromStr for GrayMethod {
+    type Err = ImgProcError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase().replace('-', "_");
+
+        match lower.as_str() {
+            "average" => return Ok(GrayMethod::Average),
+            "luminance709" => return Ok(GrayMethod::Luminance709),
+            "luminance601" => return Ok(GrayMethod::Luminance601),
+            "lightness" => return Ok(GrayMethod::Lightness),
+            "desaturate" => return Ok(GrayMethod::Desaturate),
+            _ => {},
+        }
+
+        if let Some(index) = lower.strip_prefix("single_channel(").and_then(|rest| rest.strip_suffix(')')) {
+            if let Ok(index) = index.trim().parse() {
+                return Ok(GrayMethod::SingleChannel(index));
+            }
+        }
+
+        Err(ImgProcError::InvalidArgError(format!("invalid GrayMethod: \"{}\" (expected one of: average, \
+            luminance709, luminance601, lightness, desaturate, single_channel(<index>))", s)))
+    }
+}
@@ -1,5 +1,5 @@
 use crate::error::{ImgProcResult, ImgProcError};
-use crate::image::{Number, Image, BaseImage};
+use crate::image::{Number, Image, BaseImage, ImageInfo};
 
 pub(crate) fn check_channels(channels: u8, len: usize) {
     if channels != len as u8 {
@@ -49,6 +49,22 @@ pub(crate) fn check_equal<T: std::cmp::PartialEq>(val_1: T, val_2: T, name: &str
     Ok(())
 }
 
+pub(crate) fn check_info(expected: ImageInfo, found: ImageInfo) -> ImgProcResult<()> {
+    if expected != found {
+        return Err(ImgProcError::DimensionMismatch { expected, found });
+    }
+
+    Ok(())
+}
+
+pub(crate) fn check_channel_count(expected: u8, found: usize) -> ImgProcResult<()> {
+    if expected as usize != found {
+        return Err(ImgProcError::ChannelMismatch { expected, found });
+    }
+
+    Ok(())
+}
+
 pub(crate) fn check_square(val: f32, name: &str) -> ImgProcResult<()> {
     if val.sqrt() % 1.0 != 0.0 {
         return Err(ImgProcError::InvalidArgError(format!("{} must be square", name)));
@@ -4,7 +4,9 @@ pub(crate) use self::messages::*;
 
 mod messages;
 
-use std::io;
+use crate::image::ImageInfo;
+
+use std::{fmt, io};
 
 /// Type alias for `Result<T, ImgProcError>`
 pub type ImgProcResult<T> = Result<T, ImgProcError>;
@@ -18,8 +20,32 @@ pub enum ImgProcError {
     InvalidArgError(String),
     NumericError(String),
     RulinalgError(rulinalg::error::Error),
+    /// The dimensions of an image do not match the dimensions of another image
+    DimensionMismatch { expected: ImageInfo, found: ImageInfo },
+    /// The number of channels of an image does not match the length of a pixel or an argument
+    ChannelMismatch { expected: u8, found: usize },
+}
+
+impl fmt::Display for ImgProcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImgProcError::InvalidArgError(msg) => write!(f, "{}", msg),
+            ImgProcError::NumericError(msg) => write!(f, "{}", msg),
+            ImgProcError::RulinalgError(err) => write!(f, "{}", err),
+            ImgProcError::DimensionMismatch { expected, found } => {
+                write!(f, "image dimensions must be equal: expected {}x{} with {} channels, \
+                           found {}x{} with {} channels", expected.width, expected.height,
+                       expected.channels, found.width, found.height, found.channels)
+            },
+            ImgProcError::ChannelMismatch { expected, found } => {
+                write!(f, "invalid number of channels: expected {}, found {}", expected, found)
+            },
+        }
+    }
 }
 
+impl std::error::Error for ImgProcError {}
+
 impl From<rulinalg::error::Error> for ImgProcError {
     fn from(err: rulinalg::error::Error) -> Self {
         ImgProcError::RulinalgError(err)
@@ -33,10 +59,36 @@ pub enum ImgIoError {
     UnsupportedColorTypeError(String),
     IoError(io::Error),
     ImageReaderError(image::error::ImageError),
+    TiffReaderError(tiff::TiffError),
     ImageWriteError(String),
     OtherError(String),
 }
 
+impl fmt::Display for ImgIoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImgIoError::UnsupportedFileFormatError(msg) => write!(f, "unsupported file format: {}", msg),
+            ImgIoError::UnsupportedColorTypeError(msg) => write!(f, "unsupported color type: {}", msg),
+            ImgIoError::IoError(err) => write!(f, "{}", err),
+            ImgIoError::ImageReaderError(err) => write!(f, "{}", err),
+            ImgIoError::TiffReaderError(err) => write!(f, "{}", err),
+            ImgIoError::ImageWriteError(msg) => write!(f, "image write error: {}", msg),
+            ImgIoError::OtherError(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ImgIoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImgIoError::IoError(err) => Some(err),
+            ImgIoError::ImageReaderError(err) => Some(err),
+            ImgIoError::TiffReaderError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 impl From<io::Error> for ImgIoError {
     fn from(err: io::Error) -> Self {
         ImgIoError::IoError(err)
@@ -49,6 +101,12 @@ impl From<image::error::ImageError> for ImgIoError {
     }
 }
 
+impl From<tiff::TiffError> for ImgIoError {
+    fn from(err: tiff::TiffError) -> Self {
+        ImgIoError::TiffReaderError(err)
+    }
+}
+
 impl From<String> for ImgIoError {
     fn from(err: String) -> Self {
         ImgIoError::OtherError(err)
@@ -0,0 +1,130 @@
+use crate::error::ImgProcResult;
+use crate::filter;
+use crate::image::Image;
+
+/// A filter that can be stored as a trait object, for building processing chains at runtime
+pub trait Filter {
+    /// Applies the filter to `input`
+    fn apply(&self, input: &Image<f32>) -> ImgProcResult<Image<f32>>;
+}
+
+/// Applies each filter in `filters` in order, starting with `input`
+pub fn apply_chain(input: &Image<f32>, filters: &[Box<dyn Filter>]) -> ImgProcResult<Image<f32>> {
+    let mut output = input.clone();
+    for f in filters.iter() {
+        output = f.apply(&output)?;
+    }
+
+    Ok(output)
+}
+
+/// See [`box_filter()`](fn.box_filter.html)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoxFilter {
+    /// The kernel size
+    pub size: u32,
+}
+
+impl Filter for BoxFilter {
+    fn apply(&self, input: &Image<f32>) -> ImgProcResult<Image<f32>> {
+        filter::box_filter(input, self.size)
+    }
+}
+
+/// See [`weighted_avg_filter()`](fn.weighted_avg_filter.html)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightedAvgFilter {
+    /// The kernel size
+    pub size: u32,
+
+    /// The center weight
+    pub weight: u32,
+}
+
+impl Filter for WeightedAvgFilter {
+    fn apply(&self, input: &Image<f32>) -> ImgProcResult<Image<f32>> {
+        filter::weighted_avg_filter(input, self.size, self.weight)
+    }
+}
+
+/// See [`gaussian_blur()`](fn.gaussian_blur.html)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GaussianBlur {
+    /// The kernel size
+    pub size: u32,
+
+    /// The standard deviation of the Gaussian
+    pub sigma: f32,
+}
+
+impl Filter for GaussianBlur {
+    fn apply(&self, input: &Image<f32>) -> ImgProcResult<Image<f32>> {
+        filter::gaussian_blur(input, self.size, self.sigma)
+    }
+}
+
+/// See [`sharpen()`](fn.sharpen.html)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sharpen;
+
+impl Filter for Sharpen {
+    fn apply(&self, input: &Image<f32>) -> ImgProcResult<Image<f32>> {
+        filter::sharpen(input)
+    }
+}
+
+/// See [`unsharp_masking()`](fn.unsharp_masking.html)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnsharpMasking;
+
+impl Filter for UnsharpMasking {
+    fn apply(&self, input: &Image<f32>) -> ImgProcResult<Image<f32>> {
+        filter::unsharp_masking(input)
+    }
+}
+
+/// See [`prewitt()`](fn.prewitt.html)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prewitt;
+
+impl Filter for Prewitt {
+    fn apply(&self, input: &Image<f32>) -> ImgProcResult<Image<f32>> {
+        filter::prewitt(input)
+    }
+}
+
+/// See [`sobel()`](fn.sobel.html)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sobel;
+
+impl Filter for Sobel {
+    fn apply(&self, input: &Image<f32>) -> ImgProcResult<Image<f32>> {
+        filter::sobel(input)
+    }
+}
+
+/// See [`laplacian()`](fn.laplacian.html)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Laplacian;
+
+impl Filter for Laplacian {
+    fn apply(&self, input: &Image<f32>) -> ImgProcResult<Image<f32>> {
+        filter::laplacian(input)
+    }
+}
+
+/// See [`laplacian_of_gaussian()`](fn.laplacian_of_gaussian.html)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LaplacianOfGaussian {
+    /// The kernel size
+    pub size: u32,
+
+    /// The standard deviation of the Gaussian
+    pub sigma: f32,
+}
+
+impl Filter for LaplacianOfGaussian {
+    fn apply(&self, input: &Image<f32>) -> ImgProcResult<Image<f32>> {
+        filter::laplacian_of_gaussian(input, self.size, self.sigma)
+    }
+}
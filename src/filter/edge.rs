@@ -39,6 +39,20 @@ pub fn sobel_weighted(input: &Image<f32>, weight: u32) -> ImgProcResult<Image<f3
     Ok(derivative_mask(input, &vert_kernel, &K_SOBEL_1D_HORZ)?)
 }
 
+/// Returns the signed horizontal Sobel response of a grayscale image, which is positive where
+/// intensity increases to the right
+pub fn gradient_x(input: &Image<f32>) -> ImgProcResult<Image<f32>> {
+    error::check_grayscale(input)?;
+    filter::separable_filter(input, &K_SOBEL_1D_VERT, &K_SOBEL_1D_HORZ)
+}
+
+/// Returns the signed vertical Sobel response of a grayscale image, which is positive where
+/// intensity increases downward
+pub fn gradient_y(input: &Image<f32>) -> ImgProcResult<Image<f32>> {
+    error::check_grayscale(input)?;
+    filter::separable_filter(input, &K_SOBEL_1D_HORZ, &K_SOBEL_1D_VERT)
+}
+
 /// Applies the Laplacian operator to a grayscale image. Output contains positive
 /// and negative values - use [`normalize_laplacian()`](fn.normalize_laplacian.html) for visualization
 pub fn laplacian(input: &Image<f32>) -> ImgProcResult<Image<f32>> {
@@ -0,0 +1,70 @@
+////////////////////
+// Optical flow
+////////////////////
+
+use crate::{error, filter, util};
+use crate::error::ImgProcResult;
+use crate::image::{BaseImage, Image, ImageInfo};
+use crate::util::constants::{K_SOBEL_1D_HORZ, K_SOBEL_1D_VERT};
+
+use rulinalg::matrix::Matrix;
+use rulinalg::vector::Vector;
+
+/// Estimates the optical flow between two grayscale frames using the Lucas-Kanade method, where
+/// the flow of each pixel is found by solving the least-squares system over the
+/// `window x window` neighborhood centered at that pixel. Returns a tuple containing the
+/// horizontal (u) and vertical (v) flow fields, in that order. Pixels whose neighborhood does not
+/// contain enough texture to determine the flow are set to 0
+///
+/// # Arguments
+///
+/// * `window` - Must be odd
+pub fn optical_flow_lk(frame1: &Image<f32>, frame2: &Image<f32>, window: u32) -> ImgProcResult<(Image<f32>, Image<f32>)> {
+    error::check_grayscale(frame1)?;
+    error::check_info(frame1.info(), frame2.info())?;
+    error::check_odd(window, "window")?;
+
+    let (width, height) = frame1.info().wh();
+    let radius = window / 2;
+
+    // Normalized Sobel derivatives of the first frame
+    let img_x = filter::separable_filter(frame1, &K_SOBEL_1D_VERT, &K_SOBEL_1D_HORZ)?;
+    let img_y = filter::separable_filter(frame1, &K_SOBEL_1D_HORZ, &K_SOBEL_1D_VERT)?;
+
+    // Products of derivatives, summed over each window using a summed-area table
+    let mut products = Image::blank(ImageInfo::new(width, height, 5, false));
+    for i in 0..(frame1.info().size() as usize) {
+        let i_x = img_x[i][0] / 8.0;
+        let i_y = img_y[i][0] / 8.0;
+        let i_t = frame2[i][0] - frame1[i][0];
+
+        products.set_pixel_indexed(i, &[i_x * i_x, i_x * i_y, i_y * i_y, i_x * i_t, i_y * i_t]);
+    }
+    let table = util::generate_summed_area_table(&products);
+
+    let mut flow_u = Image::blank(ImageInfo::new(width, height, 1, false));
+    let mut flow_v = Image::blank(ImageInfo::new(width, height, 1, false));
+
+    for y in 0..height {
+        for x in 0..width {
+            let x_0 = x.saturating_sub(radius);
+            let y_0 = y.saturating_sub(radius);
+            let x_1 = std::cmp::min(x + radius, width - 1);
+            let y_1 = std::cmp::min(y + radius, height - 1);
+            let sum = util::rectangular_intensity_sum(&table, x_0, y_0, x_1, y_1);
+
+            let det = sum[0] * sum[2] - sum[1] * sum[1];
+            if det.abs() <= f32::EPSILON {
+                continue;
+            }
+
+            let mat = Matrix::new(2, 2, vec![sum[0], sum[1], sum[1], sum[2]]);
+            let flow = mat.solve(Vector::new(vec![-sum[3], -sum[4]]))?;
+
+            flow_u.set_pixel(x, y, &[flow[0]]);
+            flow_v.set_pixel(x, y, &[flow[1]]);
+        }
+    }
+
+    Ok((flow_u, flow_v))
+}
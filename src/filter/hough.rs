@@ -0,0 +1,112 @@
+use crate::error;
+use crate::error::{ImgProcError, ImgProcResult};
+use crate::image::{BaseImage, Image};
+
+/// Detections whose centers and radii both lie within this many pixels of a stronger detection
+/// are discarded as duplicates
+const HOUGH_CIRCLES_MIN_DIST: i64 = 2;
+
+/// Detects circles in a binary edge image (grayscale image where non-zero pixels are edges) using
+/// the Hough circle transform. Returns the detected circles as `(x, y, radius)`, ordered from the
+/// most to the least votes
+///
+/// Instead of voting along a full circle for every radius, each edge pixel only votes for the
+/// centers lying along its edge normal, which is estimated from the gradient structure tensor
+/// of its neighborhood
+///
+/// # Arguments
+///
+/// * `min_radius` - Must be at least 1
+/// * `max_radius` - Must be at least `min_radius`
+/// * `threshold` - The minimum number of votes for a circle to be detected
+pub fn hough_circles(input: &Image<u8>, min_radius: u32, max_radius: u32, threshold: u32) -> ImgProcResult<Vec<(u32, u32, u32)>> {
+    error::check_grayscale(input)?;
+    error::check_in_range(min_radius, 1, u32::MAX, "min_radius")?;
+    if max_radius < min_radius {
+        return Err(ImgProcError::InvalidArgError("max_radius must be at least min_radius".to_string()));
+    }
+
+    let (width, height) = input.info().wh();
+    let (w, h) = (width as i64, height as i64);
+    let is_edge = |x: i64, y: i64| -> f32 {
+        let x = x.clamp(0, w - 1) as u32;
+        let y = y.clamp(0, h - 1) as u32;
+        (input.get_pixel(x, y)[0] != 0) as u8 as f32
+    };
+
+    // Sobel gradients of the edge map
+    let mut grad = vec![(0.0, 0.0); (width * height) as usize];
+    for y in 0..h {
+        for x in 0..w {
+            let gx = is_edge(x + 1, y - 1) + 2.0 * is_edge(x + 1, y) + is_edge(x + 1, y + 1)
+                - is_edge(x - 1, y - 1) - 2.0 * is_edge(x - 1, y) - is_edge(x - 1, y + 1);
+            let gy = is_edge(x - 1, y + 1) + 2.0 * is_edge(x, y + 1) + is_edge(x + 1, y + 1)
+                - is_edge(x - 1, y - 1) - 2.0 * is_edge(x, y - 1) - is_edge(x + 1, y - 1);
+            grad[(y * w + x) as usize] = (gx, gy);
+        }
+    }
+
+    let num_radii = (max_radius - min_radius + 1) as usize;
+    let mut acc = vec![0u32; (width * height) as usize * num_radii];
+
+    for y in 0..h {
+        for x in 0..w {
+            if input.get_pixel(x as u32, y as u32)[0] == 0 {
+                continue;
+            }
+
+            // The gradient of an edge map points in opposite directions on either side of an
+            // edge, so use the (sign-invariant) structure tensor to find the normal direction
+            let (mut s_xx, mut s_yy, mut s_xy) = (0.0, 0.0, 0.0);
+            for j in (y - 2).max(0)..(y + 3).min(h) {
+                for i in (x - 2).max(0)..(x + 3).min(w) {
+                    let (gx, gy) = grad[(j * w + i) as usize];
+                    s_xx += gx * gx;
+                    s_yy += gy * gy;
+                    s_xy += gx * gy;
+                }
+            }
+
+            let theta = 0.5 * (2.0 * s_xy).atan2(s_xx - s_yy);
+            let (sin, cos) = theta.sin_cos();
+
+            for (r_i, r) in (min_radius..=max_radius).enumerate() {
+                for &sign in [-1.0, 1.0].iter() {
+                    let c_x = (x as f32 + sign * r as f32 * cos).round() as i64;
+                    let c_y = (y as f32 + sign * r as f32 * sin).round() as i64;
+
+                    if c_x >= 0 && c_y >= 0 && c_x < w && c_y < h {
+                        acc[(r_i as i64 * w * h + c_y * w + c_x) as usize] += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for (i, &votes) in acc.iter().enumerate() {
+        if votes >= threshold && votes > 0 {
+            let r_i = i as i64 / (w * h);
+            let rem = i as i64 % (w * h);
+            candidates.push((votes, rem % w, rem / w, r_i + min_radius as i64));
+        }
+    }
+
+    // Sort by votes, keeping raster order among ties
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.0));
+
+    let mut circles: Vec<(u32, u32, u32)> = Vec::new();
+    for (_, x, y, r) in candidates {
+        let duplicate = circles.iter().any(|&(c_x, c_y, c_r)| {
+            (c_x as i64 - x).abs() <= HOUGH_CIRCLES_MIN_DIST
+                && (c_y as i64 - y).abs() <= HOUGH_CIRCLES_MIN_DIST
+                && (c_r as i64 - r).abs() <= HOUGH_CIRCLES_MIN_DIST
+        });
+
+        if !duplicate {
+            circles.push((x as u32, y as u32, r as u32));
+        }
+    }
+
+    Ok(circles)
+}
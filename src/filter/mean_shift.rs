@@ -0,0 +1,89 @@
+use crate::{colorspace, error};
+use crate::enums::White;
+use crate::error::{ImgProcError, ImgProcResult};
+use crate::image::{BaseImage, Image};
+
+/// Shifts smaller than this (in joint spatial-color units) end the iteration for a pixel
+const CONVERGENCE: f32 = 0.1;
+
+/// Applies mean shift filtering using CIE LAB. Each pixel is repeatedly moved to the mean
+/// position and color of the pixels within `spatial_radius` of its current position and within
+/// `color_radius` of its current color, and takes the color it converges to. The result is a
+/// posterized image with flattened regions, useful as a first step in segmentation
+///
+/// # Arguments
+///
+/// * `spatial_radius` - Must be at least 1
+/// * `color_radius` - Must be positive
+/// * `max_iter` - Must be at least 1
+pub fn mean_shift(input: &Image<u8>, spatial_radius: u32, color_radius: f32, max_iter: u32) -> ImgProcResult<Image<u8>> {
+    if input.info().channels_non_alpha() != 3 {
+        return Err(ImgProcError::InvalidArgError("input is not an RGB or RGBA image".to_string()));
+    }
+    error::check_in_range(spatial_radius, 1, u32::MAX, "spatial_radius")?;
+    if color_radius <= 0.0 {
+        return Err(ImgProcError::InvalidArgError("color_radius must be positive".to_string()));
+    }
+    error::check_in_range(max_iter, 1, u32::MAX, "max_iter")?;
+
+    let (width, height) = input.info().wh();
+    let lab = colorspace::srgb_to_lab_f32(input, &White::D65);
+    let mut output = lab.clone();
+    let radius = spatial_radius as i64;
+    let color_radius_sq = color_radius * color_radius;
+
+    for y in 0..height {
+        for x in 0..width {
+            let (mut pos_x, mut pos_y) = (x as f32, y as f32);
+            let mut color = [0.0; 3];
+            color.copy_from_slice(&lab.get_pixel(x, y)[..3]);
+
+            for _ in 0..max_iter {
+                let (center_x, center_y) = (pos_x.round() as i64, pos_y.round() as i64);
+                let x_range = (center_x - radius).max(0)..=(center_x + radius).min(width as i64 - 1);
+                let y_range = (center_y - radius).max(0)..=(center_y + radius).min(height as i64 - 1);
+
+                let mut count = 0.0;
+                let (mut sum_x, mut sum_y) = (0.0, 0.0);
+                let mut sum_color = [0.0; 3];
+
+                for j in y_range {
+                    for i in x_range.clone() {
+                        let p = lab.get_pixel(i as u32, j as u32);
+                        let dist_sq: f32 = (0..3).map(|c| (p[c] - color[c]).powi(2)).sum();
+
+                        if dist_sq <= color_radius_sq {
+                            count += 1.0;
+                            sum_x += i as f32;
+                            sum_y += j as f32;
+                            for c in 0..3 {
+                                sum_color[c] += p[c];
+                            }
+                        }
+                    }
+                }
+
+                if count == 0.0 {
+                    break;
+                }
+
+                let (new_x, new_y) = (sum_x / count, sum_y / count);
+                let new_color = [sum_color[0] / count, sum_color[1] / count, sum_color[2] / count];
+                let shift_sq = (new_x - pos_x).powi(2) + (new_y - pos_y).powi(2)
+                    + (0..3).map(|c| (new_color[c] - color[c]).powi(2)).sum::<f32>();
+
+                pos_x = new_x;
+                pos_y = new_y;
+                color = new_color;
+
+                if shift_sq < CONVERGENCE * CONVERGENCE {
+                    break;
+                }
+            }
+
+            output.get_pixel_mut(x, y)[..3].copy_from_slice(&color);
+        }
+    }
+
+    Ok(colorspace::lab_to_srgb_f32(&output, &White::D65))
+}
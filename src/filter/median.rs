@@ -47,6 +47,53 @@ pub fn alpha_trimmed_mean_filter(input: &Image<u8>, radius: u32, alpha: u32) ->
     Ok(output)
 }
 
+/// Applies a vector median filter, where each output pixel is the pixel in the
+/// `(2 * radius + 1) x (2 * radius + 1)` kernel (clipped to the image) that minimizes the sum of
+/// the Euclidean distances between its non-alpha channels and those of every other pixel in the
+/// kernel. Unlike `median_filter()`, which takes the median of each channel separately, the output
+/// only contains colors present in the input. Each output pixel compares every pair of pixels in
+/// its kernel, so the cost per pixel grows with `(2 * radius + 1)^4`; small radii are recommended
+///
+/// # Arguments
+///
+/// * `radius` - Must be at least 1
+pub fn vector_median_filter(input: &Image<u8>, radius: u32) -> ImgProcResult<Image<u8>> {
+    error::check_in_range(radius, 1, u32::MAX, "radius")?;
+
+    let (width, height) = input.info().wh();
+    let channels = input.info().channels_non_alpha() as usize;
+    let mut output = Image::blank(input.info());
+    let mut window: Vec<&[u8]> = Vec::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            window.clear();
+            for j in y.saturating_sub(radius)..=y.saturating_add(radius).min(height - 1) {
+                for i in x.saturating_sub(radius)..=x.saturating_add(radius).min(width - 1) {
+                    window.push(input.get_pixel(i, j));
+                }
+            }
+
+            let dist_sum = |p: &[u8]| -> f32 {
+                window.iter().map(|q| {
+                    p[..channels].iter().zip(q[..channels].iter())
+                        .map(|(&a, &b)| (a as f32 - b as f32).powi(2))
+                        .sum::<f32>()
+                        .sqrt()
+                }).sum()
+            };
+
+            let median = window.iter()
+                .map(|&p| (dist_sum(p), p))
+                .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal))
+                .unwrap().1;
+            output.set_pixel(x, y, median);
+        }
+    }
+
+    Ok(output)
+}
+
 /*
  * The PartialHistograms struct:
  *
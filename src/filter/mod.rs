@@ -1,21 +1,46 @@
 //! A module for image filtering operations
 
 pub use self::bilateral::*;
+pub use self::chain::*;
 pub use self::edge::*;
+pub use self::flow::*;
+pub use self::hough::*;
+pub use self::mean_shift::*;
 pub use self::median::*;
 
 mod median;
 mod bilateral;
+mod chain;
 mod edge;
-
-use crate::{error, util};
-use crate::enums::Thresh;
-use crate::error::ImgProcResult;
-use crate::image::{BaseImage, Image, Number};
+mod flow;
+mod hough;
+mod mean_shift;
+
+use crate::{colorspace, error, image, util};
+use crate::enums::{ConvMode, Thresh, White};
+use crate::error::{ImgProcError, ImgProcResult};
+use crate::image::{BaseImage, Image, ImageInfo, Number};
 use crate::util::constants::{K_SHARPEN, K_UNSHARP_MASKING};
 
+#[cfg(feature = "fft")]
+use rustfft::num_complex::Complex;
+#[cfg(feature = "fft")]
+use crate::enums::FreqMode;
+
+/// Side length of the tiles processed by each thread in the multithreaded filters
 #[cfg(feature = "rayon")]
-use rayon::prelude::*;
+const TILE_SIZE: u32 = 64;
+
+/// Radius of the window used to compute the dark channel in [`dehaze()`](fn.dehaze.html)
+const DEHAZE_RADIUS: u32 = 7;
+
+/// Lower bound of the transmission in [`dehaze()`](fn.dehaze.html), which keeps dense haze from
+/// amplifying noise
+const DEHAZE_MIN_TRANSMISSION: f32 = 0.1;
+
+/// Kernels with at least this many elements are applied in the frequency domain by `convolve_2d`
+#[cfg(feature = "fft")]
+const FFT_MIN_KERNEL_LEN: u32 = 225;
 
 /////////////////////
 // Linear filtering
@@ -28,7 +53,7 @@ pub fn filter_1d(input: &Image<f32>, kernel: &[f32], is_vert: bool) -> ImgProcRe
     error::check_odd(kernel.len(), "kernel length")?;
 
     let (width, height, channels) = input.info().whc();
-    let mut output = Image::blank(input.info());
+    let mut output = Image::blank(input.info()).with_border(input.border_mode());
     let mut p_out = Vec::with_capacity(channels as usize);
 
     for y in 0..height {
@@ -48,24 +73,75 @@ pub fn filter_1d(input: &Image<f32>, kernel: &[f32], is_vert: bool) -> ImgProcRe
 pub fn filter_1d(input: &Image<f32>, kernel: &[f32], is_vert: bool) -> ImgProcResult<Image<f32>> {
     error::check_odd(kernel.len(), "kernel length")?;
 
-    let (width, height, channels, alpha) = input.info().whca();
+    Ok(util::par_tiles(input.info(), TILE_SIZE, |x_0, y_0, width, height| {
+        let mut data = Vec::with_capacity((width * height * input.info().channels as u32) as usize);
 
-    let data: Vec<Vec<f32>> = (0..input.info().size())
-        .into_par_iter()
-        .map(|i| {
-            let (x, y) = util::get_2d_coords(i, width);
-            util::apply_1d_kernel(&input.get_neighborhood_1d(x, y,kernel.len() as u32, is_vert), kernel).unwrap()
-        })
-        .collect();
+        for y in y_0..(y_0 + height) {
+            for x in x_0..(x_0 + width) {
+                data.extend(util::apply_1d_kernel(&input.get_neighborhood_1d(x, y, kernel.len() as u32, is_vert),
+                                                  kernel).unwrap());
+            }
+        }
+
+        data
+    }).with_border(input.border_mode()))
+}
+
+/// Applies a 1D filter with integer weights to a u8 image, where each output channel is the
+/// weighted sum of the input channels under `kernel` divided by `divisor`, rounded and clamped to
+/// [0, 255]. If `is_vert` is true, applies `kernel` as a vertical filter; otherwise applies
+/// `kernel` as a horizontal filter. Uses clamp padding for edge pixels
+///
+/// # Arguments
+///
+/// * `kernel` - Must have odd length
+/// * `divisor` - Must be positive
+pub fn filter_1d_u8(input: &Image<u8>, kernel: &[i32], divisor: i32, is_vert: bool) -> ImgProcResult<Image<u8>> {
+    error::check_odd(kernel.len(), "kernel length")?;
+    error::check_in_range(divisor, 1, i32::MAX, "divisor")?;
+
+    let (width, height, channels) = input.info().whc();
+    let channels = channels as usize;
+    let half = (kernel.len() / 2) as i64;
+    let mut output = Image::blank(input.info());
+    let mut sums = vec![0i32; channels];
+
+    for y in 0..height {
+        for x in 0..width {
+            sums.iter_mut().for_each(|sum| *sum = 0);
+
+            for (i, &weight) in kernel.iter().enumerate() {
+                let offset = i as i64 - half;
+                let (in_x, in_y) = if is_vert {
+                    (x, (y as i64 + offset).clamp(0, height as i64 - 1) as u32)
+                } else {
+                    ((x as i64 + offset).clamp(0, width as i64 - 1) as u32, y)
+                };
+
+                for (sum, &c) in sums.iter_mut().zip(input.get_pixel(in_x, in_y).iter()) {
+                    *sum += weight * c as i32;
+                }
+            }
+
+            for (c, &sum) in output.get_pixel_mut(x, y).iter_mut().zip(sums.iter()) {
+                *c = (sum + divisor / 2).div_euclid(divisor).clamp(0, 255) as u8;
+            }
+        }
+    }
 
-    Ok(Image::from_vec_of_vec(width, height, channels, alpha, data))
+    Ok(output)
 }
 
-/// Applies a separable linear filter by first applying `vert_kernel` and then `horz_kernel`
+/// Applies a separable linear filter by first applying `vert_kernel` and then `horz_kernel`. The
+/// kernels may have different lengths
+///
+/// # Arguments
+///
+/// * `vert_kernel` - Must have odd length
+/// * `horz_kernel` - Must have odd length
 pub fn separable_filter(input: &Image<f32>, vert_kernel: &[f32], horz_kernel: &[f32]) -> ImgProcResult<Image<f32>> {
     error::check_odd(vert_kernel.len(), "vert_kernel length")?;
     error::check_odd(horz_kernel.len(), "horz_kernel length")?;
-    error::check_equal(vert_kernel.len(), horz_kernel.len(), "kernel lengths")?;
 
     let vertical = filter_1d(input, vert_kernel, true)?;
     Ok(filter_1d(&vertical, horz_kernel, false)?)
@@ -78,18 +154,10 @@ pub fn unseparable_filter(input: &Image<f32>, kernel: &[f32]) -> ImgProcResult<I
     error::check_square(kernel.len() as f32, "kernel length")?;
 
     let size = (kernel.len() as f32).sqrt() as u32;
-    let (width, height, channels) = input.info().whc();
-    let mut output = Image::blank(input.info());
-    let mut p_out = Vec::with_capacity(channels as usize);
-
-    for y in 0..height {
-        for x in 0..width {
-            util::apply_2d_kernel(&input.get_neighborhood_2d(x, y, size), &mut p_out, kernel)?;
-            output.set_pixel(x, y, &p_out);
-        }
-    }
 
-    Ok(output)
+    Ok(input.map_neighborhood(size, |neighborhood, p_out| {
+        util::apply_2d_kernel(neighborhood, p_out, kernel).unwrap();
+    }))
 }
 
 /// Applies an unseparable linear filter
@@ -99,17 +167,196 @@ pub fn unseparable_filter(input: &Image<f32>, kernel: &[f32]) -> ImgProcResult<I
     error::check_square(kernel.len() as f32, "kernel length")?;
 
     let size = (kernel.len() as f32).sqrt() as u32;
+
+    Ok(util::par_tiles(input.info(), TILE_SIZE, |x_0, y_0, width, height| {
+        let mut data = Vec::with_capacity((width * height * input.info().channels as u32) as usize);
+
+        for y in y_0..(y_0 + height) {
+            for x in x_0..(x_0 + width) {
+                data.extend(util::apply_2d_kernel(&input.get_neighborhood_2d(x, y, size), kernel).unwrap());
+            }
+        }
+
+        data
+    }).with_border(input.border_mode()))
+}
+
+/// Convolves an image with the `kw x kh` kernel `kernel` (stored row by row), with the output size
+/// determined by `mode`. Unlike the other linear filters, the kernel is flipped, as in a true
+/// convolution
+///
+/// # Arguments
+///
+/// * `kernel` - Must have length `kw * kh`
+/// * `kw` - Must be odd if `mode` is `ConvMode::Same`, and at most the input width if `mode` is
+///   `ConvMode::Valid`
+/// * `kh` - Must be odd if `mode` is `ConvMode::Same`, and at most the input height if `mode` is
+///   `ConvMode::Valid`
+pub fn convolve_mode(input: &Image<f32>, kernel: &[f32], kw: u32, kh: u32, mode: ConvMode) -> ImgProcResult<Image<f32>> {
+    error::check_equal(kernel.len(), (kw * kh) as usize, "kernel length and kernel dimensions")?;
+
     let (width, height, channels, alpha) = input.info().whca();
+    let (w_out, h_out, offset_x, offset_y) = match mode {
+        ConvMode::Same => {
+            error::check_odd(kw, "kw")?;
+            error::check_odd(kh, "kh")?;
+            (width, height, (kw / 2) as i64, (kh / 2) as i64)
+        },
+        ConvMode::Valid => {
+            if kw > width || kh > height {
+                return Err(ImgProcError::InvalidArgError(format!("invalid kernel dimensions: \
+                    image is {}x{}, but kernel is {}x{}", width, height, kw, kh)));
+            }
+            (width - kw + 1, height - kh + 1, (kw - 1) as i64, (kh - 1) as i64)
+        },
+        ConvMode::Full => (width + kw - 1, height + kh - 1, 0, 0),
+    };
+
+    let mut output = Image::blank(ImageInfo::new(w_out, h_out, channels, alpha));
+    let mut p_out = vec![0.0; channels as usize];
+
+    for y in 0..h_out {
+        for x in 0..w_out {
+            p_out.iter_mut().for_each(|c| *c = 0.0);
+
+            for j in 0..kh {
+                for i in 0..kw {
+                    let mut in_x = x as i64 + offset_x - i as i64;
+                    let mut in_y = y as i64 + offset_y - j as i64;
+
+                    if mode == ConvMode::Same {
+                        in_x = in_x.clamp(0, width as i64 - 1);
+                        in_y = in_y.clamp(0, height as i64 - 1);
+                    } else if in_x < 0 || in_x >= width as i64 || in_y < 0 || in_y >= height as i64 {
+                        continue;
+                    }
+
+                    let weight = kernel[(j * kw + i) as usize];
+                    let p_in = input.get_pixel(in_x as u32, in_y as u32);
+                    for (c, val) in p_out.iter_mut().enumerate() {
+                        *val += weight * p_in[c];
+                    }
+                }
+            }
 
-    let data: Vec<Vec<f32>> = (0..input.info().size())
-        .into_par_iter()
-        .map(|i| {
-            let (x, y) = util::get_2d_coords(i, width);
-            util::apply_2d_kernel(&input.get_neighborhood_2d(x, y, size), kernel).unwrap()
-        })
-        .collect();
+            output.set_pixel(x, y, &p_out);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Convolves an image with the `kw x kh` kernel `kernel` (stored row by row) in the frequency
+/// domain. Produces the same output as `convolve_mode()` with `ConvMode::Same`, but is much faster
+/// for large kernels
+///
+/// # Arguments
+///
+/// * `kernel` - Must have length `kw * kh`
+/// * `kw` - Must be odd
+/// * `kh` - Must be odd
+#[cfg(feature = "fft")]
+pub fn convolve_fft(input: &Image<f32>, kernel: &[f32], kw: u32, kh: u32) -> ImgProcResult<Image<f32>> {
+    error::check_equal(kernel.len(), (kw * kh) as usize, "kernel length and kernel dimensions")?;
+    error::check_odd(kw, "kw")?;
+    error::check_odd(kh, "kh")?;
+
+    let (width, height, channels, _) = input.info().whca();
+    let (width, height, kw, kh) = (width as usize, height as usize, kw as usize, kh as usize);
+
+    // The input is padded by clamping to its edges, and the output is taken from the part of the
+    // circular convolution that does not wrap around
+    let (fft_w, fft_h) = (width + kw - 1, height + kh - 1);
+    let mut kernel_fft = vec![Complex::new(0.0, 0.0); fft_w * fft_h];
+    for y in 0..kh {
+        for x in 0..kw {
+            kernel_fft[y * fft_w + x] = Complex::new(kernel[y * kw + x], 0.0);
+        }
+    }
+    util::fft_2d(&mut kernel_fft, fft_w, fft_h, false);
+
+    let mut output = Image::blank(input.info());
+    let mut buf = vec![Complex::new(0.0, 0.0); fft_w * fft_h];
+    let scale = 1.0 / (fft_w * fft_h) as f32;
+
+    for c in 0..(channels as usize) {
+        for y in 0..fft_h {
+            let in_y = (y as i64 - (kh / 2) as i64).clamp(0, height as i64 - 1) as u32;
+            for x in 0..fft_w {
+                let in_x = (x as i64 - (kw / 2) as i64).clamp(0, width as i64 - 1) as u32;
+                buf[y * fft_w + x] = Complex::new(input.get_pixel(in_x, in_y)[c], 0.0);
+            }
+        }
+
+        util::fft_2d(&mut buf, fft_w, fft_h, false);
+        buf.iter_mut().zip(kernel_fft.iter()).for_each(|(val, k)| *val *= k);
+        util::fft_2d(&mut buf, fft_w, fft_h, true);
+
+        for y in 0..height {
+            for x in 0..width {
+                let val = buf[(y + kh - 1) * fft_w + x + kw - 1].re * scale;
+                output.get_pixel_mut(x as u32, y as u32)[c] = val;
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Convolves an image with the `kw x kh` kernel `kernel` (stored row by row), producing an output
+/// of the same size as the input. Large kernels are applied in the frequency domain if the `fft`
+/// feature is enabled, and all other kernels are applied directly
+///
+/// # Arguments
+///
+/// * `kernel` - Must have length `kw * kh`
+/// * `kw` - Must be odd
+/// * `kh` - Must be odd
+pub fn convolve_2d(input: &Image<f32>, kernel: &[f32], kw: u32, kh: u32) -> ImgProcResult<Image<f32>> {
+    #[cfg(feature = "fft")]
+    {
+        if kw * kh >= FFT_MIN_KERNEL_LEN {
+            return convolve_fft(input, kernel, kw, kh);
+        }
+    }
 
-    Ok(Image::from_vec_of_vec(width, height, channels, alpha, data))
+    convolve_mode(input, kernel, kw, kh, ConvMode::Same)
+}
+
+/// Deblurs an image that was blurred by the `psf_w x psf_h` point spread function `psf` (stored
+/// row by row) using `iterations` iterations of the Richardson-Lucy algorithm. The input is
+/// expected to be non-negative, and `psf` is normalized to sum to 1
+///
+/// # Arguments
+///
+/// * `psf` - Must have length `psf_w * psf_h`, with non-negative values and a positive sum
+/// * `psf_w` - Must be odd
+/// * `psf_h` - Must be odd
+pub fn deconvolve_richardson_lucy(input: &Image<f32>, psf: &[f32], psf_w: u32, psf_h: u32, iterations: u32) -> ImgProcResult<Image<f32>> {
+    error::check_equal(psf.len(), (psf_w * psf_h) as usize, "psf length and psf dimensions")?;
+    error::check_odd(psf_w, "psf_w")?;
+    error::check_odd(psf_h, "psf_h")?;
+
+    let sum: f32 = psf.iter().sum();
+    if psf.iter().any(|&v| v < 0.0) || sum <= 0.0 {
+        return Err(ImgProcError::InvalidArgError("psf must be non-negative with a positive sum".to_string()));
+    }
+
+    let psf: Vec<f32> = psf.iter().map(|v| v / sum).collect();
+    let psf_flipped: Vec<f32> = psf.iter().rev().copied().collect();
+    let mut estimate = input.clone();
+
+    for _ in 0..iterations {
+        let mut ratio = convolve_2d(&estimate, &psf, psf_w, psf_h)?;
+        ratio.data_mut().iter_mut().zip(input.data().iter())
+            .for_each(|(blurred, observed)| *blurred = observed / blurred.max(f32::EPSILON));
+
+        let correction = convolve_2d(&ratio, &psf_flipped, psf_w, psf_h)?;
+        estimate.data_mut().iter_mut().zip(correction.data().iter())
+            .for_each(|(val, corr)| *val *= corr);
+    }
+
+    Ok(estimate)
 }
 
 /// Applies a linear filter using the 2D `kernel`
@@ -156,20 +403,107 @@ pub fn gaussian_blur(input: &Image<f32>, size: u32, sigma: f32) -> ImgProcResult
     Ok(linear_filter(input, &kernel)?)
 }
 
+/// Returns a normalized 1D Gaussian kernel with standard deviation `sigma`, truncated at 3 `sigma`
+pub(crate) fn gaussian_kernel_1d(sigma: f32) -> Vec<f32> {
+    let k = (3.0 * sigma).ceil() as i32;
+    let kernel: Vec<f32> = (-k..=k).map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp()).collect();
+    let sum: f32 = kernel.iter().sum();
+
+    kernel.iter().map(|val| val / sum).collect()
+}
+
 ////////////////
 // Sharpening
 ////////////////
 
-/// Sharpens image
+/// Sharpens image. Output may contain values outside of the range of `input` - use
+/// [`Image::clamp()`](../image/struct.Image.html#method.clamp) before converting to `Image<u8>`
 pub fn sharpen(input: &Image<f32>) -> ImgProcResult<Image<f32>> {
     Ok(unseparable_filter(input, &K_SHARPEN)?)
 }
 
-/// Sharpens image by applying the unsharp masking kernel
+/// Sharpens image by applying the unsharp masking kernel. Output may contain values outside of
+/// the range of `input` - use [`Image::clamp()`](../image/struct.Image.html#method.clamp) before
+/// converting to `Image<u8>`
 pub fn unsharp_masking(input: &Image<f32>) -> ImgProcResult<Image<f32>> {
     Ok(unseparable_filter(input, &K_UNSHARP_MASKING)?)
 }
 
+/// Sharpens a grayscale or RGB(A) image by unsharp masking only where the Sobel gradient magnitude
+/// of its luminance exceeds `edge_threshold`, leaving flat areas unchanged so that noise is not
+/// amplified. Luminance is computed using the Rec. 709 coefficients for RGB(A) images. Output may
+/// contain values outside of the range of `input` - use
+/// [`Image::clamp()`](../image/struct.Image.html#method.clamp) before converting to `Image<u8>`.
+/// Alpha channels are unchanged
+///
+/// # Arguments
+///
+/// * `amount` - The strength of the sharpening. Must be non-negative
+/// * `sigma` - The standard deviation of the Gaussian blur of the unsharp mask. Must be positive
+/// * `edge_threshold` - The gradient magnitude above which pixels are sharpened. The Sobel
+///   operator is not normalized, so a step of height `h` has a magnitude of up to `4 * h`
+pub fn smart_sharpen(input: &Image<f32>, amount: f32, sigma: f32, edge_threshold: f32) -> ImgProcResult<Image<f32>> {
+    let channels = input.info().channels_non_alpha() as usize;
+    if channels != 1 && channels != 3 {
+        return Err(ImgProcError::InvalidArgError("input is not a grayscale or RGB image".to_string()));
+    }
+    error::check_non_neg(amount, "amount")?;
+    if sigma <= 0.0 {
+        return Err(ImgProcError::InvalidArgError("sigma must be positive".to_string()));
+    }
+
+    let (width, height) = input.info().wh();
+    let luma: Vec<f32> = (0..(input.info().size() as usize)).map(|i| {
+        let p = &input[i];
+        if channels == 1 { p[0] } else { 0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2] }
+    }).collect();
+    let gradient = sobel(&Image::from_vec(width, height, 1, false, luma))?;
+
+    let kernel = gaussian_kernel_1d(sigma);
+    let blurred = separable_filter(input, &kernel, &kernel)?;
+
+    let mut output = input.clone();
+    for i in 0..(input.info().size() as usize) {
+        if gradient[i][0] > edge_threshold {
+            for c in 0..channels {
+                output[i][c] += amount * (input[i][c] - blurred[i][c]);
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Sharpens an RGB(A) image by applying unsharp masking to the L* channel of the image in CIELAB,
+/// which avoids amplifying chroma noise. Alpha channels are unchanged
+///
+/// # Arguments
+///
+/// * `amount` - The strength of the sharpening. Must be non-negative
+/// * `sigma` - The standard deviation of the Gaussian blur of the unsharp mask. Must be positive
+pub fn sharpen_luma(input: &Image<u8>, amount: f32, sigma: f32) -> ImgProcResult<Image<u8>> {
+    error::check_channel_count(3, input.info().channels_non_alpha() as usize)?;
+    error::check_non_neg(amount, "amount")?;
+    if sigma <= 0.0 {
+        return Err(ImgProcError::InvalidArgError("sigma must be positive".to_string()));
+    }
+
+    let mut lab = colorspace::srgb_to_lab_f32(input, &White::D65);
+    let (width, height, channels) = lab.info().whc();
+    let luma: Vec<f32> = lab.data().iter().step_by(channels as usize).copied().collect();
+    let luma = Image::from_vec(width, height, 1, false, luma);
+
+    let kernel = gaussian_kernel_1d(sigma);
+    let blurred = separable_filter(&luma, &kernel, &kernel)?;
+
+    for i in 0..(luma.info().size() as usize) {
+        let sharpened = luma[i][0] + amount * (luma[i][0] - blurred[i][0]);
+        lab[i][0] = sharpened.clamp(0.0, 100.0);
+    }
+
+    Ok(colorspace::lab_to_srgb_f32(&lab, &White::D65))
+}
+
 //////////////////
 // Thresholding
 //////////////////
@@ -178,52 +512,106 @@ pub fn unsharp_masking(input: &Image<f32>) -> ImgProcResult<Image<f32>> {
 pub fn threshold(input: &Image<f32>, threshold: f32, max: f32, method: Thresh) -> ImgProcResult<Image<f32>> {
     error::check_grayscale(input)?;
 
+    Ok(input.map_channels_if_alpha(|channel| thresh_fn(channel, threshold, max, method), |a| a))
+}
+
+/// Performs a thresholding operation based on `method` on each channel of a color image, using
+/// `thresholds[c]` as the threshold for channel `c`. The alpha channel, if present, is unchanged
+///
+/// # Arguments
+///
+/// * `thresholds` - Must contain one threshold for each non-alpha channel of `input`
+pub fn threshold_color(input: &Image<f32>, thresholds: &[f32], max: f32, method: Thresh) -> ImgProcResult<Image<f32>> {
+    error::check_channel_count(input.info().channels_non_alpha(), thresholds.len())?;
+
+    Ok(input.map_pixels_if_alpha(|channels, p_out| {
+        for (channel, threshold) in channels.iter().zip(thresholds.iter()) {
+            p_out.push(thresh_fn(*channel, *threshold, max, method));
+        }
+    }, |a| a))
+}
+
+/// Performs a thresholding operation based on `method` on the luminance of each pixel, and
+/// returns the result as a single-channel mask. Luminance is computed using the Rec. 709
+/// coefficients for RGB(A) images, and is the gray value itself for grayscale images
+pub fn threshold_luma(input: &Image<f32>, threshold: f32, max: f32, method: Thresh) -> ImgProcResult<Image<f32>> {
+    let channels = input.info().channels_non_alpha();
+    if channels != 1 && channels != 3 {
+        return Err(ImgProcError::InvalidArgError("input is not a grayscale or RGB image".to_string()));
+    }
+
+    let (width, height) = input.info().wh();
+    let mut output = Image::blank(ImageInfo::new(width, height, 1, false));
+
+    for i in 0..(input.info().size() as usize) {
+        let p_in = &input[i];
+        let luma = if channels == 1 {
+            p_in[0]
+        } else {
+            0.2126 * p_in[0] + 0.7152 * p_in[1] + 0.0722 * p_in[2]
+        };
+
+        output.set_pixel_indexed(i, &[thresh_fn(luma, threshold, max, method)]);
+    }
+
+    Ok(output)
+}
+
+/// Binarizes a grayscale image using Niblack's method, where each pixel is set to 255 if it is
+/// greater than the threshold `mean + k * std_dev` of the `window x window` neighborhood centered
+/// at it, and to 0 otherwise. The alpha channel, if present, is unchanged
+///
+/// # Arguments
+///
+/// * `window` - Must be odd
+/// * `k` - Typically negative (e.g. -0.2) for dark text on a light background
+pub fn threshold_niblack(input: &Image<u8>, window: u32, k: f32) -> ImgProcResult<Image<u8>> {
+    threshold_local(input, window, |mean, std_dev| mean + k * std_dev)
+}
+
+/// Binarizes a grayscale image using Sauvola's method, where each pixel is set to 255 if it is
+/// greater than the threshold `mean * (1 + k * (std_dev / r - 1))` of the `window x window`
+/// neighborhood centered at it, and to 0 otherwise. Performs better than Niblack's method on
+/// documents with uneven illumination. The alpha channel, if present, is unchanged
+///
+/// # Arguments
+///
+/// * `window` - Must be odd
+/// * `k` - Typically 0.2 to 0.5
+/// * `r` - The dynamic range of the standard deviation, typically 128. Must be positive
+pub fn threshold_sauvola(input: &Image<u8>, window: u32, k: f32, r: f32) -> ImgProcResult<Image<u8>> {
+    if r <= 0.0 {
+        return Err(ImgProcError::InvalidArgError("r must be positive".to_string()));
+    }
+
+    threshold_local(input, window, |mean, std_dev| mean * (1.0 + k * (std_dev / r - 1.0)))
+}
+
+/// Binarizes a grayscale image using the per-pixel threshold `thresh_fn(mean, std_dev)` of the
+/// `window x window` neighborhood centered at each pixel
+fn threshold_local<F>(input: &Image<u8>, window: u32, thresh_fn: F) -> ImgProcResult<Image<u8>>
+    where F: Fn(f32, f32) -> f32 {
+    error::check_grayscale(input)?;
+
+    let input_f32: Image<f32> = input.clone().into();
+    let (mean, variance) = local_mean_variance(&input_f32, window)?;
+
+    let mut output = input.clone();
+    for i in 0..(input.info().size() as usize) {
+        let threshold = thresh_fn(mean[i][0], variance[i][0].sqrt());
+        output[i][0] = if input_f32[i][0] > threshold { 255 } else { 0 };
+    }
+
+    Ok(output)
+}
+
+fn thresh_fn(channel: f32, threshold: f32, max: f32, method: Thresh) -> f32 {
     match method {
-        Thresh::Binary => {
-            Ok(input.map_channels_if_alpha(|channel| {
-                if channel > threshold {
-                    max
-                } else {
-                    0.0
-                }
-            }, |a| a))
-        },
-        Thresh::BinaryInv => {
-            Ok(input.map_channels_if_alpha(|channel| {
-                if channel > threshold {
-                    0.0
-                } else {
-                    max
-                }
-            }, |a| a))
-        },
-        Thresh::Trunc => {
-            Ok(input.map_channels_if_alpha(|channel| {
-                if channel > threshold {
-                    threshold
-                } else {
-                    channel
-                }
-            }, |a| a))
-        },
-        Thresh::ToZero => {
-            Ok(input.map_channels_if_alpha(|channel| {
-                if channel > threshold {
-                    channel
-                } else {
-                    0.0
-                }
-            }, |a| a))
-        },
-        Thresh::ToZeroInv => {
-            Ok(input.map_channels_if_alpha(|channel| {
-                if channel > threshold {
-                    0.0
-                } else {
-                    channel
-                }
-            }, |a| a))
-        },
+        Thresh::Binary => if channel > threshold { max } else { 0.0 },
+        Thresh::BinaryInv => if channel > threshold { 0.0 } else { max },
+        Thresh::Trunc => if channel > threshold { threshold } else { channel },
+        Thresh::ToZero => if channel > threshold { channel } else { 0.0 },
+        Thresh::ToZeroInv => if channel > threshold { 0.0 } else { channel },
     }
 }
 
@@ -233,21 +621,274 @@ pub fn threshold(input: &Image<f32>, threshold: f32, max: f32, method: Thresh) -
 
 /// Returns the residual image of a filter operation
 pub fn residual<T: Number>(original: &Image<T>, filtered: &Image<T>) -> ImgProcResult<Image<T>> {
-    error::check_equal(original.info(), filtered.info(), "image dimensions")?;
+    error::check_info(original.info(), filtered.info())?;
+
+    image::zip_pixels(original, filtered, |p_1, p_2, p_out| {
+        p_out.extend(p_1.iter().zip(p_2.iter()).map(|(&a, &b)| a - b));
+    })
+}
+
+/// Computes the mean and variance of each channel over the `size x size` window centered at each
+/// pixel, using summed-area tables of the values and their squares. Returns a tuple containing
+/// the mean and variance images, in that order. Windows are clipped at the image boundaries
+///
+/// # Arguments
+///
+/// * `size` - Must be odd
+pub fn local_mean_variance(input: &Image<f32>, size: u32) -> ImgProcResult<(Image<f32>, Image<f32>)> {
+    error::check_odd(size, "size")?;
 
-    let (width, height, channels, alpha) = original.info().whca();
-    let mut data = Vec::new();
+    let (width, height, channels) = input.info().whc();
+    let channels = channels as usize;
+    let radius = size / 2;
+
+    // Values are shifted by the mean of each channel to reduce the cancellation error of the variance
+    let shift: Vec<f32> = (0..channels).map(|c| {
+        input.data().iter().skip(c).step_by(channels).sum::<f32>() / input.info().size() as f32
+    }).collect();
+
+    let mut values = Image::blank(ImageInfo::new(width, height, 2 * channels as u8, false));
+    for i in 0..(input.info().size() as usize) {
+        let p_out = &mut values[i];
+        for c in 0..channels {
+            let val = input[i][c] - shift[c];
+            p_out[c] = val;
+            p_out[channels + c] = val * val;
+        }
+    }
+    let table = util::generate_summed_area_table(&values);
+
+    let mut mean = Image::blank(input.info());
+    let mut variance = Image::blank(input.info());
 
     for y in 0..height {
         for x in 0..width {
-            let p_1 = original.get_pixel(x, y);
-            let p_2 = filtered.get_pixel(x, y);
+            let x_0 = x.saturating_sub(radius);
+            let y_0 = y.saturating_sub(radius);
+            let x_1 = std::cmp::min(x + radius, width - 1);
+            let y_1 = std::cmp::min(y + radius, height - 1);
+            let area = ((x_1 - x_0 + 1) * (y_1 - y_0 + 1)) as f32;
+            let sum = util::rectangular_intensity_sum(&table, x_0, y_0, x_1, y_1);
+
+            let p_mean = mean.get_pixel_mut(x, y);
+            for c in 0..channels {
+                p_mean[c] = sum[c] / area + shift[c];
+            }
 
-            for c in 0..(channels as usize) {
-                data.push(p_1[c] - p_2[c]);
+            let p_var = variance.get_pixel_mut(x, y);
+            for c in 0..channels {
+                let m = sum[c] / area;
+                p_var[c] = (sum[channels + c] / area - m * m).max(0.0);
             }
         }
     }
 
-    Ok(Image::from_slice(width, height, channels, alpha, &data))
-}
\ No newline at end of file
+    Ok((mean, variance))
+}
+
+/// Removes haze from an RGB(A) image using the dark channel prior. The dark channel of the image
+/// (the minimum over the color channels and a 15x15 window) is used to estimate the atmospheric
+/// light, taken from the brightest of the 0.1% of pixels with the largest dark channel values, and
+/// the transmission of each pixel, from which the scene radiance is recovered. The transmission is
+/// not refined, so some halos may remain around strong edges. The alpha channel, if present, is
+/// unchanged
+///
+/// # Arguments
+///
+/// * `omega` - The amount of haze to remove, where 1 removes all of it. Values slightly less
+///   than 1 (e.g. 0.95) keep distant objects looking distant. Must be between 0 and 1 (inclusive)
+pub fn dehaze(input: &Image<u8>, omega: f32) -> ImgProcResult<Image<u8>> {
+    error::check_channel_count(3, input.info().channels_non_alpha() as usize)?;
+    error::check_in_range(omega, 0.0, 1.0, "omega")?;
+
+    let (width, height) = input.info().wh();
+    let size = input.info().size() as usize;
+
+    let dark: Vec<f32> = (0..size)
+        .map(|i| input[i][..3].iter().copied().min().unwrap() as f32)
+        .collect();
+    let dark = min_filter(&dark, width, height, DEHAZE_RADIUS);
+
+    // Atmospheric light
+    let mut indices: Vec<usize> = (0..size).collect();
+    indices.sort_by(|&a, &b| dark[b].partial_cmp(&dark[a]).unwrap());
+    let brightest = indices.iter()
+        .take(std::cmp::max(size / 1000, 1))
+        .max_by_key(|&&i| input[i][..3].iter().map(|&c| c as u32).sum::<u32>())
+        .copied()
+        .unwrap_or(0);
+    let atmosphere: Vec<f32> = (0..3)
+        .map(|c| if size == 0 { 255.0 } else { (input[brightest][c] as f32).max(1.0) })
+        .collect();
+
+    // Transmission
+    let normalized: Vec<f32> = (0..size)
+        .map(|i| (0..3).map(|c| input[i][c] as f32 / atmosphere[c]).fold(f32::MAX, f32::min))
+        .collect();
+    let transmission = min_filter(&normalized, width, height, DEHAZE_RADIUS);
+
+    let mut output = input.clone();
+    for i in 0..size {
+        let t = (1.0 - omega * transmission[i]).max(DEHAZE_MIN_TRANSMISSION);
+        for c in 0..3 {
+            let radiance = (input[i][c] as f32 - atmosphere[c]) / t + atmosphere[c];
+            output[i][c] = radiance.round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Returns the minimum of the `(2 * radius + 1) x (2 * radius + 1)` window centered at each value
+/// of `data`, a `width x height` array in row-major order. Windows are clipped at the boundaries
+fn min_filter(data: &[f32], width: u32, height: u32, radius: u32) -> Vec<f32> {
+    let (w, h, r) = (width as usize, height as usize, radius as usize);
+
+    let mut rows = vec![0.0; data.len()];
+    for y in 0..h {
+        for x in 0..w {
+            rows[y * w + x] = data[(y * w + x.saturating_sub(r))..=(y * w + std::cmp::min(x + r, w - 1))]
+                .iter().copied().fold(f32::MAX, f32::min);
+        }
+    }
+
+    let mut output = vec![0.0; data.len()];
+    for y in 0..h {
+        for x in 0..w {
+            output[y * w + x] = (y.saturating_sub(r)..=std::cmp::min(y + r, h - 1))
+                .map(|j| rows[j * w + x])
+                .fold(f32::MAX, f32::min);
+        }
+    }
+
+    output
+}
+
+/// Computes a saliency map of an image using the spectral residual method, which highlights the
+/// regions that stand out from the rest of the image. The non-alpha channels are averaged, and
+/// the output is a grayscale image with values in the range [0, 1]
+///
+/// The residual is the log amplitude spectrum (`ln(1 + amplitude)`, so that frequencies with zero
+/// amplitude are well-defined) minus its 3x3 local average. The output is the squared magnitude
+/// of the inverse transform of the residual (with the original phase), smoothed by a Gaussian
+/// blur with a standard deviation of 3% of the larger image dimension
+#[cfg(feature = "fft")]
+pub fn saliency_spectral(input: &Image<f32>) -> ImgProcResult<Image<f32>> {
+    let (width, height) = input.info().wh();
+    let (w, h) = (width as usize, height as usize);
+    let channels = input.info().channels_non_alpha() as usize;
+
+    let mut spectrum: Vec<Complex<f32>> = (0..(w * h)).map(|i| {
+        let p = &input[i];
+        Complex::new(p[..channels].iter().sum::<f32>() / channels as f32, 0.0)
+    }).collect();
+    util::fft_2d(&mut spectrum, w, h, false);
+
+    let log_amp: Vec<f32> = spectrum.iter().map(|val| val.norm().ln_1p()).collect();
+    let log_amp = Image::from_vec(width, height, 1, false, log_amp);
+    let avg = separable_filter(&log_amp, &[1.0 / 3.0; 3], &[1.0 / 3.0; 3])?;
+
+    for (i, val) in spectrum.iter_mut().enumerate() {
+        let residual = log_amp[i][0] - avg[i][0];
+        *val = Complex::from_polar(residual.exp(), val.arg());
+    }
+    util::fft_2d(&mut spectrum, w, h, true);
+
+    let saliency = Image::from_vec(width, height, 1, false,
+                                   spectrum.iter().map(|val| val.norm_sqr()).collect());
+    let kernel = gaussian_kernel_1d((0.03 * width.max(height) as f32).max(0.5));
+    let mut output = separable_filter(&saliency, &kernel, &kernel)?;
+
+    let max = output.data().iter().cloned().fold(0.0, f32::max);
+    if max > 0.0 {
+        output.data_mut().iter_mut().for_each(|val| *val /= max);
+    }
+
+    Ok(output)
+}
+
+/// Filters an image in the frequency domain using the filter `mode`. Alpha channels are unchanged
+///
+/// # Arguments
+///
+/// * `cutoff` - The cutoff frequency, in cycles per pixel. Must be positive
+#[cfg(feature = "fft")]
+pub fn frequency_filter(input: &Image<f32>, mode: FreqMode, cutoff: f32) -> ImgProcResult<Image<f32>> {
+    if cutoff <= 0.0 {
+        return Err(ImgProcError::InvalidArgError("cutoff must be positive".to_string()));
+    }
+
+    let butterworth_low = |dist: f32, cutoff: f32| 1.0 / (1.0 + (dist / cutoff).powi(4));
+    let butterworth_high = |dist: f32, cutoff: f32| 1.0 - butterworth_low(dist, cutoff);
+
+    Ok(filter_spectrum(input, |u, v| {
+        let dist = (u * u + v * v).sqrt();
+
+        match mode {
+            FreqMode::IdealLowPass => (dist <= cutoff) as u8 as f32,
+            FreqMode::IdealHighPass => (dist >= cutoff) as u8 as f32,
+            FreqMode::IdealBandPass => (dist >= cutoff / 2.0 && dist <= cutoff * 2.0) as u8 as f32,
+            FreqMode::ButterworthLowPass => butterworth_low(dist, cutoff),
+            FreqMode::ButterworthHighPass => butterworth_high(dist, cutoff),
+            FreqMode::ButterworthBandPass => {
+                butterworth_low(dist, cutoff * 2.0) * butterworth_high(dist, cutoff / 2.0)
+            },
+        }
+    }))
+}
+
+/// Removes periodic noise from an image by zeroing out the frequencies around each notch. Alpha
+/// channels are unchanged
+///
+/// # Arguments
+///
+/// * `notches` - The notches to suppress as `(u, v, radius)`, where `u` and `v` are the horizontal
+///   and vertical frequencies in cycles per pixel, and `radius` is positive. The symmetric notch at
+///   `(-u, -v)` is also suppressed
+#[cfg(feature = "fft")]
+pub fn remove_periodic_noise(input: &Image<f32>, notches: &[(f32, f32, f32)]) -> ImgProcResult<Image<f32>> {
+    if notches.iter().any(|&(_, _, radius)| radius <= 0.0) {
+        return Err(ImgProcError::InvalidArgError("notch radii must be positive".to_string()));
+    }
+
+    Ok(filter_spectrum(input, |u, v| {
+        let in_notch = notches.iter().any(|&(n_u, n_v, radius)| {
+            (u - n_u).hypot(v - n_v) <= radius || (u + n_u).hypot(v + n_v) <= radius
+        });
+
+        (!in_notch) as u8 as f32
+    }))
+}
+
+/// Multiplies the spectrum of each non-alpha channel of `input` by `mask`, which is called with
+/// the horizontal and vertical frequencies (in cycles per pixel, in the range [-0.5, 0.5)) of
+/// each coefficient
+#[cfg(feature = "fft")]
+fn filter_spectrum<F>(input: &Image<f32>, mask: F) -> Image<f32>
+    where F: Fn(f32, f32) -> f32 {
+    let (width, height) = input.info().wh();
+    let (w, h) = (width as usize, height as usize);
+    let freq = |i: usize, len: usize| -> f32 {
+        if i < len.div_ceil(2) { i as f32 / len as f32 } else { i as f32 / len as f32 - 1.0 }
+    };
+
+    let weights: Vec<f32> = (0..(w * h)).map(|i| mask(freq(i % w, w), freq(i / w, h))).collect();
+    let mut output = input.clone();
+    let mut buf = vec![Complex::new(0.0, 0.0); w * h];
+
+    for c in 0..(input.info().channels_non_alpha() as usize) {
+        for (i, val) in buf.iter_mut().enumerate() {
+            *val = Complex::new(input[i][c], 0.0);
+        }
+
+        util::fft_2d(&mut buf, w, h, false);
+        buf.iter_mut().zip(weights.iter()).for_each(|(val, weight)| *val *= weight);
+        util::fft_2d(&mut buf, w, h, true);
+
+        for (i, val) in buf.iter().enumerate() {
+            output.data_mut()[i * input.info().channels as usize + c] = val.re / (w * h) as f32;
+        }
+    }
+
+    output
+}
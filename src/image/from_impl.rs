@@ -1,4 +1,6 @@
 use crate::image::Image;
+#[cfg(feature = "image-interop")]
+use crate::image::BaseImage;
 
 impl From<Image<u8>> for Image<f32> {
     fn from(img: Image<u8>) -> Image<f32> {
@@ -58,4 +60,55 @@ impl From<Image<f32>> for Image<u8> {
     fn from(img: Image<f32>) -> Image<u8> {
         img.map_channels(|channel| channel.round() as u8)
     }
-}
\ No newline at end of file
+}
+
+/// Converts an `image::DynamicImage` into an `Image<u8>`. Images that are not 8-bit Gray(A) or
+/// RGB(A) are converted to the 8-bit Gray(A) or RGB(A) equivalent first
+#[cfg(feature = "image-interop")]
+impl From<::image::DynamicImage> for Image<u8> {
+    fn from(img: ::image::DynamicImage) -> Image<u8> {
+        let color = img.color();
+
+        match (color.channel_count(), color.has_alpha()) {
+            (1, _) => {
+                let buf = img.into_luma8();
+                Image::from_vec(buf.width(), buf.height(), 1, false, buf.into_raw())
+            },
+            (2, _) => {
+                let buf = img.into_luma_alpha8();
+                Image::from_vec(buf.width(), buf.height(), 2, true, buf.into_raw())
+            },
+            (_, false) => {
+                let buf = img.into_rgb8();
+                Image::from_vec(buf.width(), buf.height(), 3, false, buf.into_raw())
+            },
+            (_, true) => {
+                let buf = img.into_rgba8();
+                Image::from_vec(buf.width(), buf.height(), 4, true, buf.into_raw())
+            },
+        }
+    }
+}
+
+/// Converts a Gray(A) or RGB(A) `Image<u8>` into an `image::DynamicImage`
+///
+/// # Panics
+///
+/// Panics if the number of channels is not 1, 2, 3, or 4
+#[cfg(feature = "image-interop")]
+impl From<Image<u8>> for ::image::DynamicImage {
+    fn from(img: Image<u8>) -> ::image::DynamicImage {
+        use ::image::{DynamicImage, ImageBuffer};
+
+        let (width, height, channels) = img.info().whc();
+        let data = img.data().to_vec();
+
+        match channels {
+            1 => DynamicImage::ImageLuma8(ImageBuffer::from_raw(width, height, data).unwrap()),
+            2 => DynamicImage::ImageLumaA8(ImageBuffer::from_raw(width, height, data).unwrap()),
+            3 => DynamicImage::ImageRgb8(ImageBuffer::from_raw(width, height, data).unwrap()),
+            4 => DynamicImage::ImageRgba8(ImageBuffer::from_raw(width, height, data).unwrap()),
+            _ => panic!("unsupported number of channels: {}", channels),
+        }
+    }
+}
@@ -65,13 +65,20 @@ mod pixel;
 mod from_impl;
 mod pixel_iter;
 
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::enums::Border;
 use crate::error;
+use crate::error::{ImgProcError, ImgProcResult};
 
-/// A struct representing an image
-#[derive(Debug, Clone, PartialEq)]
+/// A struct representing an image. Two images compare equal if they have the same information
+/// and pixel data, regardless of their border modes
+#[derive(Debug, Clone)]
 pub struct Image<T: Number> {
     info: ImageInfo,
     data: Vec<T>,
+    border_mode: Border,
 }
 
 /// A struct containing image information
@@ -102,6 +109,7 @@ std::marker::Copy
 + std::ops::DivAssign
 + std::ops::RemAssign
 + From<u8>
++ num_traits::NumCast
     where Self: std::marker::Sized {}
 
 impl<T> Number for T
@@ -122,7 +130,8 @@ impl<T> Number for T
     + std::ops::MulAssign
     + std::ops::DivAssign
     + std::ops::RemAssign
-    + From<u8> {}
+    + From<u8>
+    + num_traits::NumCast {}
 
 /// A trait for a base image
 pub trait BaseImage<T: Number> {
@@ -176,6 +185,16 @@ impl ImageInfo {
     pub fn full_size(&self) -> u32 {
         self.width * self.height * (self.channels as u32)
     }
+
+    /// Returns the size of the image (width * height) as a `usize`
+    pub fn size_usize(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    /// Returns the full size of the image (width * height * channels) as a `usize`
+    pub fn full_size_usize(&self) -> usize {
+        self.size_usize() * self.channels as usize
+    }
 }
 
 impl std::fmt::Display for ImageInfo {
@@ -190,6 +209,7 @@ impl<T: Number> Image<T> {
         Image {
             info: ImageInfo{ width, height, channels, alpha },
             data: data.to_vec(),
+            border_mode: Border::Replicate,
         }
     }
 
@@ -198,6 +218,7 @@ impl<T: Number> Image<T> {
         Image {
             info: ImageInfo{ width, height, channels, alpha },
             data,
+            border_mode: Border::Replicate,
         }
     }
 
@@ -211,6 +232,7 @@ impl<T: Number> Image<T> {
         Image {
             info: ImageInfo{ width, height, channels, alpha },
             data: data_vec,
+            border_mode: Border::Replicate,
         }
     }
 
@@ -224,6 +246,7 @@ impl<T: Number> Image<T> {
         Image {
             info: ImageInfo{ width, height, channels, alpha },
             data: data_vec,
+            border_mode: Border::Replicate,
         }
     }
 
@@ -232,6 +255,7 @@ impl<T: Number> Image<T> {
         Image {
             info,
             data: vec![0.into(); info.full_size() as usize],
+            border_mode: Border::Replicate,
         }
     }
 
@@ -240,6 +264,7 @@ impl<T: Number> Image<T> {
         Image {
             info,
             data: Vec::with_capacity(info.full_size() as usize),
+            border_mode: Border::Replicate,
         }
     }
 
@@ -248,6 +273,17 @@ impl<T: Number> Image<T> {
         ((y * self.info.width + x) * self.info.channels as u32) as usize
     }
 
+    /// Returns the number of pixels in the image, which is the range of valid indices for
+    /// `image[i]`
+    pub fn len(&self) -> usize {
+        self.info.size_usize()
+    }
+
+    /// Returns `true` if the image has no pixels
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// Returns all data as a slice
     pub fn data(&self) -> &[T] {
         &self.data[..]
@@ -263,6 +299,22 @@ impl<T: Number> Image<T> {
         &self[(y * self.info.width + x) as usize]
     }
 
+    /// Returns a slice representing the pixel located at `(x, y)`, or `None` if `x` or `y` is out
+    /// of bounds
+    pub fn get_pixel_checked(&self, x: u32, y: u32) -> Option<&[T]> {
+        if x < self.info.width && y < self.info.height {
+            Some(self.get_pixel_unchecked(x, y))
+        } else {
+            None
+        }
+    }
+
+    /// Returns a slice representing the pixel located at `(x, y)`, or `default` if `x` or `y` is
+    /// out of bounds
+    pub fn get_pixel_or<'a>(&'a self, x: u32, y: u32, default: &'a [T]) -> &'a [T] {
+        self.get_pixel_checked(x, y).unwrap_or(default)
+    }
+
     /// Returns a mutable slice representing the pixel located at `(x, y)`
     ///
     /// # Panics
@@ -302,9 +354,56 @@ impl<T: Number> Image<T> {
         SubImage::new(width, height, self.info.channels, self.info.alpha, data)
     }
 
+    /// Returns a smaller copy of the image containing every `step_x`th column and every `step_y`th
+    /// row, starting from the upper left hand corner. Pixels are only sampled, not averaged
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step_x` or `step_y` is 0
+    pub fn subsample(&self, step_x: u32, step_y: u32) -> Image<T> {
+        if step_x == 0 || step_y == 0 {
+            panic!("invalid step: step_x is {} and step_y is {}, but both must be at least 1", step_x, step_y);
+        }
+
+        let width = self.info.width.div_ceil(step_x);
+        let height = self.info.height.div_ceil(step_y);
+        let mut data = Vec::with_capacity((width * height * self.info.channels as u32) as usize);
+
+        for y in (0..self.info.height).step_by(step_y as usize) {
+            for x in (0..self.info.width).step_by(step_x as usize) {
+                data.extend_from_slice(self.get_pixel_unchecked(x, y));
+            }
+        }
+
+        Image {
+            info: ImageInfo { width, height, ..self.info },
+            data,
+            border_mode: self.border_mode,
+        }
+    }
+
+    /// Sets the border mode of the image, which determines how
+    /// [`get_neighborhood_1d()`](#method.get_neighborhood_1d) and
+    /// [`get_neighborhood_2d()`](#method.get_neighborhood_2d) (and therefore the filters built on
+    /// them) handle pixels beyond the edges of the image. Defaults to `Border::Replicate`
+    ///
+    /// Storing the border mode on the image lets existing filters honor it without any changes to
+    /// their signatures, but it only carries over to images derived from this one through
+    /// `clone()`, the `map_*()` methods and the linear filters; other functions that build their
+    /// output from a blank image return an image with the default border mode
+    pub fn with_border(mut self, border: Border) -> Self {
+        self.border_mode = border;
+        self
+    }
+
+    /// Returns the border mode of the image
+    pub fn border_mode(&self) -> Border {
+        self.border_mode
+    }
+
     /// Returns a `SubImage<T>` representing the row or column of pixels of length `size` centered at
     /// `(x, y)`. If `is_vert` is `true`, returns the column; otherwise, returns the row.
-    /// Uses clamp padding for edge pixels (edge pixels are repeated indefinitely)
+    /// Pixels beyond the edges of the image are handled according to the border mode of the image
     ///
     /// # Panics
     ///
@@ -318,10 +417,8 @@ impl<T: Number> Image<T> {
             let start_y = (y as i32) - (size as i32) / 2;
 
             for i in 0..size {
-                let mut curr_y = start_y + (i as i32);
-                if curr_y < 0 || curr_y >= self.info.height as i32 { curr_y = y as i32 };
-
-                data.push(self.get_pixel(x, curr_y as u32));
+                let curr_y = self.border_coord(start_y + (i as i32), self.info.height);
+                data.push(self.get_pixel(x, curr_y));
             }
 
             SubImage::new(1, size, self.info.channels, self.info.alpha, data)
@@ -329,10 +426,8 @@ impl<T: Number> Image<T> {
             let start_x = (x as i32) - (size as i32) / 2;
 
             for i in 0..size {
-                let mut curr_x = start_x + (i as i32);
-                if curr_x < 0 || curr_x >= self.info.width as i32 { curr_x = x as i32 };
-
-                data.push(self.get_pixel(curr_x as u32, y));
+                let curr_x = self.border_coord(start_x + (i as i32), self.info.width);
+                data.push(self.get_pixel(curr_x, y));
             }
 
             SubImage::new(size, 1, self.info.channels, self.info.alpha, data)
@@ -340,7 +435,8 @@ impl<T: Number> Image<T> {
     }
 
     /// Returns a `SubImage<T>` representing the "square" of pixels of side length `size` centered
-    /// at `(x, y)`. Uses clamp padding for edge pixels (edge pixels are repeated indefinitely)
+    /// at `(x, y)`. Pixels beyond the edges of the image are handled according to the border mode
+    /// of the image
     ///
     /// # Panics
     ///
@@ -354,19 +450,34 @@ impl<T: Number> Image<T> {
         let mut data = Vec::new();
         for i in 0..size {
             for j in 0..size {
-                let mut curr_x = start_x + (j as i32);
-                let mut curr_y = start_y + (i as i32);
-
-                if curr_x < 0 || curr_x >= self.info.width as i32 { curr_x = x as i32 };
-                if curr_y < 0 || curr_y >= self.info.height as i32 { curr_y = y as i32 };
+                let curr_x = self.border_coord(start_x + (j as i32), self.info.width);
+                let curr_y = self.border_coord(start_y + (i as i32), self.info.height);
 
-                data.push(self.get_pixel(curr_x as u32, curr_y as u32));
+                data.push(self.get_pixel(curr_x, curr_y));
             }
         }
 
         SubImage::new(size, size, self.info.channels, self.info.alpha, data)
     }
 
+    /// Maps the (possibly out of bounds) coordinate `coord` along an axis of length `len` into the
+    /// image according to the border mode of the image
+    fn border_coord(&self, coord: i32, len: u32) -> u32 {
+        let len = len as i32;
+        let coord = match self.border_mode {
+            Border::Replicate => coord.clamp(0, len - 1),
+            Border::Reflect if len == 1 => 0,
+            Border::Reflect => {
+                let period = 2 * (len - 1);
+                let c = coord.rem_euclid(period);
+                if c < len { c } else { period - c }
+            },
+            Border::Wrap => coord.rem_euclid(len),
+        };
+
+        coord as u32
+    }
+
     /// Replaces the pixel located at `(x, y)` with `pixel`
     ///
     /// # Panics
@@ -381,6 +492,57 @@ impl<T: Number> Image<T> {
         self.data[start..((self.info.channels as usize) + start)].clone_from_slice(pixel);
     }
 
+    /// Replaces the rectangular region of width `width` and height `height` with upper left hand
+    /// corner located at `(x, y)` with `data`, which contains the pixels of the region in row-major
+    /// order
+    pub fn set_region(&mut self, x: u32, y: u32, width: u32, height: u32, data: &[T]) -> ImgProcResult<()> {
+        let in_bounds = x.checked_add(width).is_some_and(|x_end| x_end <= self.info.width)
+            && y.checked_add(height).is_some_and(|y_end| y_end <= self.info.height);
+        if !in_bounds {
+            return Err(ImgProcError::InvalidArgError(format!("invalid region: image dimensions are \
+                {}x{}, but region extends to ({}, {})", self.info.width, self.info.height,
+                x as u64 + width as u64, y as u64 + height as u64)));
+        }
+        error::check_equal(data.len(), width as usize * height as usize * self.info.channels as usize,
+                           "data length and region size")?;
+
+        let row_len = (width * self.info.channels as u32) as usize;
+        for (j, row) in data.chunks_exact(row_len.max(1)).enumerate() {
+            let start = self.index(x, y + j as u32);
+            self.data[start..(start + row_len)].copy_from_slice(row);
+        }
+
+        Ok(())
+    }
+
+    /// Crops the image in place to the rectangle with upper left corner located at `(x, y)` with
+    /// width `width` and height `height`. The cropped pixels are moved within the existing buffer,
+    /// so the original and cropped images are never held in memory at the same time. The excess
+    /// capacity of the pixel buffer is released afterwards
+    pub fn crop_in_place(&mut self, x: u32, y: u32, width: u32, height: u32) -> ImgProcResult<()> {
+        if x.checked_add(width).is_none_or(|x_end| x_end > self.info.width) {
+            return Err(ImgProcError::InvalidArgError(format!("invalid width: image width is {} \
+                but x + width is {}", self.info.width, x as u64 + width as u64)));
+        } else if y.checked_add(height).is_none_or(|y_end| y_end > self.info.height) {
+            return Err(ImgProcError::InvalidArgError(format!("invalid height: image height is {} \
+                but y + height is {}", self.info.height, y as u64 + height as u64)));
+        }
+
+        // Rows only ever move towards the start of the buffer, so they can be copied in order
+        let row_len = (width * self.info.channels as u32) as usize;
+        for j in 0..height {
+            let start = self.index(x, y + j);
+            self.data.copy_within(start..(start + row_len), j as usize * row_len);
+        }
+
+        self.data.truncate(row_len * height as usize);
+        self.data.shrink_to_fit();
+        self.info.width = width;
+        self.info.height = height;
+
+        Ok(())
+    }
+
     /// Replaces the pixel at index `index` with `pixel`
     ///
     /// # Panics
@@ -414,6 +576,47 @@ impl<T: Number> Image<T> {
                 alpha: self.info.alpha
             },
             data,
+            border_mode: self.border_mode,
+        }
+    }
+
+    /// Folds every pixel into an accumulator, starting from `init` and applying `f` to the
+    /// accumulator and each pixel in order
+    pub fn fold_pixels<A, F>(&self, init: A, f: F) -> A
+        where F: Fn(A, &[T]) -> A {
+        self.data.chunks_exact(self.info.channels as usize).fold(init, f)
+    }
+
+    /// Folds every channel of every pixel into an accumulator, starting from `init` and applying
+    /// `f` to the accumulator and each channel in order
+    pub fn fold_channels<A, F>(&self, init: A, f: F) -> A
+        where F: Fn(A, T) -> A {
+        self.data.iter().fold(init, |acc, &channel| f(acc, channel))
+    }
+
+    /// Applies function `f` to each pixel in parallel
+    #[cfg(feature = "rayon")]
+    pub fn par_map_pixels<S: Number, F>(&self, f: F) -> Image<S>
+        where F: Fn(&[T], &mut Vec<S>) + Sync {
+        let data: Vec<S> = self.data.par_chunks(self.info.channels as usize)
+            .flat_map_iter(|p| {
+                let mut p_out = Vec::new();
+                f(p, &mut p_out);
+                p_out
+            })
+            .collect();
+
+        let channels = (data.len() as u32 / self.info.size()) as u8;
+
+        Image {
+            info: ImageInfo {
+                width: self.info.width,
+                height: self.info.height,
+                channels,
+                alpha: self.info.alpha
+            },
+            data,
+            border_mode: self.border_mode,
         }
     }
 
@@ -446,6 +649,37 @@ impl<T: Number> Image<T> {
                 alpha: self.info.alpha
             },
             data,
+            border_mode: self.border_mode,
+        }
+    }
+
+    /// Applies function `f` to the `size x size` neighborhood centered at each pixel (see
+    /// [`get_neighborhood_2d()`](#method.get_neighborhood_2d)), where `f` pushes the
+    /// resulting output pixel onto the `Vec<S>`
+    pub fn map_neighborhood<S: Number, F>(&self, size: u32, f: F) -> Image<S>
+        where F: Fn(&SubImage<T>, &mut Vec<S>) {
+        let (width, height) = self.info.wh();
+        let mut data = Vec::with_capacity(self.info.full_size() as usize);
+        let mut p_out = Vec::new();
+
+        for y in 0..height {
+            for x in 0..width {
+                f(&self.get_neighborhood_2d(x, y, size), &mut p_out);
+                data.append(&mut p_out);
+            }
+        }
+
+        let channels = (data.len() as u32 / self.info.size()) as u8;
+
+        Image {
+            info: ImageInfo {
+                width,
+                height,
+                channels,
+                alpha: self.info.alpha
+            },
+            data,
+            border_mode: self.border_mode,
         }
     }
 
@@ -461,6 +695,18 @@ impl<T: Number> Image<T> {
         Image {
             info: self.info,
             data,
+            border_mode: self.border_mode,
+        }
+    }
+
+    /// Applies function `f` to each channel of each pixel in parallel
+    #[cfg(feature = "rayon")]
+    pub fn par_map_channels<S: Number, F>(&self, f: F) -> Image<S>
+        where F: Fn(T) -> S + Sync {
+        Image {
+            info: self.info,
+            data: self.data.par_iter().map(|&channel| f(channel)).collect(),
+            border_mode: self.border_mode,
         }
     }
 
@@ -482,6 +728,7 @@ impl<T: Number> Image<T> {
         Image {
             info: self.info,
             data,
+            border_mode: self.border_mode,
         }
     }
 
@@ -550,6 +797,64 @@ impl<T: Number> Image<T> {
     }
 }
 
+impl Image<u8> {
+    /// Expands a grayscale image to RGB by replicating the gray value into each color channel. If
+    /// the image has an alpha channel, returns an RGBA image with the same alpha values
+    pub fn gray_to_rgb(&self) -> ImgProcResult<Image<u8>> {
+        error::check_grayscale(self)?;
+
+        Ok(self.map_pixels(|p, p_out| {
+            p_out.extend_from_slice(&[p[0], p[0], p[0]]);
+            if self.info.alpha {
+                p_out.push(p[1]);
+            }
+        }))
+    }
+
+    /// Expands a grayscale image to RGBA by replicating the gray value into each color channel.
+    /// If the image has an alpha channel, its alpha values are kept; otherwise, the alpha channel
+    /// is set to `alpha`
+    pub fn gray_to_rgba(&self, alpha: u8) -> ImgProcResult<Image<u8>> {
+        error::check_grayscale(self)?;
+
+        let mut output = self.map_pixels(|p, p_out| {
+            let a = if self.info.alpha { p[1] } else { alpha };
+            p_out.extend_from_slice(&[p[0], p[0], p[0], a]);
+        });
+        output.info.alpha = true;
+
+        Ok(output)
+    }
+
+    /// Swaps the first and third channels of each pixel in place, converting between RGB(A) and
+    /// BGR(A) channel orders
+    pub fn swap_rb_in_place(&mut self) -> ImgProcResult<()> {
+        if self.info.channels != 3 && self.info.channels != 4 {
+            return Err(ImgProcError::InvalidArgError(
+                format!("image must have 3 or 4 channels, but has {}", self.info.channels)));
+        }
+
+        for p in self.data.chunks_exact_mut(self.info.channels as usize) {
+            p.swap(0, 2);
+        }
+
+        Ok(())
+    }
+}
+
+impl Image<f32> {
+    /// Clamps all channels (including alpha channels) to the range [`min`, `max`]. This is
+    /// useful after operations such as sharpening, which can produce values outside of the valid
+    /// range of the image
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is greater than `max`, or if either is NaN
+    pub fn clamp(&mut self, min: f32, max: f32) {
+        self.apply_channels(|channel| channel.clamp(min, max));
+    }
+}
+
 impl<T: Number> BaseImage<T> for Image<T> {
     fn info(&self) -> ImageInfo {
         self.info
@@ -562,6 +867,12 @@ impl<T: Number> BaseImage<T> for Image<T> {
     }
 }
 
+impl<T: Number> PartialEq for Image<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.info == other.info && self.data == other.data
+    }
+}
+
 impl<T: Number> std::ops::Index<usize> for Image<T> {
     type Output = [T];
 
@@ -576,4 +887,36 @@ impl<T: Number> std::ops::IndexMut<usize> for Image<T> {
         let start = i * (self.info.channels as usize);
         &mut self.data[start..(start + self.info.channels as usize)]
     }
-}
\ No newline at end of file
+}
+
+/// Applies function `f` to each pair of corresponding pixels of `a` and `b`, which must have the
+/// same width and height, and returns the resulting image. The number of channels of the output
+/// is determined by the number of values `f` pushes for each pixel, and the output has an alpha
+/// channel if `a` does
+pub fn zip_pixels<T: Number, S: Number, O: Number, F>(a: &Image<T>, b: &Image<S>, f: F) -> ImgProcResult<Image<O>>
+    where F: Fn(&[T], &[S], &mut Vec<O>) {
+    // Only the dimensions of the images need to match, not their channels
+    error::check_info(ImageInfo { width: a.info.width, height: a.info.height, ..b.info }, b.info)?;
+
+    let size = a.info.size() as usize;
+    let mut data = Vec::with_capacity(size);
+    let mut p_out = Vec::new();
+
+    for i in 0..size {
+        f(&a[i], &b[i], &mut p_out);
+        data.append(&mut p_out);
+    }
+
+    let channels = data.len().checked_div(size).map_or(a.info.channels, |c| c as u8);
+
+    Ok(Image {
+        info: ImageInfo {
+            width: a.info.width,
+            height: a.info.height,
+            channels,
+            alpha: a.info.alpha,
+        },
+        data,
+        border_mode: a.border_mode,
+    })
+}
@@ -0,0 +1,895 @@
+//! JPEG encoding, and lossless rotation of JPEG files, which is performed on the quantized DCT
+//! coefficients so that the image is never decoded to pixels and recompressed
+
+use super::JpegOptions;
+use crate::enums::Subsampling;
+use crate::error::{ImgIoError, ImgIoResult};
+use crate::image::{BaseImage, Image};
+
+use std::f32::consts::{FRAC_1_SQRT_2, PI};
+
+/// The position in natural (row-major) order of each coefficient of a block in zigzag order
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5,
+    12, 19, 26, 33, 40, 48, 41, 34, 27, 20, 13, 6, 7, 14, 21, 28,
+    35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51,
+    58, 59, 52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+/// The luminance and chrominance quantization tables from section K.1 of the JPEG specification,
+/// in natural order, which correspond to a quality of 50
+const LUMA_QUANT: [u16; 64] = [
+    16, 11, 10, 16, 24, 40, 51, 61,
+    12, 12, 14, 19, 26, 58, 60, 55,
+    14, 13, 16, 24, 40, 57, 69, 56,
+    14, 17, 22, 29, 51, 87, 80, 62,
+    18, 22, 37, 56, 68, 109, 103, 77,
+    24, 35, 55, 64, 81, 104, 113, 92,
+    49, 64, 78, 87, 103, 121, 120, 101,
+    72, 92, 95, 98, 112, 100, 103, 99,
+];
+const CHROMA_QUANT: [u16; 64] = [
+    17, 18, 24, 47, 99, 99, 99, 99,
+    18, 21, 26, 66, 99, 99, 99, 99,
+    24, 26, 56, 99, 99, 99, 99, 99,
+    47, 66, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+];
+
+/// A JFIF APP0 segment with an aspect ratio of 1:1 and no thumbnail
+const JFIF_HEADER: [u8; 18] = [
+    0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00,
+];
+
+/// A 8x8 block of quantized DCT coefficients in natural order
+type Block = [i16; 64];
+
+/// A color component of a JPEG frame
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    quant_table: u8,
+    /// The width and height of the block grid, padded to a whole number of MCUs
+    blocks_w: usize,
+    blocks_h: usize,
+    blocks: Vec<Block>,
+}
+
+/// A Huffman table for decoding, as described in section F.2.2.3 of the JPEG specification
+struct HuffmanTable {
+    /// The smallest and largest code of each length, and the index into `values` of the value
+    /// of the smallest code of each length
+    min_code: [i32; 17],
+    max_code: [i32; 17],
+    val_ptr: [usize; 17],
+    values: Vec<u8>,
+}
+
+impl HuffmanTable {
+    fn new(counts: &[u8], values: &[u8]) -> Self {
+        let mut table = HuffmanTable {
+            min_code: [0; 17],
+            max_code: [-1; 17],
+            val_ptr: [0; 17],
+            values: values.to_vec(),
+        };
+
+        let (mut code, mut k) = (0, 0);
+        for len in 1..=16 {
+            let count = counts[len - 1] as usize;
+            table.val_ptr[len] = k;
+            table.min_code[len] = code;
+            if count > 0 {
+                table.max_code[len] = code + count as i32 - 1;
+            }
+
+            code = (code + count as i32) << 1;
+            k += count;
+        }
+
+        table
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> ImgIoResult<u8> {
+        let mut code = 0;
+        for len in 1..=16 {
+            code = (code << 1) | reader.bits(1)? as i32;
+            if code <= self.max_code[len] {
+                return self.values.get(self.val_ptr[len] + (code - self.min_code[len]) as usize)
+                    .copied()
+                    .ok_or_else(|| corrupt("invalid Huffman code"));
+            }
+        }
+
+        Err(corrupt("invalid Huffman code"))
+    }
+}
+
+/// Reads bits from entropy-coded data, skipping stuffed zero bytes
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    acc: u32,
+    len: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, n: u32) -> ImgIoResult<u32> {
+        while self.len < n {
+            let byte = *self.data.get(self.pos).ok_or_else(|| corrupt("truncated scan data"))?;
+            if byte == 0xFF {
+                if self.data.get(self.pos + 1) != Some(&0) {
+                    return Err(corrupt("unexpected marker in scan data"));
+                }
+                self.pos += 1;
+            }
+
+            self.pos += 1;
+            self.acc = (self.acc << 8) | byte as u32;
+            self.len += 8;
+        }
+
+        self.len -= n;
+        Ok((self.acc >> self.len) & ((1 << n) - 1))
+    }
+
+    /// Reads an `n` bit coefficient, as described in section F.2.2.1 of the JPEG specification
+    fn coefficient(&mut self, n: u32) -> ImgIoResult<i16> {
+        if n == 0 {
+            return Ok(0);
+        }
+
+        let val = self.bits(n)? as i32;
+        Ok((if val < 1 << (n - 1) { val - (1 << n) + 1 } else { val }) as i16)
+    }
+
+    /// Skips the remaining bits of the current byte and the following restart marker
+    fn restart(&mut self) -> ImgIoResult<()> {
+        self.len = 0;
+        match self.data.get(self.pos..(self.pos + 2)) {
+            Some(&[0xFF, marker]) if (0xD0..=0xD7).contains(&marker) => {
+                self.pos += 2;
+                Ok(())
+            },
+            _ => Err(corrupt("missing restart marker")),
+        }
+    }
+}
+
+/// A scan of a JPEG file, which codes the coefficients `ss..=se` (in zigzag order) of each block
+/// of the components with indices `comps`
+struct Scan {
+    comps: Vec<usize>,
+    ss: usize,
+    se: usize,
+}
+
+/// Writes bits of entropy-coded data, stuffing a zero byte after each 0xFF byte
+struct BitWriter {
+    data: Vec<u8>,
+    acc: u32,
+    len: u32,
+}
+
+impl BitWriter {
+    fn bits(&mut self, val: u32, n: u32) {
+        self.acc = (self.acc << n) | (val & ((1 << n) - 1));
+        self.len += n;
+
+        while self.len >= 8 {
+            self.len -= 8;
+            let byte = (self.acc >> self.len) as u8;
+            self.data.push(byte);
+            if byte == 0xFF {
+                self.data.push(0);
+            }
+        }
+    }
+
+    /// Pads the last byte with 1 bits
+    fn flush(&mut self) {
+        if self.len > 0 {
+            self.bits(0x7F, 8 - self.len);
+        }
+    }
+}
+
+/// Encodes `input` as a JPEG file. Gray(A) images are coded as a single component, and RGB(A)
+/// images as YCbCr with the chroma components downsampled according to `opts.subsampling`. Alpha
+/// channels are discarded. Progressive files are coded with a DC scan followed by a spectral
+/// selection scan for each band of AC coefficients
+pub(super) fn encode_image(input: &Image<u8>, opts: &JpegOptions) -> ImgIoResult<Vec<u8>> {
+    let (width, height) = (input.info().width as usize, input.info().height as usize);
+    if width == 0 || height == 0 || width > u16::MAX as usize || height > u16::MAX as usize {
+        return Err(ImgIoError::ImageWriteError(format!("JPEG dimensions must be between 1 and {}, \
+            but are {}x{}", u16::MAX, width, height)));
+    }
+
+    let pixels = input.data().chunks(input.info().channels as usize);
+    let planes: Vec<Vec<f32>> = match input.info().channels {
+        1 | 2 => vec![pixels.map(|p| p[0] as f32).collect()],
+        3 | 4 => {
+            let ycbcr: Vec<[f32; 3]> = pixels.map(|p| {
+                let (r, g, b) = (p[0] as f32, p[1] as f32, p[2] as f32);
+                [0.299 * r + 0.587 * g + 0.114 * b,
+                 -0.168736 * r - 0.331264 * g + 0.5 * b + 128.0,
+                 0.5 * r - 0.418688 * g - 0.081312 * b + 128.0]
+            }).collect();
+            (0..3).map(|c| ycbcr.iter().map(|p| p[c]).collect()).collect()
+        },
+        _ => return Err(ImgIoError::UnsupportedColorTypeError("unsupported color type".to_string())),
+    };
+
+    // Only the luma component is sampled more than once per MCU
+    let (h_max, v_max) = match opts.subsampling {
+        _ if planes.len() == 1 => (1, 1),
+        Subsampling::S444 => (1, 1),
+        Subsampling::S422 => (2, 1),
+        Subsampling::S420 => (2, 2),
+    };
+
+    let quant_tables = vec![(0, scale_quant_table(&LUMA_QUANT, opts.quality)),
+                            (1, scale_quant_table(&CHROMA_QUANT, opts.quality))];
+    let mut components: Vec<Component> = (0..planes.len()).map(|c| Component {
+        id: c as u8 + 1,
+        h: if c == 0 { h_max } else { 1 },
+        v: if c == 0 { v_max } else { 1 },
+        quant_table: if c == 0 { 0 } else { 1 },
+        blocks_w: 0,
+        blocks_h: 0,
+        blocks: Vec::new(),
+    }).collect();
+
+    let (mcus_x, mcus_y) = mcu_counts(&components, width, height);
+    for (c, plane) in components.iter_mut().zip(planes.iter()) {
+        c.blocks_w = mcus_x * c.h;
+        c.blocks_h = mcus_y * c.v;
+
+        // The natural order quantization table of the component
+        let mut quant = [0; 64];
+        for (&n, &q) in ZIGZAG.iter().zip(quant_tables[c.quant_table as usize].1.iter()) {
+            quant[n] = q;
+        }
+
+        // Each sample is the mean of the pixels it covers, and the image is padded to a whole
+        // number of MCUs by repeating its last row and column
+        let (s_x, s_y) = (h_max / c.h, v_max / c.v);
+        c.blocks = (0..(c.blocks_w * c.blocks_h)).map(|k| {
+            let (b_x, b_y) = (k % c.blocks_w, k / c.blocks_w);
+            let mut samples = [0.0; 64];
+            for (n, sample) in samples.iter_mut().enumerate() {
+                let (x0, y0) = ((b_x * 8 + n % 8) * s_x, (b_y * 8 + n / 8) * s_y);
+                let sum: f32 = (y0..(y0 + s_y))
+                    .flat_map(|y| (x0..(x0 + s_x)).map(move |x| (x, y)))
+                    .map(|(x, y)| plane[y.min(height - 1) * width + x.min(width - 1)])
+                    .sum();
+                *sample = sum / (s_x * s_y) as f32 - 128.0;
+            }
+
+            let coefs = fdct(&samples);
+            let mut block = [0; 64];
+            for n in 0..64 {
+                block[n] = (coefs[n] / quant[n] as f32).round() as i16;
+            }
+            block
+        }).collect();
+    }
+
+    let all: Vec<usize> = (0..components.len()).collect();
+    let (sof, scans) = if opts.progressive {
+        let mut scans = vec![Scan { comps: all, ss: 0, se: 0 }, Scan { comps: vec![0], ss: 1, se: 5 }];
+        scans.extend((1..components.len()).map(|c| Scan { comps: vec![c], ss: 1, se: 63 }));
+        scans.push(Scan { comps: vec![0], ss: 6, se: 63 });
+        (0xC2, scans)
+    } else {
+        (0xC0, vec![Scan { comps: all, ss: 0, se: 63 }])
+    };
+
+    Ok(encode(sof, width, height, &[&JFIF_HEADER], &quant_tables, &components, &scans))
+}
+
+/// Scales the quantization table `table` in natural order to the quality `quality` from 1 to 100
+/// as in the IJG implementation, and returns it in zigzag order
+fn scale_quant_table(table: &[u16; 64], quality: u8) -> Vec<u16> {
+    let quality = quality.clamp(1, 100) as u32;
+    let scale = if quality < 50 { 5000 / quality } else { 200 - 2 * quality };
+
+    ZIGZAG.iter().map(|&n| ((table[n] as u32 * scale + 50) / 100).clamp(1, 255) as u16).collect()
+}
+
+/// Computes the two-dimensional DCT of the level-shifted samples of a block in natural order, as
+/// described in section A.3.3 of the JPEG specification
+fn fdct(samples: &[f32; 64]) -> [f32; 64] {
+    let mut cos = [0.0; 64];
+    for (k, val) in cos.iter_mut().enumerate() {
+        let (x, u) = (k / 8, k % 8);
+        *val = ((2 * x + 1) as f32 * u as f32 * PI / 16.0).cos();
+    }
+    let scale = |u: usize| if u == 0 { FRAC_1_SQRT_2 } else { 1.0 };
+
+    // Transform the rows, then the columns
+    let mut rows = [0.0; 64];
+    for y in 0..8 {
+        for u in 0..8 {
+            rows[y * 8 + u] = (0..8).map(|x| samples[y * 8 + x] * cos[x * 8 + u]).sum();
+        }
+    }
+
+    let mut out = [0.0; 64];
+    for v in 0..8 {
+        for u in 0..8 {
+            let sum: f32 = (0..8).map(|y| rows[y * 8 + u] * cos[y * 8 + v]).sum();
+            out[v * 8 + u] = 0.25 * scale(u) * scale(v) * sum;
+        }
+    }
+
+    out
+}
+
+/// Rotates the JPEG file `bytes` `turns` times by 90 degrees clockwise by transforming its DCT
+/// coefficients. Only baseline and extended sequential Huffman-coded JPEGs with 8-bit samples are
+/// supported. Partial MCUs along the edges that would become the top or left edges of the rotated
+/// image are trimmed
+pub(super) fn rotate(bytes: &[u8], turns: u8) -> ImgIoResult<Vec<u8>> {
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return Err(ImgIoError::UnsupportedFileFormatError("not a JPEG file".to_string()));
+    }
+
+    let mut metadata = Vec::new();
+    let mut quant_tables: Vec<(u8, Vec<u16>)> = Vec::new();
+    // The DC and AC Huffman tables
+    let mut huffman_tables: [[Option<HuffmanTable>; 4]; 2] = Default::default();
+    let mut frame: Option<(u8, usize, usize)> = None;
+    let mut components: Vec<Component> = Vec::new();
+    let mut restart_interval = 0;
+    let mut scanned = false;
+
+    let mut i = 2;
+    loop {
+        // Markers may be preceded by any number of fill bytes
+        while bytes.get(i) == Some(&0xFF) && bytes.get(i + 1) == Some(&0xFF) {
+            i += 1;
+        }
+        if bytes.get(i) != Some(&0xFF) || i + 1 >= bytes.len() {
+            return Err(corrupt("expected a marker"));
+        }
+
+        let marker = bytes[i + 1];
+        if marker == 0xD9 {
+            break;
+        }
+        if i + 4 > bytes.len() {
+            return Err(corrupt("truncated segment"));
+        }
+        let len = u16::from_be_bytes([bytes[i + 2], bytes[i + 3]]) as usize;
+        let data = bytes.get((i + 4)..(i + 2 + len)).ok_or_else(|| corrupt("truncated segment"))?;
+        i += 2 + len;
+
+        match marker {
+            // Application segments and comments
+            0xE0..=0xEF | 0xFE => metadata.push(&bytes[(i - 2 - len)..i]),
+            // Quantization tables
+            0xDB => {
+                let mut j = 0;
+                while j < data.len() {
+                    let (precision, id) = (data[j] >> 4, data[j] & 0x0F);
+                    let size = if precision == 0 { 64 } else { 128 };
+                    let table = data.get((j + 1)..(j + 1 + size)).ok_or_else(|| corrupt("truncated DQT segment"))?;
+                    let table = if precision == 0 {
+                        table.iter().map(|&q| q as u16).collect()
+                    } else {
+                        table.chunks(2).map(|q| u16::from_be_bytes([q[0], q[1]])).collect()
+                    };
+
+                    quant_tables.retain(|(t, _)| *t & 0x0F != id);
+                    quant_tables.push((data[j], table));
+                    j += 1 + size;
+                }
+            },
+            // Huffman tables
+            0xC4 => {
+                let mut j = 0;
+                while j + 17 <= data.len() {
+                    let (class, id) = ((data[j] >> 4) as usize & 0x01, (data[j] & 0x03) as usize);
+                    let counts = &data[(j + 1)..(j + 17)];
+                    let num = counts.iter().map(|&c| c as usize).sum::<usize>();
+                    let values = data.get((j + 17)..(j + 17 + num)).ok_or_else(|| corrupt("truncated DHT segment"))?;
+
+                    huffman_tables[class][id] = Some(HuffmanTable::new(counts, values));
+                    j += 17 + num;
+                }
+            },
+            // Restart interval
+            0xDD if data.len() >= 2 => restart_interval = u16::from_be_bytes([data[0], data[1]]) as usize,
+            // Baseline and extended sequential Huffman-coded frames
+            0xC0 | 0xC1 => {
+                if data.len() < 6 || data[0] != 8 {
+                    return Err(unsupported());
+                }
+
+                let height = u16::from_be_bytes([data[1], data[2]]) as usize;
+                let width = u16::from_be_bytes([data[3], data[4]]) as usize;
+                let num = data[5] as usize;
+                if width == 0 || height == 0 || num == 0 || data.len() < 6 + 3 * num {
+                    return Err(corrupt("invalid frame header"));
+                }
+
+                components = data[6..(6 + 3 * num)].chunks(3).map(|c| Component {
+                    id: c[0],
+                    h: (c[1] >> 4) as usize,
+                    v: (c[1] & 0x0F) as usize,
+                    quant_table: c[2],
+                    blocks_w: 0,
+                    blocks_h: 0,
+                    blocks: Vec::new(),
+                }).collect();
+                if components.iter().any(|c| !(1..=4).contains(&c.h) || !(1..=4).contains(&c.v)) {
+                    return Err(corrupt("invalid sampling factors"));
+                }
+
+                let (mcus_x, mcus_y) = mcu_counts(&components, width, height);
+                for c in components.iter_mut() {
+                    c.blocks_w = mcus_x * c.h;
+                    c.blocks_h = mcus_y * c.v;
+                    c.blocks = vec![[0; 64]; c.blocks_w * c.blocks_h];
+                }
+
+                frame = Some((marker, width, height));
+            },
+            // Other frame types
+            0xC2 | 0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => return Err(unsupported()),
+            // Start of scan
+            0xDA => {
+                let (_, width, height) = frame.ok_or_else(|| corrupt("scan before frame header"))?;
+                let mut reader = BitReader { data: &bytes[i..], pos: 0, acc: 0, len: 0 };
+                decode_scan(data, &mut reader, &mut components, &huffman_tables, width, height,
+                            restart_interval)?;
+
+                i += reader.pos;
+                scanned = true;
+            },
+            _ => {},
+        }
+    }
+
+    let (sof, width, height) = frame.ok_or_else(|| corrupt("missing frame header"))?;
+    if !scanned {
+        return Err(corrupt("missing scan"));
+    }
+
+    // Trim partial MCUs along the edges that end up on the top or left edges
+    let turns = turns % 4;
+    let h_max = components.iter().map(|c| c.h).max().unwrap();
+    let v_max = components.iter().map(|c| c.v).max().unwrap();
+    let trim_w = if turns >= 2 { width - width % (8 * h_max) } else { width };
+    let trim_h = if turns == 1 || turns == 2 { height - height % (8 * v_max) } else { height };
+    if trim_w == 0 || trim_h == 0 {
+        return Err(ImgIoError::OtherError("image is too small to rotate losslessly".to_string()));
+    }
+
+    let (out_w, out_h) = if turns % 2 == 1 { (trim_h, trim_w) } else { (trim_w, trim_h) };
+    let mut rotated: Vec<Component> = components.iter().map(|c| {
+        let (h, v) = if turns % 2 == 1 { (c.v, c.h) } else { (c.h, c.v) };
+        Component { id: c.id, h, v, quant_table: c.quant_table, blocks_w: 0, blocks_h: 0, blocks: Vec::new() }
+    }).collect();
+
+    let (mcus_x, mcus_y) = mcu_counts(&rotated, out_w, out_h);
+    for (out, c) in rotated.iter_mut().zip(components.iter()) {
+        out.blocks_w = mcus_x * out.h;
+        out.blocks_h = mcus_y * out.v;
+
+        // The number of block columns and rows of the component that are kept
+        let cols = trim_w * c.h / h_max / 8;
+        let rows = trim_h * c.v / v_max / 8;
+
+        out.blocks = (0..(out.blocks_w * out.blocks_h)).map(|k| {
+            let (x, y) = (k % out.blocks_w, k / out.blocks_w);
+            let src = match turns {
+                1 => Some(y).zip(rows.checked_sub(x + 1)),
+                2 => cols.checked_sub(x + 1).zip(rows.checked_sub(y + 1)),
+                3 => cols.checked_sub(y + 1).zip(Some(x)),
+                _ => Some((x, y)),
+            };
+
+            match src {
+                Some((x, y)) if x < c.blocks_w && y < c.blocks_h => rotate_block(&c.blocks[y * c.blocks_w + x], turns),
+                _ => [0; 64],
+            }
+        }).collect();
+    }
+
+    // Coefficients are transposed by odd numbers of turns, so the quantization tables must be too
+    if turns % 2 == 1 {
+        for (_, table) in quant_tables.iter_mut() {
+            let mut transposed = vec![0; 64];
+            for (k, &n) in ZIGZAG.iter().enumerate() {
+                let t = (n % 8) * 8 + n / 8;
+                transposed[ZIGZAG.iter().position(|&m| m == t).unwrap()] = table[k];
+            }
+            *table = transposed;
+        }
+    }
+
+    let scan = Scan { comps: (0..rotated.len()).collect(), ss: 0, se: 63 };
+    Ok(encode(sof, out_w, out_h, &metadata, &quant_tables, &rotated, &[scan]))
+}
+
+/// Returns the number of MCUs in each row and column of an interleaved scan of `components`
+fn mcu_counts(components: &[Component], width: usize, height: usize) -> (usize, usize) {
+    let h_max = components.iter().map(|c| c.h).max().unwrap();
+    let v_max = components.iter().map(|c| c.v).max().unwrap();
+
+    (width.div_ceil(8 * h_max), height.div_ceil(8 * v_max))
+}
+
+/// Returns the blocks of each component of a scan in the order in which they are coded, as the
+/// index of the component and the index of the block in it. `comps` are the indices of the
+/// components in the scan
+fn scan_order(components: &[Component], comps: &[usize], width: usize, height: usize) -> Vec<(usize, usize)> {
+    let mut order = Vec::new();
+
+    if let [c] = comps {
+        // Non-interleaved scans only code the blocks that overlap the image
+        let comp = &components[*c];
+        let h_max = components.iter().map(|c| c.h).max().unwrap();
+        let v_max = components.iter().map(|c| c.v).max().unwrap();
+        let cols = (width * comp.h).div_ceil(h_max).div_ceil(8);
+        let rows = (height * comp.v).div_ceil(v_max).div_ceil(8);
+
+        for y in 0..rows {
+            for x in 0..cols {
+                order.push((*c, y * comp.blocks_w + x));
+            }
+        }
+    } else {
+        let (mcus_x, mcus_y) = mcu_counts(components, width, height);
+        for m_y in 0..mcus_y {
+            for m_x in 0..mcus_x {
+                for &c in comps.iter() {
+                    let comp = &components[c];
+                    for v in 0..comp.v {
+                        for h in 0..comp.h {
+                            order.push((c, (m_y * comp.v + v) * comp.blocks_w + m_x * comp.h + h));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    order
+}
+
+/// Decodes a sequential Huffman-coded scan with header `header` into the blocks of `components`,
+/// using the DC and AC Huffman tables `huffman_tables`
+fn decode_scan(header: &[u8], reader: &mut BitReader, components: &mut [Component],
+               huffman_tables: &[[Option<HuffmanTable>; 4]; 2], width: usize, height: usize,
+               restart_interval: usize) -> ImgIoResult<()> {
+    let num = *header.first().ok_or_else(|| corrupt("invalid scan header"))? as usize;
+    if header.len() < 4 + 2 * num {
+        return Err(corrupt("invalid scan header"));
+    }
+
+    let mut comps = Vec::with_capacity(num);
+    let mut tables = Vec::with_capacity(num);
+    for s in header[1..(1 + 2 * num)].chunks(2) {
+        let c = components.iter().position(|c| c.id == s[0]).ok_or_else(|| corrupt("unknown scan component"))?;
+        let dc = huffman_tables[0][(s[1] >> 4) as usize & 0x03].as_ref().ok_or_else(|| corrupt("missing Huffman table"))?;
+        let ac = huffman_tables[1][(s[1] & 0x03) as usize].as_ref().ok_or_else(|| corrupt("missing Huffman table"))?;
+        comps.push(c);
+        tables.push((dc, ac));
+    }
+
+    let order = scan_order(components, &comps, width, height);
+    let mcu_len = if num == 1 {
+        1
+    } else {
+        comps.iter().map(|&c| components[c].h * components[c].v).sum()
+    };
+
+    let mut preds = vec![0i16; components.len()];
+    for (k, &(c, b)) in order.iter().enumerate() {
+        if restart_interval > 0 && k > 0 && k % (restart_interval * mcu_len) == 0 {
+            reader.restart()?;
+            preds.iter_mut().for_each(|p| *p = 0);
+        }
+
+        let (dc, ac) = tables[comps.iter().position(|&s| s == c).unwrap()];
+        let block = &mut components[c].blocks[b];
+
+        let size = dc.decode(reader)? as u32;
+        preds[c] = preds[c].wrapping_add(reader.coefficient(size)?);
+        block[0] = preds[c];
+
+        let mut z = 1;
+        while z < 64 {
+            let symbol = ac.decode(reader)?;
+            let (run, size) = ((symbol >> 4) as usize, (symbol & 0x0F) as u32);
+            if size == 0 {
+                if run != 15 {
+                    break;
+                }
+                z += 16;
+                continue;
+            }
+
+            z += run;
+            if z >= 64 {
+                return Err(corrupt("invalid AC coefficient index"));
+            }
+            block[ZIGZAG[z]] = reader.coefficient(size)?;
+            z += 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Rotates the coefficients of `block` `turns` times by 90 degrees clockwise. Rotating by 90
+/// degrees is a transpose followed by a horizontal flip, which negates the odd horizontal
+/// frequencies
+fn rotate_block(block: &Block, turns: u8) -> Block {
+    let mut out = [0; 64];
+    for v in 0..8 {
+        for u in 0..8 {
+            let (coef, negate) = match turns {
+                1 => (block[u * 8 + v], u % 2 == 1),
+                2 => (block[v * 8 + u], (u + v) % 2 == 1),
+                3 => (block[u * 8 + v], v % 2 == 1),
+                _ => (block[v * 8 + u], false),
+            };
+            out[v * 8 + u] = if negate { -coef } else { coef };
+        }
+    }
+
+    out
+}
+
+/// Encodes `components` as a JPEG file with the Huffman-coded scans `scans`, each using optimal
+/// Huffman tables
+fn encode(sof: u8, width: usize, height: usize, metadata: &[&[u8]], quant_tables: &[(u8, Vec<u16>)],
+          components: &[Component], scans: &[Scan]) -> Vec<u8> {
+    // The first component uses the first pair of Huffman tables, and the others share the second
+    let table_id = |c: usize| if c == 0 { 0 } else { 1 };
+
+    let mut out = vec![0xFF, 0xD8];
+    for segment in metadata.iter() {
+        out.extend_from_slice(segment);
+    }
+
+    let segment = |out: &mut Vec<u8>, marker: u8, data: &[u8]| {
+        out.extend_from_slice(&[0xFF, marker]);
+        out.extend_from_slice(&((data.len() + 2) as u16).to_be_bytes());
+        out.extend_from_slice(data);
+    };
+
+    for (spec, table) in quant_tables.iter() {
+        let mut data = vec![*spec];
+        for &q in table.iter() {
+            if spec >> 4 == 0 {
+                data.push(q as u8);
+            } else {
+                data.extend_from_slice(&q.to_be_bytes());
+            }
+        }
+        segment(&mut out, 0xDB, &data);
+    }
+
+    let mut data = vec![8];
+    data.extend_from_slice(&(height as u16).to_be_bytes());
+    data.extend_from_slice(&(width as u16).to_be_bytes());
+    data.push(components.len() as u8);
+    for c in components.iter() {
+        data.extend_from_slice(&[c.id, ((c.h << 4) | c.v) as u8, c.quant_table]);
+    }
+    segment(&mut out, sof, &data);
+
+    for scan in scans.iter() {
+        let order = scan_order(components, &scan.comps, width, height);
+
+        // Gather the symbol frequencies, then code the blocks with the resulting tables
+        let mut freqs = [[0u32; 257]; 4];
+        code_blocks(components, &order, scan, |c, symbol, _, _| freqs[table_id(c) * 2 + symbol.0][symbol.1 as usize] += 1);
+        let tables: Vec<([u8; 16], Vec<u8>)> = freqs.iter().map(huffman_code_lengths).collect();
+        let codes: Vec<[(u16, u8); 256]> = tables.iter().map(|(counts, values)| huffman_codes(counts, values)).collect();
+
+        let mut writer = BitWriter { data: Vec::new(), acc: 0, len: 0 };
+        code_blocks(components, &order, scan, |c, symbol, bits, len| {
+            let (code, code_len) = codes[table_id(c) * 2 + symbol.0][symbol.1 as usize];
+            writer.bits(code as u32, code_len as u32);
+            writer.bits(bits, len);
+        });
+        writer.flush();
+
+        let mut data = Vec::new();
+        for (k, (counts, values)) in tables.iter().enumerate() {
+            if !values.is_empty() {
+                data.push((((k % 2) << 4) | (k / 2)) as u8);
+                data.extend_from_slice(counts);
+                data.extend_from_slice(values);
+            }
+        }
+        segment(&mut out, 0xC4, &data);
+
+        let mut data = vec![scan.comps.len() as u8];
+        for &c in scan.comps.iter() {
+            data.extend_from_slice(&[components[c].id, ((table_id(c) << 4) | table_id(c)) as u8]);
+        }
+        data.extend_from_slice(&[scan.ss as u8, scan.se as u8, 0]);
+        segment(&mut out, 0xDA, &data);
+
+        out.append(&mut writer.data);
+    }
+    out.extend_from_slice(&[0xFF, 0xD9]);
+
+    out
+}
+
+/// Codes the coefficients of the blocks of `components` in the order `order` that are part of
+/// `scan`, calling `emit` with the index of the component, the symbol as a table class (0 for DC
+/// and 1 for AC) and value, and the additional bits following the symbol along with their length.
+/// Since successive approximation is not used, AC coefficients are coded the same way in
+/// sequential and progressive scans, with an end-of-band symbol after each block's last non-zero
+/// coefficient
+fn code_blocks<F>(components: &[Component], order: &[(usize, usize)], scan: &Scan, mut emit: F)
+    where F: FnMut(usize, (usize, u8), u32, u32) {
+    // Returns the number of bits needed for a coefficient, and the bits themselves
+    let magnitude = |coef: i16| {
+        let len = 16 - coef.unsigned_abs().leading_zeros();
+        let bits = if coef < 0 { coef as i32 - 1 } else { coef as i32 };
+        (len, bits as u32 & ((1 << len) - 1))
+    };
+
+    let mut preds = vec![0i16; components.len()];
+    for &(c, b) in order.iter() {
+        let block = &components[c].blocks[b];
+
+        if scan.ss == 0 {
+            let (len, bits) = magnitude(block[0].wrapping_sub(preds[c]));
+            emit(c, (0, len as u8), bits, len);
+            preds[c] = block[0];
+        }
+        if scan.se == 0 {
+            continue;
+        }
+
+        let mut run = 0;
+        for &n in ZIGZAG[scan.ss.max(1)..=scan.se].iter() {
+            if block[n] == 0 {
+                run += 1;
+                continue;
+            }
+
+            while run > 15 {
+                emit(c, (1, 0xF0), 0, 0);
+                run -= 16;
+            }
+
+            let (len, bits) = magnitude(block[n]);
+            emit(c, (1, ((run << 4) | len) as u8), bits, len);
+            run = 0;
+        }
+
+        // End of block
+        if run > 0 {
+            emit(c, (1, 0), 0, 0);
+        }
+    }
+}
+
+/// Generates an optimal Huffman table with codes of at most 16 bits for the symbol frequencies
+/// `freqs`, as described in section K.2 of the JPEG specification. Returns the number of codes of
+/// each length and the symbols in order of increasing code length
+fn huffman_code_lengths(freqs: &[u32; 257]) -> ([u8; 16], Vec<u8>) {
+    let mut freqs = *freqs;
+    // A reserved symbol ensures that no code consists of only 1 bits
+    freqs[256] = 1;
+
+    if freqs[..256].iter().all(|&freq| freq == 0) {
+        return ([0; 16], Vec::new());
+    }
+
+    let mut code_size = [0usize; 257];
+    let mut others = [usize::MAX; 257];
+
+    // Returns the least frequent symbol other than `exclude`, preferring larger symbols in case
+    // of a tie
+    let least = |freqs: &[u32; 257], exclude: usize| {
+        (0..257).filter(|&s| freqs[s] > 0 && s != exclude)
+            .min_by_key(|&s| (freqs[s], usize::MAX - s))
+    };
+
+    while let Some(v_1) = least(&freqs, usize::MAX) {
+        let v_2 = match least(&freqs, v_1) {
+            Some(v) => v,
+            None => break,
+        };
+
+        freqs[v_1] += freqs[v_2];
+        freqs[v_2] = 0;
+
+        let mut v = v_1;
+        code_size[v] += 1;
+        while others[v] != usize::MAX {
+            v = others[v];
+            code_size[v] += 1;
+        }
+        others[v] = v_2;
+
+        let mut v = v_2;
+        code_size[v] += 1;
+        while others[v] != usize::MAX {
+            v = others[v];
+            code_size[v] += 1;
+        }
+    }
+
+    let mut bits = [0usize; 33];
+    for &size in code_size.iter().filter(|&&size| size > 0) {
+        bits[size] += 1;
+    }
+
+    // Limit the code lengths to 16 bits
+    for i in (17..=32).rev() {
+        while bits[i] > 0 {
+            let mut j = i - 2;
+            while bits[j] == 0 {
+                j -= 1;
+            }
+
+            bits[i] -= 2;
+            bits[i - 1] += 1;
+            bits[j + 1] += 2;
+            bits[j] -= 1;
+        }
+    }
+
+    // Remove the reserved symbol, which has one of the longest codes
+    let mut i = 16;
+    while bits[i] == 0 {
+        i -= 1;
+    }
+    bits[i] -= 1;
+
+    let mut counts = [0; 16];
+    for (count, &num) in counts.iter_mut().zip(bits[1..17].iter()) {
+        *count = num as u8;
+    }
+
+    // Symbols are assigned lengths in order of increasing code size
+    let mut values: Vec<usize> = (0..256).filter(|&s| code_size[s] > 0).collect();
+    values.sort_by_key(|&s| code_size[s]);
+
+    (counts, values.into_iter().map(|s| s as u8).collect())
+}
+
+/// Returns the canonical code and code length of each symbol of a Huffman table
+fn huffman_codes(counts: &[u8; 16], values: &[u8]) -> [(u16, u8); 256] {
+    let mut codes = [(0, 0); 256];
+    let mut code = 0u16;
+    let mut k = 0;
+
+    for (len, &count) in counts.iter().enumerate() {
+        for _ in 0..count {
+            codes[values[k] as usize] = (code, len as u8 + 1);
+            code += 1;
+            k += 1;
+        }
+        code <<= 1;
+    }
+
+    codes
+}
+
+fn corrupt(msg: &str) -> ImgIoError {
+    ImgIoError::OtherError(format!("invalid JPEG file: {}", msg))
+}
+
+fn unsupported() -> ImgIoError {
+    ImgIoError::UnsupportedFileFormatError("only baseline and extended sequential Huffman-coded \
+        JPEGs with 8-bit samples can be rotated losslessly".to_string())
+}
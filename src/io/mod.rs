@@ -19,11 +19,27 @@
 //! # }
 //! ```
 
-use crate::error::{ImgIoError, ImgIoResult};
+mod jpeg;
+
+use crate::enums::Subsampling;
+use crate::error::{ImgIoError, ImgIoResult, ImgProcResult};
 use crate::image::{Image, BaseImage};
+use crate::tone::CubeLut;
 
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngEncoder;
 use image::io::Reader;
-use image::{GenericImageView, ColorType, ImageBuffer};
+use image::{AnimationDecoder, GenericImageView, ColorType, DynamicImage, ImageBuffer, ImageFormat};
+
+use image_webp::WebPDecoder;
+
+use tiff::ColorType as TiffColorType;
+use tiff::decoder::{Decoder as TiffDecoder, DecodingResult};
+
+use std::fs;
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::path::Path;
 
 /// Extracts channels and alpha from an `image::ColorType`
 fn from_color_type(color: ColorType) -> ImgIoResult<(u8, bool)> {
@@ -39,13 +55,217 @@ fn from_color_type(color: ColorType) -> ImgIoResult<(u8, bool)> {
 // TODO: Fix rotation of JPG images where width < height
 /// Reads an image file into an `Image<u8>`. A wrapper around `image::io::Reader::open()`
 pub fn read(filename: &str) -> ImgIoResult<Image<u8>> {
-    let img = Reader::open(filename)?.decode()?;
+    match extension(filename).as_deref() {
+        Some("webp") => decode_webp(&fs::read(filename)?),
+        Some("tif") | Some("tiff") => {
+            read_tiff(filename)?.into_iter().next()
+                .ok_or_else(|| ImgIoError::OtherError("TIFF file contains no images".to_string()))
+        },
+        _ => {
+            let img = Reader::open(filename)?.decode()?;
+            from_dynamic_image(&img)
+        },
+    }
+}
+
+/// Decodes a lossy or lossless WebP image from `data` into an RGB `Image<u8>`, or an RGBA
+/// `Image<u8>` if the image has an alpha channel. Only the first frame of animated images is
+/// decoded
+pub fn decode_webp(data: &[u8]) -> ImgIoResult<Image<u8>> {
+    let mut decoder = WebPDecoder::new(Cursor::new(data)).map_err(|err| err.to_string())?;
+    let (width, height) = decoder.dimensions();
+    let (channels, alpha) = if decoder.has_alpha() { (4, true) } else { (3, false) };
+
+    let mut buf = vec![0; decoder.output_buffer_size()
+        .ok_or_else(|| ImgIoError::OtherError("WebP image is too large".to_string()))?];
+    decoder.read_image(&mut buf).map_err(|err| err.to_string())?;
+
+    Ok(Image::from_vec(width, height, channels, alpha, buf))
+}
+
+/// Reads all pages of a (possibly multi-page) TIFF file into a vector of `Image<u8>`s. Supports
+/// grayscale, RGB, and RGBA images with 8, 16, 32, or 64-bit integer or floating point samples;
+/// samples with more than 8 bits are scaled down to 8 bits, and floating point samples are
+/// assumed to be in the range `[0, 1]`
+pub fn read_tiff(filename: &str) -> ImgIoResult<Vec<Image<u8>>> {
+    let mut decoder = TiffDecoder::new(BufReader::new(File::open(filename)?))?;
+    let mut pages = Vec::new();
+
+    loop {
+        let (width, height) = decoder.dimensions()?;
+        let (channels, alpha) = match decoder.colortype()? {
+            TiffColorType::Gray(_) => (1, false),
+            TiffColorType::GrayA(_) => (2, true),
+            TiffColorType::RGB(_) => (3, false),
+            TiffColorType::RGBA(_) => (4, true),
+            color => return Err(ImgIoError::UnsupportedColorTypeError(
+                format!("unsupported TIFF color type: {:?}", color))),
+        };
+
+        let data = match decoder.read_image()? {
+            DecodingResult::U8(data) => data,
+            DecodingResult::U16(data) => data.iter().map(|&v| (v >> 8) as u8).collect(),
+            DecodingResult::U32(data) => data.iter().map(|&v| (v >> 24) as u8).collect(),
+            DecodingResult::U64(data) => data.iter().map(|&v| (v >> 56) as u8).collect(),
+            DecodingResult::F32(data) => data.iter().map(|&v| (v.clamp(0.0, 1.0) * 255.0).round() as u8).collect(),
+            DecodingResult::F64(data) => data.iter().map(|&v| (v.clamp(0.0, 1.0) * 255.0).round() as u8).collect(),
+        };
+
+        pages.push(Image::from_vec(width, height, channels, alpha, data));
+
+        if !decoder.more_images() {
+            break;
+        }
+        decoder.next_image()?;
+    }
+
+    Ok(pages)
+}
+
+/// Reads all frames of a (possibly animated) GIF file into a vector of RGBA `Image<u8>`s, each
+/// paired with its delay in centiseconds. Each frame is composited onto the previous frames
+/// according to its disposal method, so every returned frame is a complete image
+pub fn read_gif(filename: &str) -> ImgIoResult<Vec<(Image<u8>, u16)>> {
+    let decoder = GifDecoder::new(BufReader::new(File::open(filename)?))?;
+    let mut frames = Vec::new();
+
+    for frame in decoder.into_frames() {
+        let frame = frame?;
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let delay = (numer as f32 / denom as f32 / 10.0).round() as u16;
+
+        let buf = frame.into_buffer();
+        frames.push((Image::from_vec(buf.width(), buf.height(), 4, true, buf.into_raw()), delay));
+    }
+
+    Ok(frames)
+}
+
+/// Reads a 3D LUT from an Adobe `.cube` file. 1D LUTs are not supported
+pub fn read_cube_lut(filename: &str) -> ImgIoResult<CubeLut> {
+    let contents = fs::read_to_string(filename)?;
+    let mut size = None;
+    let mut domain_min = [0.0; 3];
+    let mut domain_max = [1.0; 3];
+    let mut data = Vec::new();
+
+    for (line_num, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut tokens = line.split_whitespace();
+        let keyword = tokens.next().unwrap_or_default();
+        let parse_err = || ImgIoError::OtherError(format!("invalid .cube file: could not parse line {}: \"{}\"",
+                                                          line_num + 1, line));
+        let parse_floats = |tokens: std::str::SplitWhitespace, out: &mut [f32]| -> ImgIoResult<()> {
+            let vals = tokens.map(|t| t.parse::<f32>()).collect::<Result<Vec<f32>, _>>()
+                .map_err(|_| parse_err())?;
+            if vals.len() != out.len() {
+                return Err(parse_err());
+            }
+            out.copy_from_slice(&vals);
+            Ok(())
+        };
+
+        match keyword {
+            "TITLE" => {},
+            "LUT_1D_SIZE" => {
+                return Err(ImgIoError::UnsupportedFileFormatError("1D .cube LUTs are not supported".to_string()));
+            },
+            "LUT_3D_SIZE" => {
+                size = Some(tokens.next().and_then(|t| t.parse::<u32>().ok()).ok_or_else(parse_err)?);
+            },
+            "DOMAIN_MIN" => parse_floats(tokens, &mut domain_min)?,
+            "DOMAIN_MAX" => parse_floats(tokens, &mut domain_max)?,
+            "LUT_3D_INPUT_RANGE" => {
+                let mut range = [0.0; 2];
+                parse_floats(tokens, &mut range)?;
+                domain_min = [range[0]; 3];
+                domain_max = [range[1]; 3];
+            },
+            _ => {
+                let mut rgb = [0.0; 3];
+                parse_floats(line.split_whitespace(), &mut rgb)?;
+                data.push(rgb);
+            },
+        }
+    }
+
+    let size = size.ok_or_else(|| ImgIoError::OtherError("invalid .cube file: missing LUT_3D_SIZE".to_string()))?;
+    let mut lut = CubeLut::new(size, data)
+        .map_err(|err| ImgIoError::OtherError(format!("invalid .cube file: {}", err)))?;
+    lut.domain_min = domain_min;
+    lut.domain_max = domain_max;
+
+    Ok(lut)
+}
+
+/// Reads every supported image file in `in_dir`, applies `op` to it, and writes the result to
+/// `out_dir` under the same filename, creating `out_dir` if necessary. Files with unsupported
+/// extensions and subdirectories are skipped.
+///
+/// A failure to process one file does not stop the others from being processed; if any file
+/// fails, an `ImgIoError::OtherError` listing each failed file and its error is returned after all
+/// files have been processed
+pub fn process_directory<F>(in_dir: &str, out_dir: &str, op: F) -> ImgIoResult<()>
+    where F: Fn(&Image<u8>) -> ImgProcResult<Image<u8>> {
+    fs::create_dir_all(out_dir)?;
+
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(in_dir)? {
+        let path = entry?.path();
+        let supported = path.to_str()
+            .and_then(extension)
+            .and_then(ImageFormat::from_extension)
+            .is_some();
+
+        if path.is_file() && supported {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut failures = Vec::new();
+    for path in paths {
+        let in_file = path.to_string_lossy().to_string();
+        let out_path = Path::new(out_dir).join(path.file_name().unwrap());
+        let out_file = out_path.to_string_lossy().to_string();
+
+        let result = read(&in_file).and_then(|img| {
+            let output = op(&img).map_err(|err| ImgIoError::OtherError(err.to_string()))?;
+            write(&output, &out_file)
+        });
+
+        if let Err(err) = result {
+            failures.push(format!("{}: {}", in_file, err));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(ImgIoError::OtherError(format!("failed to process {} file(s): {}",
+                                           failures.len(), failures.join("; "))))
+    }
+}
+
+/// Converts an 8-bit `image::DynamicImage` into an `Image<u8>`
+fn from_dynamic_image(img: &DynamicImage) -> ImgIoResult<Image<u8>> {
     let (width, height) = img.dimensions();
     let (channels, alpha) = from_color_type(img.color())?;
 
     Ok(Image::from_slice(width, height, channels, alpha, img.as_bytes()))
 }
 
+/// Returns the lowercase extension of `filename`, if any
+fn extension(filename: &str) -> Option<String> {
+    Path::new(filename).extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+}
+
 /// Writes an RGB(A)8 or Gray(A)8 `Image<u8>` into an image file. A wrapper around `image::io::Reader::save()`
 pub fn write(input: &Image<u8>, filename: &str) -> ImgIoResult<()> {
     let (width, height, channels, alpha) = input.info().whca();
@@ -81,4 +301,216 @@ pub fn write(input: &Image<u8>, filename: &str) -> ImgIoResult<()> {
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Options for JPEG encoding
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JpegOptions {
+    /// The encoding quality, from 1 (worst) to 100 (best)
+    pub quality: u8,
+
+    /// The chroma subsampling ratio
+    pub subsampling: Subsampling,
+
+    /// Whether to use progressive encoding
+    pub progressive: bool,
+}
+
+impl Default for JpegOptions {
+    fn default() -> Self {
+        JpegOptions {
+            quality: 75,
+            subsampling: Subsampling::S444,
+            progressive: false,
+        }
+    }
+}
+
+/// Writes a Gray(A)8 or RGB(A)8 `Image<u8>` into a JPEG file using the encoding options `opts`.
+/// Alpha channels are discarded, and `opts.subsampling` has no effect on Gray(A)8 images
+pub fn write_jpg_opts(input: &Image<u8>, filename: &str, opts: &JpegOptions) -> ImgIoResult<()> {
+    if opts.quality < 1 || opts.quality > 100 {
+        return Err(ImgIoError::OtherError(format!("invalid quality: quality must be between 1 and 100 \
+            (inclusive), but is {}", opts.quality)));
+    }
+
+    fs::write(filename, jpeg::encode_image(input, opts)?)?;
+
+    Ok(())
+}
+
+/// Returns the 8-bit `image::ColorType` corresponding to the channels of `input`
+fn to_color_type(input: &Image<u8>) -> ImgIoResult<ColorType> {
+    match input.info().channels {
+        1 => Ok(ColorType::L8),
+        2 => Ok(ColorType::La8),
+        3 => Ok(ColorType::Rgb8),
+        4 => Ok(ColorType::Rgba8),
+        _ => Err(ImgIoError::UnsupportedColorTypeError("unsupported color type".to_string())),
+    }
+}
+
+/// Image metadata that is not part of the pixel data
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageMeta {
+    /// The horizontal resolution in dots per inch, if known
+    pub dpi_x: Option<f32>,
+
+    /// The vertical resolution in dots per inch, if known
+    pub dpi_y: Option<f32>,
+
+    /// The color type of the image file, before conversion to 8 bits per channel
+    pub color_type: ColorType,
+}
+
+/// Reads an image file into an `Image<u8>`, along with its metadata. Images with more than 8
+/// bits per channel are converted to 8 bits per channel. The resolution is read from the `pHYs`
+/// chunk of PNG files and the JFIF density of JPEG files, and is `None` for other formats
+pub fn read_with_meta(filename: &str) -> ImgIoResult<(Image<u8>, ImageMeta)> {
+    let bytes = fs::read(filename)?;
+    let (dpi_x, dpi_y) = match image::guess_format(&bytes) {
+        Ok(ImageFormat::Png) => png_dpi(&bytes),
+        Ok(ImageFormat::Jpeg) => jpeg_dpi(&bytes),
+        _ => {
+            let img = read(filename)?;
+            let color_type = to_color_type(&img)?;
+            return Ok((img, ImageMeta { dpi_x: None, dpi_y: None, color_type }));
+        },
+    };
+
+    let img = image::load_from_memory(&bytes)?;
+    let color_type = img.color();
+    let img = match (color_type.channel_count(), color_type.has_alpha()) {
+        (1, _) => DynamicImage::ImageLuma8(img.to_luma8()),
+        (2, _) => DynamicImage::ImageLumaA8(img.to_luma_alpha8()),
+        (_, false) => DynamicImage::ImageRgb8(img.to_rgb8()),
+        (_, true) => DynamicImage::ImageRgba8(img.to_rgba8()),
+    };
+
+    Ok((from_dynamic_image(&img)?, ImageMeta { dpi_x, dpi_y, color_type }))
+}
+
+/// Writes a Gray(A)8 or RGB(A)8 `Image<u8>` into an image file, like [`write()`](fn.write.html),
+/// along with its metadata. The resolution is written to the `pHYs` chunk of PNG files if both
+/// `meta.dpi_x` and `meta.dpi_y` are set, and is discarded for other formats. `meta.color_type`
+/// is ignored
+pub fn write_with_meta(input: &Image<u8>, filename: &str, meta: &ImageMeta) -> ImgIoResult<()> {
+    let dpi = meta.dpi_x.zip(meta.dpi_y);
+    if extension(filename).as_deref() != Some("png") || dpi.is_none() {
+        return write(input, filename);
+    }
+
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png).encode(input.data(), input.info().width, input.info().height, to_color_type(input)?)?;
+
+    // pHYs stores the resolution in pixels per meter
+    let (dpi_x, dpi_y) = dpi.unwrap();
+    let mut chunk = Vec::with_capacity(21);
+    chunk.extend_from_slice(&9u32.to_be_bytes());
+    chunk.extend_from_slice(b"pHYs");
+    chunk.extend_from_slice(&((dpi_x / METERS_PER_INCH).round() as u32).to_be_bytes());
+    chunk.extend_from_slice(&((dpi_y / METERS_PER_INCH).round() as u32).to_be_bytes());
+    chunk.push(1);
+    let crc = crc32(&chunk[4..]);
+    chunk.extend_from_slice(&crc.to_be_bytes());
+
+    // Insert the chunk right after the signature and the IHDR chunk
+    png.splice(PNG_IHDR_END..PNG_IHDR_END, chunk);
+    fs::write(filename, png)?;
+
+    Ok(())
+}
+
+/// Rotates a JPEG file `turns` times by 90 degrees clockwise without recompressing it, by
+/// rotating the quantized DCT coefficients of each block and rearranging the blocks, so there is
+/// no loss of quality. Metadata segments (such as EXIF data) are copied unchanged
+///
+/// Only baseline and extended sequential (Huffman-coded, 8-bit) JPEGs are supported. As blocks
+/// cannot be split, partial MCUs (blocks of 8x8 pixels, or 16x16 pixels for chroma subsampled
+/// images) along edges that would become the top or left edge of the rotated image are trimmed,
+/// as with `jpegtran -trim`. The output is written with a single scan and optimized Huffman
+/// tables, and without restart markers
+pub fn rotate_jpeg_lossless(in_path: &str, out_path: &str, turns: u8) -> ImgIoResult<()> {
+    let bytes = fs::read(in_path)?;
+    let rotated = jpeg::rotate(&bytes, turns).map_err(|err| match err {
+        ImgIoError::UnsupportedFileFormatError(msg) => ImgIoError::UnsupportedFileFormatError(format!("{}: {}", in_path, msg)),
+        err => err,
+    })?;
+
+    fs::write(out_path, rotated)?;
+    Ok(())
+}
+
+/// The number of meters in an inch
+const METERS_PER_INCH: f32 = 0.0254;
+
+/// The end of the IHDR chunk, which is always the first chunk after the 8-byte PNG signature
+const PNG_IHDR_END: usize = 33;
+
+/// Returns the resolution in dots per inch from the `pHYs` chunk of a PNG file
+fn png_dpi(bytes: &[u8]) -> (Option<f32>, Option<f32>) {
+    let mut i = 8;
+    while i + 8 <= bytes.len() {
+        let len = u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]) as usize;
+        let data = &bytes[(i + 8).min(bytes.len())..(i + 8 + len).min(bytes.len())];
+
+        match &bytes[(i + 4)..(i + 8)] {
+            // A unit of 1 is meters; otherwise, only the aspect ratio is known
+            b"pHYs" if data.len() == 9 && data[8] == 1 => {
+                let x = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as f32;
+                let y = u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as f32;
+                return (Some(x * METERS_PER_INCH), Some(y * METERS_PER_INCH));
+            },
+            b"IDAT" | b"IEND" => break,
+            _ => {},
+        }
+
+        i += 12 + len;
+    }
+
+    (None, None)
+}
+
+/// Returns the resolution in dots per inch from the JFIF APP0 segment of a JPEG file
+fn jpeg_dpi(bytes: &[u8]) -> (Option<f32>, Option<f32>) {
+    let mut i = 2;
+    while i + 4 <= bytes.len() && bytes[i] == 0xFF {
+        let marker = bytes[i + 1];
+        let len = u16::from_be_bytes([bytes[i + 2], bytes[i + 3]]) as usize;
+        let data = &bytes[(i + 4).min(bytes.len())..(i + 2 + len).min(bytes.len())];
+
+        match marker {
+            // A unit of 1 is dots per inch and 2 is dots per centimeter; otherwise, only the
+            // aspect ratio is known
+            0xE0 if data.len() >= 12 && &data[..5] == b"JFIF\0" => {
+                let x = u16::from_be_bytes([data[8], data[9]]) as f32;
+                let y = u16::from_be_bytes([data[10], data[11]]) as f32;
+                return match data[7] {
+                    1 => (Some(x), Some(y)),
+                    2 => (Some(x * 2.54), Some(y * 2.54)),
+                    _ => (None, None),
+                };
+            },
+            // Start of scan
+            0xDA => break,
+            _ => {},
+        }
+
+        i += 2 + len;
+    }
+
+    (None, None)
+}
+
+/// Computes the CRC-32 checksum used by PNG chunks
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in bytes.iter() {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+
+    !crc
+}
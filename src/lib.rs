@@ -1,6 +1,7 @@
 // Modules
 pub mod colorspace;
 pub mod convert;
+pub mod draw;
 pub mod enums;
 pub mod error;
 pub mod filter;
@@ -1,7 +1,7 @@
 //! A module for image morphology operations
 
 use crate::{error, util};
-use crate::error::ImgProcResult;
+use crate::error::{ImgProcError, ImgProcResult};
 use crate::image::{Image, BaseImage};
 
 /// Erodes a binary image (grayscale image with pixel values of 0 or 255) using a kernel of size
@@ -172,4 +172,163 @@ pub fn gradient(input: &Image<u8>, radius: u32) -> ImgProcResult<Image<u8>> {
     }
 
     Ok(output)
-}
\ No newline at end of file
+}
+
+/// Thins a binary image (grayscale image with pixel values of 0 or 255) down to a skeleton of
+/// 1-pixel-wide lines using the Zhang-Suen thinning algorithm. Pixels outside the image are
+/// treated as background
+pub fn skeletonize(input: &Image<u8>) -> ImgProcResult<Image<u8>> {
+    error::check_grayscale(input)?;
+
+    let (width, height) = input.info().wh();
+    let mut output = input.clone();
+    let mut to_remove = Vec::new();
+
+    loop {
+        let mut changed = false;
+
+        for step in 0..2 {
+            for y in 0..height {
+                for x in 0..width {
+                    if output.get_pixel(x, y)[0] == 0 {
+                        continue;
+                    }
+
+                    // Neighbors p2 to p9, clockwise starting from the pixel directly above
+                    let p = zhang_suen_neighbors(&output, x, y);
+                    let num_fg = p.iter().filter(|&&v| v).count();
+                    let transitions = (0..8).filter(|&i| !p[i] && p[(i + 1) % 8]).count();
+
+                    let v = p.map(|b| b as u8);
+                    let cond = if step == 0 {
+                        v[0] * v[2] * v[4] == 0 && v[2] * v[4] * v[6] == 0
+                    } else {
+                        v[0] * v[2] * v[6] == 0 && v[0] * v[4] * v[6] == 0
+                    };
+
+                    if (2..=6).contains(&num_fg) && transitions == 1 && cond {
+                        to_remove.push((x, y));
+                    }
+                }
+            }
+
+            if !to_remove.is_empty() {
+                changed = true;
+            }
+
+            for (x, y) in to_remove.drain(..) {
+                output.get_pixel_mut(x, y)[0] = 0;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Traces the outer boundaries of the 8-connected foreground regions of a binary image
+/// (grayscale image with pixel values of 0 or 255) using Moore-neighbor tracing. Pixels outside
+/// the image are treated as background
+///
+/// Returns one list of boundary points per region, ordered clockwise starting from the region's
+/// top left pixel. Regions are returned in raster order of their top left pixels
+pub fn find_contours(input: &Image<u8>) -> ImgProcResult<Vec<Vec<(u32, u32)>>> {
+    error::check_channel_count(1, input.info().channels as usize)?;
+    if input.data().iter().any(|&v| v != 0 && v != 255) {
+        return Err(ImgProcError::InvalidArgError("input is not a binary image".to_string()));
+    }
+
+    // Neighbor offsets in clockwise order, starting from the pixel directly to the right
+    let offsets = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+    let (width, height) = input.info().wh();
+    let is_fg = |x: i32, y: i32| -> bool {
+        x >= 0 && y >= 0 && x < width as i32 && y < height as i32
+            && input.get_pixel(x as u32, y as u32)[0] != 0
+    };
+
+    let mut visited = vec![false; (width * height) as usize];
+    let mut contours = Vec::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            if visited[(y * width + x) as usize] || input.get_pixel(x, y)[0] == 0 {
+                continue;
+            }
+
+            // This is the top left pixel of a new region, so its left neighbor is background
+            let start = (x as i32, y as i32);
+            let mut curr = start;
+            let mut back_dir = 4;
+            let mut first_step = None;
+            let mut contour = vec![(x, y)];
+
+            loop {
+                let next_dir = (1..=8).map(|i| (back_dir + i) % 8)
+                    .find(|&d| is_fg(curr.0 + offsets[d].0, curr.1 + offsets[d].1));
+
+                let d = match next_dir {
+                    Some(d) => d,
+                    None => break,
+                };
+
+                let next = (curr.0 + offsets[d].0, curr.1 + offsets[d].1);
+                if curr == start && first_step == Some(next) {
+                    break;
+                }
+                if first_step.is_none() {
+                    first_step = Some(next);
+                }
+
+                // The last background pixel checked becomes the new backtrack pixel
+                let back = (curr.0 + offsets[(d + 7) % 8].0, curr.1 + offsets[(d + 7) % 8].1);
+                back_dir = offsets.iter()
+                    .position(|&(dx, dy)| (next.0 + dx, next.1 + dy) == back)
+                    .unwrap();
+
+                curr = next;
+                if curr != start {
+                    contour.push((curr.0 as u32, curr.1 as u32));
+                }
+            }
+
+            contours.push(contour);
+
+            // Mark the rest of the region as visited
+            let mut stack = vec![(x, y)];
+            visited[(y * width + x) as usize] = true;
+            while let Some((curr_x, curr_y)) = stack.pop() {
+                for &(dx, dy) in offsets.iter() {
+                    let (n_x, n_y) = (curr_x as i32 + dx, curr_y as i32 + dy);
+                    if is_fg(n_x, n_y) && !visited[(n_y as u32 * width + n_x as u32) as usize] {
+                        visited[(n_y as u32 * width + n_x as u32) as usize] = true;
+                        stack.push((n_x as u32, n_y as u32));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(contours)
+}
+
+// Returns whether each of the 8 neighbors of `(x, y)` is foreground, in clockwise order starting
+// from the pixel directly above
+fn zhang_suen_neighbors(input: &Image<u8>, x: u32, y: u32) -> [bool; 8] {
+    let (width, height) = input.info().wh();
+    let offsets = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)];
+    let mut p = [false; 8];
+
+    for (i, (dx, dy)) in offsets.iter().enumerate() {
+        let curr_x = x as i32 + dx;
+        let curr_y = y as i32 + dy;
+
+        if curr_x >= 0 && curr_y >= 0 && curr_x < width as i32 && curr_y < height as i32 {
+            p[i] = input.get_pixel(curr_x as u32, curr_y as u32)[0] != 0;
+        }
+    }
+
+    p
+}
@@ -127,6 +127,86 @@ pub unsafe fn adds_n_256_u8(input: &Image<u8>, val: i16, n: u8) -> Image<u8> {
                        input.info().alpha, data)
 }
 
+/// Adds `deltas[c]` to channel `c` of each pixel of a RGB(A) `input` using saturation, ignoring
+/// the alpha channel if present. Uses AVX2 if it is available, and a scalar implementation
+/// otherwise
+///
+/// # Panics
+///
+/// Panics if `input` does not have exactly 3 non-alpha channels
+#[cfg(feature = "simd")]
+pub fn adds_rgb_256_u8(input: &Image<u8>, deltas: [i16; 3]) -> Image<u8> {
+    if input.info().channels_non_alpha() != 3 {
+        panic!("invalid number of channels: image must have 3 non-alpha channels, but has {}",
+               input.info().channels_non_alpha());
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return unsafe { adds_rgb_avx2_u8(input, deltas) };
+            }
+        }
+
+    let mut output = input.clone();
+    adds_rgb_norm(output.data_mut(), input.info().channels as usize, deltas);
+    output
+}
+
+/// The AVX2 implementation of [`adds_rgb_256_u8()`](fn.adds_rgb_256_u8.html)
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+#[target_feature(enable = "avx2")]
+unsafe fn adds_rgb_avx2_u8(input: &Image<u8>, deltas: [i16; 3]) -> Image<u8> {
+    let channels = input.info().channels as usize;
+    let num_bytes = input.info().full_size() as usize;
+    let mut data: Vec<u8> = input.data().to_vec();
+
+    // Build per-lane vectors of the positive and negative parts of the deltas, matching the
+    // channel layout. Alpha lanes stay 0
+    let mut add = [0u8; 32];
+    let mut sub = [0u8; 32];
+    for lane in 0..32 {
+        if lane % channels < 3 {
+            let delta = deltas[lane % channels].clamp(-255, 255);
+            add[lane] = delta.max(0) as u8;
+            sub[lane] = (-delta).max(0) as u8;
+        }
+    }
+    let add_256 = _mm256_loadu_si256(add.as_ptr() as *const _);
+    let sub_256 = _mm256_loadu_si256(sub.as_ptr() as *const _);
+
+    // The lane vectors only line up with whole pixels, so 3-channel images are processed 30 bytes
+    // at a time (the last 2 bytes of each chunk are overwritten by the next chunk)
+    let step = 32 - 32 % channels;
+
+    let mut i = 0;
+    while (i + 32) <= num_bytes {
+        let chunk = _mm256_loadu_si256(input.data().as_ptr().add(i) as *const _);
+        let res = _mm256_subs_epu8(_mm256_adds_epu8(chunk, add_256), sub_256);
+        _mm256_storeu_si256(data.as_mut_ptr().add(i) as *mut _, res);
+
+        i += step;
+    }
+
+    // Process the remaining bytes normally, starting over from the input since the last chunk
+    // may have extended past them
+    data[i..].copy_from_slice(&input.data()[i..]);
+    adds_rgb_norm(&mut data[i..], channels, deltas);
+
+    Image::from_vec(input.info().width, input.info().height, input.info().channels,
+                    input.info().alpha, data)
+}
+
+/// Adds `deltas` to the first 3 channels of each pixel in `data` using saturation
+#[cfg(feature = "simd")]
+fn adds_rgb_norm(data: &mut [u8], channels: usize, deltas: [i16; 3]) {
+    for p in data.chunks_exact_mut(channels) {
+        for (c, delta) in p.iter_mut().zip(deltas.iter()) {
+            *c = (*c as i16 + delta).clamp(0, 255) as u8;
+        }
+    }
+}
+
 /// Separates a 3-channel input image into 3 256-bit wide integer vectors, starting at the channel
 /// denoted by `offset`. Does not check if `offset` is valid.
 #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
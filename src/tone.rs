@@ -1,15 +1,19 @@
 //! A module for image tone operations
 
-use crate::{util, colorspace, error};
-use crate::enums::White;
-use crate::image::Image;
-use crate::error::ImgProcResult;
+use crate::{util, colorspace, error, filter};
+use crate::enums::{Colormap, White};
+use crate::image::{BaseImage, Image};
+use crate::error::{ImgProcError, ImgProcResult};
+use crate::util::constants::VIRIDIS;
 
 #[cfg(feature = "simd")]
 use crate::simd;
 
 use std::collections::HashMap;
 
+/// The luminance below which [`recover_shadows()`](fn.recover_shadows.html) lifts values
+const SHADOW_RANGE: f32 = 0.5;
+
 /// Adjusts brightness by adding `bias` to each RGB channel
 ///
 /// # Arguments
@@ -83,6 +87,38 @@ pub fn contrast_lab(input: &Image<u8>, gain: f32) -> ImgProcResult<Image<u8>> {
     Ok(colorspace::lab_to_srgb_f32(&lab, &White::D50))
 }
 
+/// Adjusts each RGB channel independently by computing `channel * gains[c] + offsets[c]` for
+/// channel `c`, with saturation
+///
+/// # Arguments
+///
+/// * `gains` - Must contain one non-negative gain for each non-alpha channel of `input`
+/// * `offsets` - Must contain one offset between -255 and 255 (inclusive) for each non-alpha
+///   channel of `input`
+pub fn color_balance(input: &Image<u8>, gains: &[f32], offsets: &[i16]) -> ImgProcResult<Image<u8>> {
+    let channels = input.info().channels_non_alpha() as usize;
+    error::check_channel_count(input.info().channels_non_alpha(), gains.len())?;
+    error::check_channel_count(input.info().channels_non_alpha(), offsets.len())?;
+
+    let mut lookup_tables = Vec::with_capacity(channels);
+    for (gain, offset) in gains.iter().zip(offsets.iter()) {
+        error::check_non_neg(*gain, "gain")?;
+        error::check_in_range(*offset, -255, 255, "offset")?;
+
+        let mut lookup_table: [u8; 256] = [0; 256];
+        util::generate_lookup_table(&mut lookup_table, |i| {
+            (i as f32 * gain + *offset as f32).round().clamp(0.0, 255.0) as u8
+        });
+        lookup_tables.push(lookup_table);
+    }
+
+    Ok(input.map_pixels_if_alpha(|channels, p_out| {
+        for (c, channel) in channels.iter().enumerate() {
+            p_out.push(lookup_tables[c][*channel as usize]);
+        }
+    }, |a| a))
+}
+
 /// Adjusts saturation by adding `saturation` to the saturation value (S) of `input` in HSV
 ///
 /// # Arguments
@@ -124,6 +160,85 @@ pub fn gamma(input: &Image<u8>, gamma: f32, max: u8) -> ImgProcResult<Image<u8>>
     }, |a| a))
 }
 
+/// Adjusts exposure by multiplying each channel by the gain, in linear light, that makes the mean
+/// luminance of `input` equal to `target_mean`. Channels that exceed the maximum value are
+/// clipped. Luminance is computed using the Rec. 709 coefficients for RGB(A) images, and as the
+/// average of the non-alpha channels for other images. Images that are completely black are
+/// unchanged
+///
+/// # Arguments
+///
+/// * `target_mean` - The mean luminance in linear light, e.g. 0.18 for middle gray. Must be
+///   between 0 and 1 (inclusive)
+///
+/// # Panics
+///
+/// Panics if `target_mean` is not between 0 and 1 (inclusive)
+pub fn auto_exposure(input: &Image<u8>, target_mean: f32) -> Image<u8> {
+    assert!((0.0..=1.0).contains(&target_mean), "target_mean must be between 0 and 1 (inclusive)");
+
+    let linear = colorspace::linearize_srgb_f32(input);
+    let channels = input.info().channels_non_alpha() as usize;
+    let luma_sum: f64 = (0..(input.info().size() as usize)).map(|i| {
+        let p = &linear[i];
+        if channels == 3 {
+            (0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2]) as f64
+        } else {
+            (p[..channels].iter().sum::<f32>() / channels as f32) as f64
+        }
+    }).sum();
+
+    let mean = luma_sum / input.info().size() as f64;
+    if mean <= 0.0 {
+        return input.clone();
+    }
+
+    let gain = (target_mean as f64 / mean) as f32;
+    colorspace::unlinearize_srgb_f32(&linear.map_channels_if_alpha(|channel| (channel * gain).min(1.0), |a| a))
+}
+
+/// Enhances local contrast and normalizes uneven illumination using multi-scale retinex. For each
+/// standard deviation in `sigmas`, the log of the Gaussian-blurred image (the estimated
+/// illumination) is subtracted from the log of the image, and the results are averaged across
+/// scales. The result is then stretched linearly over the full range of values, using the same
+/// range for all channels; images without any local contrast become mid-gray. The alpha channel,
+/// if present, is unchanged
+///
+/// # Arguments
+///
+/// * `sigmas` - The standard deviations of the Gaussian blurs, typically small, medium, and large
+///   (e.g. 15, 80, and 250). Must be non-empty and positive
+pub fn retinex_msr(input: &Image<u8>, sigmas: &[f32]) -> ImgProcResult<Image<u8>> {
+    if sigmas.is_empty() || sigmas.iter().any(|&sigma| sigma <= 0.0 || sigma.is_nan()) {
+        return Err(ImgProcError::InvalidArgError("sigmas must be non-empty and positive".to_string()));
+    }
+
+    let input_f32: Image<f32> = input.clone().into();
+    let channels = input.info().channels_non_alpha() as usize;
+    let mut retinex = vec![0.0; input.info().size() as usize * channels];
+
+    for &sigma in sigmas.iter() {
+        let kernel = filter::gaussian_kernel_1d(sigma);
+        let blurred = filter::separable_filter(&input_f32, &kernel, &kernel)?;
+
+        for (i, val) in retinex.iter_mut().enumerate() {
+            let (p, c) = (i / channels, i % channels);
+            *val += ((input_f32[p][c] + 1.0).ln() - (blurred[p][c] + 1.0).ln()) / sigmas.len() as f32;
+        }
+    }
+
+    let min = retinex.iter().copied().fold(f32::MAX, f32::min);
+    let max = retinex.iter().copied().fold(f32::MIN, f32::max);
+
+    let mut output = input.clone();
+    for (i, val) in retinex.iter().enumerate() {
+        let scaled = if max > min { (val - min) / (max - min) * 255.0 } else { 127.5 };
+        output[i / channels][i % channels] = scaled.round() as u8;
+    }
+
+    Ok(output)
+}
+
 /// Performs a histogram equalization on `input`
 ///
 /// # Arguments
@@ -149,3 +264,241 @@ pub fn histogram_equalization(input: &Image<u8>, alpha: f32, ref_white: &White,
 
     Ok(colorspace::lab_to_srgb_f32(&lab, ref_white))
 }
+
+/// Maps a single-channel image to RGB using the colormap `map`, for visualizing data such as
+/// gradient magnitudes or depth maps. The alpha channel, if present, is kept
+pub fn apply_colormap(input: &Image<u8>, map: Colormap) -> ImgProcResult<Image<u8>> {
+    error::check_grayscale(input)?;
+
+    let lookup_table = generate_colormap_table(map);
+
+    Ok(input.map_pixels_if_alpha(|channels, p_out| {
+        p_out.extend_from_slice(&lookup_table[channels[0] as usize]);
+    }, |a| a))
+}
+
+fn generate_colormap_table(map: Colormap) -> [[u8; 3]; 256] {
+    let mut lookup_table = [[0; 3]; 256];
+    let to_u8 = |val: f32| (val.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    for (i, rgb) in lookup_table.iter_mut().enumerate() {
+        let t = i as f32 / 255.0;
+
+        *rgb = match map {
+            Colormap::Grayscale => [i as u8; 3],
+            Colormap::Jet => [to_u8(1.5 - (4.0 * t - 3.0).abs()),
+                              to_u8(1.5 - (4.0 * t - 2.0).abs()),
+                              to_u8(1.5 - (4.0 * t - 1.0).abs())],
+            Colormap::Hot => [to_u8(3.0 * t), to_u8(3.0 * t - 1.0), to_u8(3.0 * t - 2.0)],
+            Colormap::Viridis => {
+                // Linearly interpolate between the control points
+                let pos = t * (VIRIDIS.len() - 1) as f32;
+                let index = std::cmp::min(pos.floor() as usize, VIRIDIS.len() - 2);
+                let weight = pos - index as f32;
+
+                let mut rgb = [0; 3];
+                for (c, val) in rgb.iter_mut().enumerate() {
+                    let low = VIRIDIS[index][c] as f32;
+                    let high = VIRIDIS[index + 1][c] as f32;
+                    *val = (low + weight * (high - low)).round() as u8;
+                }
+
+                rgb
+            },
+        };
+    }
+
+    lookup_table
+}
+
+/// A 3D color lookup table, as stored in an Adobe `.cube` file
+#[derive(Debug, Clone, PartialEq)]
+pub struct CubeLut {
+    /// Number of entries along each axis of the cube
+    pub size: u32,
+
+    /// Input values mapped to the first entry along each axis
+    pub domain_min: [f32; 3],
+
+    /// Input values mapped to the last entry along each axis
+    pub domain_max: [f32; 3],
+
+    /// Output RGB values, with the red index changing fastest and the blue index slowest
+    pub data: Vec<[f32; 3]>,
+}
+
+impl CubeLut {
+    /// Creates a new `CubeLut` with a domain of `[0, 1]` on each axis
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Must be at least 2
+    /// * `data` - Must have length `size^3`
+    pub fn new(size: u32, data: Vec<[f32; 3]>) -> ImgProcResult<Self> {
+        error::check_in_range(size, 2, 256, "size")?;
+        error::check_equal(data.len(), (size * size * size) as usize, "data length and size^3")?;
+
+        Ok(CubeLut { size, domain_min: [0.0; 3], domain_max: [1.0; 3], data })
+    }
+
+    /// Returns the identity LUT of size `size`
+    pub fn identity(size: u32) -> ImgProcResult<Self> {
+        error::check_in_range(size, 2, 256, "size")?;
+
+        let max = (size - 1) as f32;
+        let mut data = Vec::with_capacity((size * size * size) as usize);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    data.push([r as f32 / max, g as f32 / max, b as f32 / max]);
+                }
+            }
+        }
+
+        CubeLut::new(size, data)
+    }
+
+    /// Returns the output value at index `(r, g, b)`
+    fn get(&self, r: usize, g: usize, b: usize) -> [f32; 3] {
+        let size = self.size as usize;
+        self.data[(b * size + g) * size + r]
+    }
+}
+
+/// Maps the colors of an RGB(A) image through the 3D LUT `lut` using trilinear interpolation.
+/// The alpha channel, if present, is kept
+pub fn apply_cube_lut(input: &Image<u8>, lut: &CubeLut) -> ImgProcResult<Image<u8>> {
+    if input.info().channels_non_alpha() != 3 {
+        return Err(ImgProcError::InvalidArgError("input is not an RGB or RGBA image".to_string()));
+    }
+    error::check_equal(lut.data.len(), (lut.size * lut.size * lut.size) as usize, "lut data length and size^3")?;
+
+    let max = (lut.size - 1) as f32;
+
+    Ok(input.map_pixels_if_alpha(|channels, p_out| {
+        // Position of the pixel in the cube, and the lower corner and weights of its cell
+        let mut lower = [0; 3];
+        let mut weight = [0.0; 3];
+        for c in 0..3 {
+            let val = channels[c] as f32 / 255.0;
+            let pos = ((val - lut.domain_min[c]) / (lut.domain_max[c] - lut.domain_min[c]) * max)
+                .clamp(0.0, max);
+
+            lower[c] = (pos.floor() as usize).min(lut.size as usize - 2);
+            weight[c] = pos - lower[c] as f32;
+        }
+
+        let mut rgb = [0.0; 3];
+        for corner in 0..8 {
+            let (dr, dg, db) = (corner & 1, (corner >> 1) & 1, (corner >> 2) & 1);
+            let w = (if dr == 1 { weight[0] } else { 1.0 - weight[0] })
+                * (if dg == 1 { weight[1] } else { 1.0 - weight[1] })
+                * (if db == 1 { weight[2] } else { 1.0 - weight[2] });
+
+            let val = lut.get(lower[0] + dr, lower[1] + dg, lower[2] + db);
+            for c in 0..3 {
+                rgb[c] += w * val[c];
+            }
+        }
+
+        for val in rgb.iter() {
+            p_out.push((val.clamp(0.0, 1.0) * 255.0).round() as u8);
+        }
+    }, |a| a))
+}
+
+/// Compresses an HDR image with unbounded non-negative channel values into the range [0, 1]
+/// using the global Reinhard operator, where `key` is the target average brightness of the
+/// output (0.18 is typical). Operates on luminance to preserve color ratios
+pub fn reinhard(input: &Image<f32>, key: f32) -> Image<f32> {
+    let channels = input.info().channels_non_alpha() as usize;
+
+    // Log-average luminance
+    let mut log_sum = 0.0;
+    for i in 0..(input.info().size() as usize) {
+        log_sum += (1e-4 + luminance(&input[i][..channels])).ln();
+    }
+    let log_avg = (log_sum / input.info().size() as f32).exp();
+
+    tone_map_luminance(input, |lum| {
+        let scaled = key * lum / log_avg;
+        scaled / (1.0 + scaled)
+    })
+}
+
+/// Compresses an HDR image with unbounded non-negative channel values into the range [0, 1]
+/// using Krzysztof Narkowicz's fit of the ACES filmic curve. Operates on luminance to preserve
+/// color ratios
+pub fn aces_filmic(input: &Image<f32>) -> Image<f32> {
+    tone_map_luminance(input, |lum| {
+        (lum * (2.51 * lum + 0.03)) / (lum * (2.43 * lum + 0.59) + 0.14)
+    })
+}
+
+/// Pulls back bright highlights by compressing the luminance above the knee point
+/// `1 - amount / 2` with a smooth roll-off that approaches 1, so that values above 1 are brought
+/// back into range instead of being clipped. Operates on luminance to preserve color ratios, and
+/// clamps the results to [0, 1]
+///
+/// # Arguments
+///
+/// * `amount` - The strength of the recovery, where 0 only clamps. Must be between 0 and 1
+///   (inclusive)
+pub fn recover_highlights(input: &Image<f32>, amount: f32) -> ImgProcResult<Image<f32>> {
+    error::check_in_range(amount, 0.0, 1.0, "amount")?;
+
+    let range = amount / 2.0;
+    let knee = 1.0 - range;
+
+    Ok(tone_map_luminance(input, |lum| {
+        if lum <= knee || range == 0.0 {
+            lum
+        } else {
+            knee + (lum - knee) / (1.0 + (lum - knee) / range)
+        }
+    }))
+}
+
+/// Lifts dark regions by brightening the luminance below 0.5 with a smooth curve that keeps black
+/// at 0 and blends into the unchanged midtones, multiplying the darkest values by up to
+/// `1 + amount`. Operates on luminance to preserve color ratios, and clamps the results to [0, 1]
+///
+/// # Arguments
+///
+/// * `amount` - The strength of the lift, where 0 only clamps. Must be between 0 and 1
+///   (inclusive)
+pub fn recover_shadows(input: &Image<f32>, amount: f32) -> ImgProcResult<Image<f32>> {
+    error::check_in_range(amount, 0.0, 1.0, "amount")?;
+
+    Ok(tone_map_luminance(input, |lum| {
+        let t = lum / SHADOW_RANGE;
+        if t < 1.0 {
+            lum * (1.0 + amount * (1.0 - t) * (1.0 - t))
+        } else {
+            lum
+        }
+    }))
+}
+
+// Returns the Rec. 709 luminance of an RGB pixel, or the value of a grayscale pixel
+fn luminance(channels: &[f32]) -> f32 {
+    if channels.len() >= 3 {
+        0.2126 * channels[0] + 0.7152 * channels[1] + 0.0722 * channels[2]
+    } else {
+        channels[0]
+    }
+}
+
+// Maps the luminance of each pixel through `f` and scales the non-alpha channels by the same
+// ratio, clamping the results to [0, 1]
+fn tone_map_luminance<F>(input: &Image<f32>, f: F) -> Image<f32>
+    where F: Fn(f32) -> f32 {
+    input.map_pixels_if_alpha(|channels, p_out| {
+        let lum = luminance(channels);
+        let ratio = if lum > 0.0 { f(lum) / lum } else { 0.0 };
+
+        for channel in channels.iter() {
+            p_out.push((channel * ratio).clamp(0.0, 1.0));
+        }
+    }, |a| a)
+}
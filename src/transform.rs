@@ -3,20 +3,36 @@
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
-use crate::enums::{Refl, Scale};
-use crate::error;
+use crate::enums::{Anchor, BlendMode, Refl, Scale};
+use crate::{colorspace, error};
 use crate::error::{ImgProcError, ImgProcResult};
 use crate::image::{BaseImage, Image, ImageInfo, Number};
 use crate::util;
 
+#[cfg(feature = "fft")]
+use rustfft::num_complex::Complex;
+
+/// The maximum number of solver iterations in `seamless_clone()`
+const SEAMLESS_CLONE_MAX_ITER: u32 = 10000;
+
+/// The largest change in any value for which the `seamless_clone()` solver is considered converged
+const SEAMLESS_CLONE_TOLERANCE: f32 = 1e-3;
+
+/// The over-relaxation factor of the `seamless_clone()` solver
+const SEAMLESS_CLONE_SOR: f32 = 1.8;
+
+/// The shades of the squares in `flatten_on_checkerboard()`
+const CHECKERBOARD_LIGHT: u8 = 204;
+const CHECKERBOARD_DARK: u8 = 153;
+
 /// Crops an image to a rectangle with upper left corner located at `(x, y)` with width `width`
 /// and height `height`
 #[cfg(not(feature = "rayon"))]
 pub fn crop<T: Number>(input: &Image<T>, x: u32, y: u32, width: u32, height: u32) -> ImgProcResult<Image<T>> {
-    if (x + width) >= input.info().width {
+    if (x + width) > input.info().width {
         return Err(ImgProcError::InvalidArgError(format!("invalid width: input width is {} \
             but x + width is {}", input.info().width, (x + width))));
-    } else if (y + height) >= input.info().height {
+    } else if (y + height) > input.info().height {
         return Err(ImgProcError::InvalidArgError(format!("invalid height: input height is {} \
             but y + height is {}", input.info().height, (y + height))));
     }
@@ -37,10 +53,10 @@ pub fn crop<T: Number>(input: &Image<T>, x: u32, y: u32, width: u32, height: u32
 /// and height `height`
 #[cfg(feature = "rayon")]
 pub fn crop<T: Number>(input: &Image<T>, x: u32, y: u32, width: u32, height: u32) -> ImgProcResult<Image<T>> {
-    if (x + width) >= input.info().width {
+    if (x + width) > input.info().width {
         return Err(ImgProcError::InvalidArgError(format!("invalid width: input width is {} \
             but x + width is {}", input.info().width, (x + width))));
-    } else if (y + height) >= input.info().height {
+    } else if (y + height) > input.info().height {
         return Err(ImgProcError::InvalidArgError(format!("invalid height: input height is {} \
             but y + height is {}", input.info().height, (y + height))));
     }
@@ -57,12 +73,42 @@ pub fn crop<T: Number>(input: &Image<T>, x: u32, y: u32, width: u32, height: u32
     Ok(Image::from_vec_of_slice(width, height, input.info().channels, input.info().alpha, data))
 }
 
+/// Crops an image to the largest rectangle with an aspect ratio of `ratio_w:ratio_h`, positioned
+/// according to `anchor`
+///
+/// # Arguments
+///
+/// * `ratio_w` - Must be at least 1
+/// * `ratio_h` - Must be at least 1
+pub fn crop_to_aspect<T: Number>(input: &Image<T>, ratio_w: u32, ratio_h: u32, anchor: Anchor) -> ImgProcResult<Image<T>> {
+    error::check_in_range(ratio_w, 1, u32::MAX, "ratio_w")?;
+    error::check_in_range(ratio_h, 1, u32::MAX, "ratio_h")?;
+
+    let (width, height) = input.info().wh();
+    let (crop_w, crop_h) = if width as u64 * ratio_h as u64 > height as u64 * ratio_w as u64 {
+        ((height as u64 * ratio_w as u64 / ratio_h as u64).max(1) as u32, height)
+    } else {
+        (width, (width as u64 * ratio_h as u64 / ratio_w as u64).max(1) as u32)
+    };
+
+    let (center_x, center_y) = ((width - crop_w) / 2, (height - crop_h) / 2);
+    let (x, y) = match anchor {
+        Anchor::Center => (center_x, center_y),
+        Anchor::Top => (center_x, 0),
+        Anchor::Bottom => (center_x, height - crop_h),
+        Anchor::Left => (0, center_y),
+        Anchor::Right => (width - crop_w, center_y),
+    };
+
+    crop(input, x, y, crop_w, crop_h)
+}
+
 /// Aligns the top left corner of `front` onto the location `(x, y)` on `back` and superimposes
 /// the two images with weight `alpha` for pixel values of `back` and weight 1 - `alpha` for
 /// pixel values of `front`
 pub fn superimpose(back: &Image<f32>, front: &Image<f32>, x: u32, y: u32, alpha: f32) -> ImgProcResult<Image<f32>> {
-    error::check_equal(back.info().channels, front.info().channels, "image channels")?;
-    error::check_in_range(0.0, 1.0, alpha, "alpha")?;
+    error::check_channel_count(back.info().channels, front.info().channels as usize)?;
+    error::check_in_range(alpha, 0.0, 1.0, "alpha")?;
 
     let mut output = back.clone();
     let width = std::cmp::min(x + front.info().width, back.info().width);
@@ -84,30 +130,394 @@ pub fn superimpose(back: &Image<f32>, front: &Image<f32>, x: u32, y: u32, alpha:
     Ok(output)
 }
 
+/// Aligns the top left corner of `front` onto the location `(x, y)` on `back` and superimposes
+/// the two sRGB images in linear light with weight `alpha` for pixel values of `back` and weight
+/// 1 - `alpha` for pixel values of `front`. Unlike [`superimpose()`](fn.superimpose.html) on
+/// nonlinear sRGB values, this preserves the perceived brightness of the midtones in the blend
+pub fn superimpose_linear(back: &Image<u8>, front: &Image<u8>, x: u32, y: u32, alpha: f32) -> ImgProcResult<Image<u8>> {
+    let back_lin = colorspace::linearize_srgb_f32(back);
+    let front_lin = colorspace::linearize_srgb_f32(front);
+
+    Ok(colorspace::unlinearize_srgb_f32(&superimpose(&back_lin, &front_lin, x, y, alpha)?))
+}
+
+/// Blends two sRGB images in linear light using the per-pixel weights `weights`, where each
+/// output pixel is `a * (1 - w) + b * w` for the corresponding weight `w`. Weights outside of the
+/// range [0, 1] are clamped. Alpha channels are blended in the same way
+///
+/// # Arguments
+///
+/// * `a` - Must have the same dimensions and number of channels as `b`
+/// * `weights` - A single-channel image with the same dimensions as `a`
+pub fn blend_weighted(a: &Image<u8>, b: &Image<u8>, weights: &Image<f32>) -> ImgProcResult<Image<u8>> {
+    error::check_info(a.info(), b.info())?;
+    error::check_channel_count(1, weights.info().channels as usize)?;
+    error::check_equal(a.info().wh(), weights.info().wh(), "image and weights dimensions")?;
+
+    let mut output = colorspace::linearize_srgb_f32(a);
+    let b_lin = colorspace::linearize_srgb_f32(b);
+
+    for i in 0..(output.info().size() as usize) {
+        let w = weights[i][0].clamp(0.0, 1.0);
+        for (out, val) in output[i].iter_mut().zip(b_lin[i].iter()) {
+            *out = *out * (1.0 - w) + val * w;
+        }
+    }
+
+    Ok(colorspace::unlinearize_srgb_f32(&output))
+}
+
+/// Aligns the top left corner of `src` onto the location `(x, y)` on `dst` and seamlessly clones
+/// the region of `src` selected by `mask` into `dst` using Poisson image editing: the gradients of
+/// `src` are preserved inside the region, while the values along its boundary match `dst`. Alpha
+/// channels of `dst` are unchanged
+///
+/// # Arguments
+///
+/// * `src` - Must have the same number of channels as `dst`
+/// * `mask` - A grayscale image with the same dimensions as `src`, where non-zero pixels select
+///   the region to clone
+pub fn seamless_clone(src: &Image<f32>, dst: &Image<f32>, mask: &Image<u8>, x: u32, y: u32) -> ImgProcResult<Image<f32>> {
+    error::check_channel_count(dst.info().channels, src.info().channels as usize)?;
+    error::check_grayscale(mask)?;
+    error::check_equal(src.info().wh(), mask.info().wh(), "src and mask dimensions")?;
+
+    let (width, height) = dst.info().wh();
+    let (src_w, src_h) = src.info().wh();
+    let (w, h) = (width as i64, height as i64);
+    let (x_0, y_0) = (x as i64, y as i64);
+    let x_1 = (x_0 + src_w as i64).min(w);
+    let y_1 = (y_0 + src_h as i64).min(h);
+
+    // Pixels of `dst` in the cloned region, along with their neighbors in `dst`
+    let mut region = Vec::new();
+    let mut region_index = vec![usize::MAX; (width * height) as usize];
+    for j in y_0..y_1 {
+        for i in x_0..x_1 {
+            if mask.get_pixel((i - x_0) as u32, (j - y_0) as u32)[0] != 0 {
+                let neighbors: Vec<(i64, i64)> = [(i - 1, j), (i + 1, j), (i, j - 1), (i, j + 1)].iter()
+                    .filter(|&&(n_x, n_y)| n_x >= 0 && n_y >= 0 && n_x < w && n_y < h)
+                    .copied()
+                    .collect();
+
+                region_index[(j * w + i) as usize] = region.len();
+                region.push((i, j, neighbors));
+            }
+        }
+    }
+
+    let mut output = dst.clone();
+    let channels = dst.info().channels as usize;
+
+    for c in 0..(dst.info().channels_non_alpha() as usize) {
+        let src_val = |i: i64, j: i64| src.get_pixel((i - x_0) as u32, (j - y_0) as u32)[c];
+
+        // The constant part of each equation: the guidance gradients from `src` (where the
+        // neighbor also lies in `src`), plus the fixed `dst` values of neighbors outside of the
+        // region
+        let rhs: Vec<f32> = region.iter().map(|(i, j, neighbors)| {
+            neighbors.iter().map(|&(n_x, n_y)| {
+                let guidance = if n_x >= x_0 && n_y >= y_0 && n_x < x_1 && n_y < y_1 {
+                    src_val(*i, *j) - src_val(n_x, n_y)
+                } else {
+                    0.0
+                };
+                let boundary = if region_index[(n_y * w + n_x) as usize] == usize::MAX {
+                    dst.get_pixel(n_x as u32, n_y as u32)[c]
+                } else {
+                    0.0
+                };
+
+                guidance + boundary
+            }).sum()
+        }).collect();
+
+        // Solve with successive over-relaxation, starting from the values of `src`
+        let mut vals: Vec<f32> = region.iter().map(|(i, j, _)| src_val(*i, *j)).collect();
+        for _ in 0..SEAMLESS_CLONE_MAX_ITER {
+            let mut max_change: f32 = 0.0;
+
+            for (k, (_, _, neighbors)) in region.iter().enumerate() {
+                if neighbors.is_empty() {
+                    continue;
+                }
+
+                let sum: f32 = neighbors.iter()
+                    .map(|&(n_x, n_y)| region_index[(n_y * w + n_x) as usize])
+                    .filter(|&n| n != usize::MAX)
+                    .map(|n| vals[n])
+                    .sum();
+                let new_val = (rhs[k] + sum) / neighbors.len() as f32;
+                let change = SEAMLESS_CLONE_SOR * (new_val - vals[k]);
+
+                vals[k] += change;
+                max_change = max_change.max(change.abs());
+            }
+
+            if max_change < SEAMLESS_CLONE_TOLERANCE {
+                break;
+            }
+        }
+
+        for (k, (i, j, _)) in region.iter().enumerate() {
+            output.data_mut()[((j * w + i) as usize) * channels + c] = vals[k];
+        }
+    }
+
+    Ok(output)
+}
+
 /// Aligns the top left corner of `front` onto the location `(x, y)` on `back` and overlays
 /// `front` on `back`
 pub fn overlay<T: Number>(back: &Image<T>, front: &Image<T>, x: u32, y: u32) -> ImgProcResult<Image<T>> {
-    error::check_equal(back.info().channels, front.info().channels, "image channels")?;
+    error::check_channel_count(back.info().channels, front.info().channels as usize)?;
 
     let mut output = back.clone();
-    let width = std::cmp::min(x + front.info().width, back.info().width);
-    let height = std::cmp::min(y + front.info().height, back.info().height);
+    if x >= back.info().width || y >= back.info().height {
+        return Ok(output);
+    }
+
+    // Clip `front` at the boundaries of `back`
+    let width = std::cmp::min(front.info().width, back.info().width - x);
+    let height = std::cmp::min(front.info().height, back.info().height - y);
+    let row_len = (width * front.info().channels as u32) as usize;
+
+    let mut data = Vec::with_capacity(row_len * height as usize);
+    for j in 0..height {
+        let start = front.index(0, j);
+        data.extend_from_slice(&front.data()[start..(start + row_len)]);
+    }
+
+    output.set_region(x, y, width, height, &data)?;
+
+    Ok(output)
+}
+
+/// Aligns the top left corner of `front` onto the location `(x, y)` on `back` and composites
+/// `front` over `back` using the alpha channel of `front`, so that each color channel is
+/// `front * alpha + back * (1 - alpha)`. If `back` also has an alpha channel, the standard
+/// "source over" operator is used for both the color and alpha channels
+pub fn overlay_alpha(back: &Image<u8>, front: &Image<u8>, x: u32, y: u32) -> ImgProcResult<Image<u8>> {
+    if !front.info().alpha {
+        return Err(ImgProcError::InvalidArgError("front does not have an alpha channel".to_string()));
+    }
+    error::check_channel_count(back.info().channels_non_alpha(), front.info().channels_non_alpha() as usize)?;
+
+    let mut output = back.clone();
+    let width = std::cmp::min(x.saturating_add(front.info().width), back.info().width);
+    let height = std::cmp::min(y.saturating_add(front.info().height), back.info().height);
+    let channels = back.info().channels_non_alpha() as usize;
+    let back_alpha = back.info().alpha;
 
     for j in y..height {
         for i in x..width {
-            output.set_pixel(i, j, front.get_pixel(i - x, j - y));
+            let p_front = front.get_pixel(i - x, j - y);
+            let p_out = output.get_pixel_mut(i, j);
+
+            let a_front = p_front[channels] as f32 / 255.0;
+            let a_back = if back_alpha { p_out[channels] as f32 / 255.0 } else { 1.0 };
+            let a_out = a_front + a_back * (1.0 - a_front);
+
+            for c in 0..channels {
+                let val = if a_out > 0.0 {
+                    (p_front[c] as f32 * a_front + p_out[c] as f32 * a_back * (1.0 - a_front)) / a_out
+                } else {
+                    0.0
+                };
+                p_out[c] = val.round() as u8;
+            }
+
+            if back_alpha {
+                p_out[channels] = (a_out * 255.0).round() as u8;
+            }
         }
     }
 
     Ok(output)
 }
 
+/// Composites an image with an alpha channel over a solid `background` color, returning an opaque
+/// image with the non-alpha channels of `input`. This is useful before writing an image to a
+/// format that cannot store alpha
+///
+/// # Arguments
+///
+/// * `input` - Must have an alpha channel
+/// * `background` - Must have a length equal to the number of non-alpha channels of `input`
+pub fn flatten(input: &Image<u8>, background: &[u8]) -> ImgProcResult<Image<u8>> {
+    if !input.info().alpha {
+        return Err(ImgProcError::InvalidArgError("input does not have an alpha channel".to_string()));
+    }
+    error::check_channel_count(input.info().channels_non_alpha(), background.len())?;
+
+    let (width, height) = input.info().wh();
+    let data = background.iter().copied().cycle().take((width * height) as usize * background.len()).collect();
+    let back = Image::from_vec(width, height, input.info().channels_non_alpha(), false, data);
+
+    overlay_alpha(&back, input, 0, 0)
+}
+
+/// Composites an image with an alpha channel over a light and dark gray checkerboard with squares
+/// of side length `square_size`, as is conventional for previewing transparent images. Returns
+/// an opaque image with the non-alpha channels of `input`
+///
+/// # Arguments
+///
+/// * `input` - Must have an alpha channel
+/// * `square_size` - Must be at least 1
+pub fn flatten_on_checkerboard(input: &Image<u8>, square_size: u32) -> ImgProcResult<Image<u8>> {
+    error::check_in_range(square_size, 1, u32::MAX, "square_size")?;
+
+    let (width, height) = input.info().wh();
+    let channels = input.info().channels_non_alpha();
+    let mut board = Image::blank(ImageInfo::new(width, height, channels, false));
+    for y in 0..height {
+        for x in 0..width {
+            let shade = if ((x / square_size) ^ (y / square_size)) & 1 == 0 {
+                CHECKERBOARD_LIGHT
+            } else {
+                CHECKERBOARD_DARK
+            };
+            board.set_pixel(x, y, &vec![shade; channels as usize]);
+        }
+    }
+
+    overlay_alpha(&board, input, 0, 0)
+}
+
+/// Returns an RGBA copy of an image in which the pixels outside of a rectangle with rounded
+/// corners of radius `radius` are transparent. The edges of the corners are anti-aliased by
+/// scaling the alpha of each pixel by the fraction of it covered by the rounded rectangle.
+/// Grayscale images are expanded to RGB, and images without an alpha channel are given an opaque
+/// one
+///
+/// # Arguments
+///
+/// * `input` - Must be a grayscale or RGB image, with or without an alpha channel
+/// * `radius` - Must be at most half of the smaller dimension of `input`
+pub fn round_corners(input: &Image<u8>, radius: u32) -> ImgProcResult<Image<u8>> {
+    let (width, height) = input.info().wh();
+    error::check_in_range(radius, 0, std::cmp::min(width, height) / 2, "radius")?;
+
+    let mut output = match (input.info().channels_non_alpha(), input.info().alpha) {
+        (1, _) => input.gray_to_rgba(255)?,
+        (3, true) => input.clone(),
+        (3, false) => {
+            let data = input.data().chunks_exact(3).flat_map(|p| vec![p[0], p[1], p[2], 255]).collect();
+            Image::from_vec(width, height, 4, true, data)
+        },
+        _ => return Err(ImgProcError::InvalidArgError("input is not a grayscale or RGB image".to_string())),
+    };
+    if radius == 0 {
+        return Ok(output);
+    }
+
+    let r = radius as f32;
+    for y in 0..height {
+        for x in 0..width {
+            // Distance from the pixel center to the nearest point of the rectangle formed by the
+            // centers of the corner circles
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let dx = px - px.clamp(r, width as f32 - r);
+            let dy = py - py.clamp(r, height as f32 - r);
+            let dist = (dx * dx + dy * dy).sqrt();
+
+            if dist > 0.0 {
+                let coverage = (r - dist + 0.5).clamp(0.0, 1.0);
+                let alpha = &mut output.get_pixel_mut(x, y)[3];
+                *alpha = (*alpha as f32 * coverage).round() as u8;
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Aligns the top left corner of `front` onto the location `(x, y)` on `back` and blends the
+/// non-alpha channels of the overlapping region using `mode`. The alpha channel of `back`, if
+/// present, is kept
+pub fn blend_modes(back: &Image<u8>, front: &Image<u8>, x: u32, y: u32, mode: BlendMode) -> ImgProcResult<Image<u8>> {
+    error::check_channel_count(back.info().channels, front.info().channels as usize)?;
+
+    let mut output = back.clone();
+    let width = std::cmp::min(x.saturating_add(front.info().width), back.info().width);
+    let height = std::cmp::min(y.saturating_add(front.info().height), back.info().height);
+    let channels = back.info().channels_non_alpha() as usize;
+
+    for j in y..height {
+        for i in x..width {
+            let p_front = front.get_pixel(i - x, j - y);
+            let p_out = output.get_pixel_mut(i, j);
+
+            for c in 0..channels {
+                let a = p_out[c] as f32 / 255.0;
+                let b = p_front[c] as f32 / 255.0;
+
+                let val = match mode {
+                    BlendMode::Multiply => a * b,
+                    BlendMode::Screen => 1.0 - (1.0 - a) * (1.0 - b),
+                    BlendMode::Overlay => {
+                        if a < 0.5 {
+                            2.0 * a * b
+                        } else {
+                            1.0 - 2.0 * (1.0 - a) * (1.0 - b)
+                        }
+                    },
+                    BlendMode::Darken => a.min(b),
+                    BlendMode::Lighten => a.max(b),
+                    BlendMode::Difference => (a - b).abs(),
+                    BlendMode::Add => (a + b).min(1.0),
+                };
+
+                p_out[c] = (val * 255.0).round() as u8;
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Same as [`superimpose()`](fn.superimpose.html), but instead of clipping `front` at the
+/// boundaries of `back`, grows the output to contain both images. The part of the output not
+/// covered by `back` is filled with `background` before `front` is superimposed
+pub fn superimpose_expand(back: &Image<f32>, front: &Image<f32>, x: u32, y: u32, alpha: f32, background: &[f32]) -> ImgProcResult<Image<f32>> {
+    let canvas = expand_canvas(back, front, x, y, background)?;
+    superimpose(&canvas, front, x, y, alpha)
+}
+
+/// Same as [`overlay()`](fn.overlay.html), but instead of clipping `front` at the boundaries of
+/// `back`, grows the output to contain both images. The part of the output not covered by
+/// either image is filled with `background`
+pub fn overlay_expand<T: Number>(back: &Image<T>, front: &Image<T>, x: u32, y: u32, background: &[T]) -> ImgProcResult<Image<T>> {
+    let canvas = expand_canvas(back, front, x, y, background)?;
+    overlay(&canvas, front, x, y)
+}
+
+/// Returns a copy of `back` grown to contain `front` placed at `(x, y)`, with the new area filled
+/// with `background`
+fn expand_canvas<T: Number>(back: &Image<T>, front: &Image<T>, x: u32, y: u32, background: &[T]) -> ImgProcResult<Image<T>> {
+    error::check_channel_count(back.info().channels, front.info().channels as usize)?;
+    error::check_channel_count(back.info().channels, background.len())?;
+
+    let width = std::cmp::max(back.info().width, x + front.info().width);
+    let height = std::cmp::max(back.info().height, y + front.info().height);
+    let mut output = Image::blank(ImageInfo::new(width, height, back.info().channels, back.info().alpha));
+
+    for i in 0..(output.info().size() as usize) {
+        output.set_pixel_indexed(i, background);
+    }
+    output.set_region(0, 0, back.info().width, back.info().height, back.data())?;
+
+    Ok(output)
+}
+
 ////////////////////////////
 // Affine transformations
 ////////////////////////////
 
 /// Scales an image horizontally by `x_factor` and vertically by `y_factor` using the specified
-/// `method`
+/// `method`. When downscaling, the `Bicubic`, `Lanczos`, `Mitchell`, and `CatmullRom` kernels are
+/// widened by `1 / factor` to prevent aliasing
 #[cfg(not(feature = "rayon"))]
 pub fn scale(input: &Image<f32>, x_factor: f32, y_factor: f32, method: Scale) -> ImgProcResult<Image<f32>> {
     error::check_non_neg(x_factor, "x_factor")?;
@@ -126,17 +536,24 @@ pub fn scale(input: &Image<f32>, x_factor: f32, y_factor: f32, method: Scale) ->
             scale_bilinear(input, &mut output, x_factor, y_factor);
         },
         Scale::Bicubic => {
-            scale_bicubic(input, &mut output, x_factor, y_factor);
+            scale_cubic(input, &mut output, x_factor, y_factor, util::cubic_weighting_fn);
         },
         Scale::Lanczos => {
             scale_lanczos_resampling(input, &mut output, x_factor, y_factor, 3);
+        },
+        Scale::Mitchell => {
+            scale_cubic(input, &mut output, x_factor, y_factor, util::mitchell_fn);
+        },
+        Scale::CatmullRom => {
+            scale_cubic(input, &mut output, x_factor, y_factor, util::catmull_rom_fn);
         }
     }
 
     Ok(output)
 }
 /// Scales an image horizontally by `x_factor` and vertically by `y_factor` using the specified
-/// `method`
+/// `method`. When downscaling, the `Bicubic`, `Lanczos`, `Mitchell`, and `CatmullRom` kernels are
+/// widened by `1 / factor` to prevent aliasing
 #[cfg(feature = "rayon")]
 pub fn scale(input: &Image<f32>, x_factor: f32, y_factor: f32, method: Scale) -> ImgProcResult<Image<f32>> {
     error::check_non_neg(x_factor, "x_factor")?;
@@ -154,10 +571,16 @@ pub fn scale(input: &Image<f32>, x_factor: f32, y_factor: f32, method: Scale) ->
             Ok(scale_bilinear(input, &info, x_factor, y_factor))
         },
         Scale::Bicubic => {
-            Ok(scale_bicubic(input, &info, x_factor, y_factor))
+            Ok(scale_cubic(input, &info, x_factor, y_factor, util::cubic_weighting_fn))
         },
         Scale::Lanczos => {
             Ok(scale_lanczos_resampling(input, &info, x_factor, y_factor, 3))
+        },
+        Scale::Mitchell => {
+            Ok(scale_cubic(input, &info, x_factor, y_factor, util::mitchell_fn))
+        },
+        Scale::CatmullRom => {
+            Ok(scale_cubic(input, &info, x_factor, y_factor, util::catmull_rom_fn))
         }
     }
 }
@@ -192,6 +615,139 @@ pub fn scale_lanczos(input: &Image<f32>, x_factor: f32, y_factor: f32, size: u32
     Ok(scale_lanczos_resampling(input, &info, x_factor, y_factor, size))
 }
 
+/// Scales an image horizontally by `x_factor` and vertically by `y_factor` by setting each output
+/// pixel to the exact average of the input area covered by it, where input pixels along the edges
+/// of the area are weighted by the fraction of them that is covered. The fully covered part of the
+/// area is summed using a summed-area table. Intended for downscaling, where it avoids the
+/// aliasing of the interpolation methods in [`scale()`](fn.scale.html)
+pub fn scale_area(input: &Image<f32>, x_factor: f32, y_factor: f32) -> ImgProcResult<Image<f32>> {
+    error::check_non_neg(x_factor, "x_factor")?;
+    error::check_non_neg(y_factor, "y_factor")?;
+
+    let (w_in, h_in, channels) = input.info().whc();
+    let width = (w_in as f32 * x_factor).round() as u32;
+    let height = (h_in as f32 * y_factor).round() as u32;
+    let mut output = Image::blank(ImageInfo::new(width, height, channels, input.info().alpha));
+
+    let table = util::generate_summed_area_table(input);
+    let coverage_x: Vec<Vec<(u32, u32, f32)>> = (0..width)
+        .map(|x| area_coverage(x, x_factor, w_in))
+        .collect();
+    let mut p_out = vec![0.0; channels as usize];
+
+    for y in 0..height {
+        let coverage_y = area_coverage(y, y_factor, h_in);
+
+        for (x, ranges_x) in coverage_x.iter().enumerate() {
+            p_out.iter_mut().for_each(|c| *c = 0.0);
+            let mut area = 0.0;
+
+            for &(y_0, y_1, weight_y) in coverage_y.iter() {
+                for &(x_0, x_1, weight_x) in ranges_x.iter() {
+                    let weight = weight_x * weight_y;
+                    let sum = util::rectangular_intensity_sum(&table, x_0, y_0, x_1, y_1);
+
+                    for (out, val) in p_out.iter_mut().zip(sum.iter()) {
+                        *out += val * weight;
+                    }
+                    area += ((x_1 - x_0 + 1) * (y_1 - y_0 + 1)) as f32 * weight;
+                }
+            }
+
+            p_out.iter_mut().for_each(|c| *c /= area);
+            output.set_pixel(x as u32, y, &p_out);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Scales an image horizontally by `x_factor` and vertically by `y_factor` by separable
+/// resampling with the 1D interpolation kernel `kernel`, which allows kernels other than the ones
+/// in [`scale()`](fn.scale.html) (e.g. Mitchell-Netravali or Catmull-Rom). As in `scale()`, the
+/// kernel is widened by `1 / factor` when downscaling to prevent aliasing. Weights are normalized
+/// so that they sum to 1
+///
+/// # Arguments
+///
+/// * `kernel` - The kernel, as a function of the distance (in input pixels) from the sample point
+/// * `support` - The radius outside of which `kernel` is zero. Must be positive
+pub fn scale_kernel(input: &Image<f32>, x_factor: f32, y_factor: f32, kernel: impl Fn(f32) -> f32,
+                    support: f32) -> ImgProcResult<Image<f32>> {
+    error::check_non_neg(x_factor, "x_factor")?;
+    error::check_non_neg(y_factor, "y_factor")?;
+    if support <= 0.0 {
+        return Err(ImgProcError::InvalidArgError("support must be positive".to_string()));
+    }
+
+    let (w_in, h_in, channels) = input.info().whc();
+    let width = (w_in as f32 * x_factor).round() as u32;
+    let height = (h_in as f32 * y_factor).round() as u32;
+
+    // Resample the rows, then the columns
+    let mut horz = Image::blank(ImageInfo::new(width, h_in, channels, input.info().alpha));
+    for x in 0..width {
+        let weights = resample_weights(x, x_factor, w_in, support, &kernel);
+
+        for y in 0..h_in {
+            let p_out: &mut [f32] = horz.get_pixel_mut(x, y);
+            for &(x_in, weight) in weights.iter() {
+                for (out, val) in p_out.iter_mut().zip(input.get_pixel(x_in, y)) {
+                    *out += val * weight;
+                }
+            }
+        }
+    }
+
+    let mut output = Image::blank(ImageInfo::new(width, height, channels, input.info().alpha));
+    for y in 0..height {
+        let weights = resample_weights(y, y_factor, h_in, support, &kernel);
+
+        for x in 0..width {
+            let p_out: &mut [f32] = output.get_pixel_mut(x, y);
+            for &(y_in, weight) in weights.iter() {
+                for (out, val) in p_out.iter_mut().zip(horz.get_pixel(x, y_in)) {
+                    *out += val * weight;
+                }
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Resizes an image to `width x height`, choosing the scaling method from the scale factors. Axes
+/// that are shrunk are downscaled using [`scale_area()`](fn.scale_area.html); axes that are
+/// enlarged are then upscaled using Lanczos resampling for factors up to 2, or bicubic
+/// interpolation for larger factors, which rings less when enlarging greatly
+///
+/// # Arguments
+///
+/// * `width` - Must be at least 1
+/// * `height` - Must be at least 1
+pub fn resize_auto(input: &Image<f32>, width: u32, height: u32) -> ImgProcResult<Image<f32>> {
+    error::check_in_range(width, 1, u32::MAX, "width")?;
+    error::check_in_range(height, 1, u32::MAX, "height")?;
+
+    let (w_in, h_in) = input.info().wh();
+    let x_factor = width as f32 / w_in as f32;
+    let y_factor = height as f32 / h_in as f32;
+
+    let mut output = if x_factor < 1.0 || y_factor < 1.0 {
+        scale_area(input, x_factor.min(1.0), y_factor.min(1.0))?
+    } else {
+        input.clone()
+    };
+
+    if x_factor > 1.0 || y_factor > 1.0 {
+        let method = if x_factor.max(y_factor) <= 2.0 { Scale::Lanczos } else { Scale::Bicubic };
+        output = scale(&output, width as f32 / output.info().width as f32,
+                       height as f32 / output.info().height as f32, method)?;
+    }
+
+    Ok(output)
+}
+
 /// Translates an image to the position with upper left corner located at `(x, y)`. Fills in the
 /// rest of the image as black
 pub fn translate<T: Number>(input: &Image<T>, x: u32, y: u32) -> ImgProcResult<Image<T>> {
@@ -258,6 +814,151 @@ pub fn rotate(input: &Image<f32>, degrees: f32) -> ImgProcResult<Image<f32>> {
     Ok(output)
 }
 
+/// Rotates an image `degrees` degrees counterclockwise around the center of the image, keeping
+/// the dimensions of the input image. Corners not covered by the rotated image are filled in with
+/// `fill`
+pub fn rotate_in_place(input: &Image<f32>, degrees: f32, fill: &[f32]) -> ImgProcResult<Image<f32>> {
+    error::check_channel_count(input.info().channels, fill.len())?;
+
+    let (width, height) = input.info().wh();
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let center_x = (width as f32 - 1.0) / 2.0;
+    let center_y = (height as f32 - 1.0) / 2.0;
+    let mut output = Image::blank(input.info());
+
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 - center_x;
+            let dy = y as f32 - center_y;
+
+            // Inverse mapping from output to input coordinates
+            let src_x = center_x + dx * cos - dy * sin;
+            let src_y = center_y + dx * sin + dy * cos;
+
+            if src_x > -0.5 && src_x < width as f32 - 0.5 && src_y > -0.5 && src_y < height as f32 - 0.5 {
+                output.set_pixel(x, y, &sample_bilinear(input, src_x, src_y));
+            } else {
+                output.set_pixel(x, y, fill);
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Rotates `src` `degrees` degrees counterclockwise around its center and renders it onto `dst`
+/// so that its center lies at `(center_x, center_y)`. Pixels of `dst` not covered by the rotated
+/// image are unchanged
+///
+/// # Arguments
+///
+/// * `src` - Must have the same number of channels as `dst`
+pub fn rotate_onto(src: &Image<f32>, dst: &mut Image<f32>, center_x: f32, center_y: f32, degrees: f32) -> ImgProcResult<()> {
+    error::check_channel_count(dst.info().channels, src.info().channels as usize)?;
+
+    let (width, height) = src.info().wh();
+    let (dst_w, dst_h) = dst.info().wh();
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let src_center_x = (width as f32 - 1.0) / 2.0;
+    let src_center_y = (height as f32 - 1.0) / 2.0;
+
+    // Only visit the pixels of `dst` within the circle enclosing the rotated image
+    let radius = (width as f32).hypot(height as f32) / 2.0;
+    let range = |c: f32, max: u32| -> (u32, u32) {
+        ((c - radius).floor().clamp(0.0, max as f32) as u32, (c + radius).ceil().clamp(0.0, max as f32) as u32)
+    };
+    let (x_0, x_1) = range(center_x, dst_w);
+    let (y_0, y_1) = range(center_y, dst_h);
+
+    for y in y_0..y_1 {
+        for x in x_0..x_1 {
+            let dx = x as f32 - center_x;
+            let dy = y as f32 - center_y;
+
+            // Inverse mapping from output to input coordinates
+            let src_x = src_center_x + dx * cos - dy * sin;
+            let src_y = src_center_y + dx * sin + dy * cos;
+
+            if src_x > -0.5 && src_x < width as f32 - 0.5 && src_y > -0.5 && src_y < height as f32 - 0.5 {
+                dst.set_pixel(x, y, &sample_bilinear(src, src_x, src_y));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Straightens a scanned document containing dark text on a light background by estimating the
+/// skew angle of its text lines and rotating it with
+/// [`rotate_in_place()`](fn.rotate_in_place.html), filling the uncovered corners with white.
+/// The skew angle is the angle within `[-max_angle, max_angle]` degrees that maximizes the
+/// variance of the horizontal projection profile of the rotated text
+///
+/// # Arguments
+///
+/// * `max_angle` - Must be between 0 and 45 (inclusive)
+pub fn deskew(input: &Image<u8>, max_angle: f32) -> ImgProcResult<Image<u8>> {
+    error::check_in_range(max_angle, 0.0, 45.0, "max_angle")?;
+
+    let (width, height) = input.info().wh();
+    let center_x = (width as f32 - 1.0) / 2.0;
+    let center_y = (height as f32 - 1.0) / 2.0;
+    let channels = input.info().channels_non_alpha() as usize;
+
+    // Darkness of each pixel, relative to its position from the center
+    let mut ink = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let p = input.get_pixel(x, y);
+            let val = 255.0 - p[..channels].iter().map(|&c| c as f32).sum::<f32>() / channels as f32;
+            if val > 0.0 {
+                ink.push((x as f32 - center_x, y as f32 - center_y, val));
+            }
+        }
+    }
+
+    let num_bins = ((width * width + height * height) as f32).sqrt().ceil() as usize + 2;
+    let mut bins = vec![0.0; num_bins];
+    let mut score = |degrees: f32| -> f32 {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        bins.iter_mut().for_each(|b| *b = 0.0);
+
+        for &(dx, dy, val) in ink.iter() {
+            let rotated_y = dy * cos - dx * sin;
+            bins[(rotated_y + num_bins as f32 / 2.0) as usize] += val;
+        }
+
+        // The sum of the bins is fixed, so the sum of squares is maximized with the variance
+        bins.iter().map(|b| b * b).sum()
+    };
+
+    // Search in steps of 0.5 degrees, then refine around the best angle in steps of 0.05 degrees
+    let mut best = (0.0, score(0.0));
+    let num_steps = (max_angle / 0.5).floor() as i32;
+    let candidates: Vec<f32> = (-num_steps..=num_steps).map(|i| i as f32 * 0.5).collect();
+    for angle in candidates {
+        let curr = score(angle);
+        if curr > best.1 {
+            best = (angle, curr);
+        }
+    }
+
+    let coarse = best.0;
+    for i in -10..=10 {
+        let angle = coarse + i as f32 * 0.05;
+        if angle.abs() <= max_angle {
+            let curr = score(angle);
+            if curr > best.1 {
+                best = (angle, curr);
+            }
+        }
+    }
+
+    let fill = vec![255.0; input.info().channels as usize];
+    let output = rotate_in_place(&input.clone().into(), best.0, &fill)?;
+    Ok(output.into())
+}
+
 /// Reflects an image across the specified axis
 pub fn reflect<T: Number>(input: &Image<T>, axis: Refl) -> ImgProcResult<Image<T>> {
     let mut output = Image::blank(input.info());
@@ -317,6 +1018,240 @@ pub fn shear(input: &Image<f32>, shear_x: f32, shear_y: f32) -> ImgProcResult<Im
     Ok(output)
 }
 
+/// Pixelates an image by replacing each `block_size` x `block_size` block with its average
+/// color. Blocks along the right and bottom edges are smaller if the dimensions of the image are
+/// not divisible by `block_size`. For integer types, the averages are truncated
+pub fn pixelate<T: Number>(input: &Image<T>, block_size: u32) -> ImgProcResult<Image<T>> {
+    error::check_in_range(block_size, 1, u32::MAX, "block_size")?;
+
+    let (width, height, channels) = input.info().whc();
+    let mut output = Image::blank(input.info());
+    let mut p_out = vec![0.into(); channels as usize];
+
+    for y0 in (0..height).step_by(block_size as usize) {
+        let y1 = std::cmp::min(y0 + block_size, height);
+
+        for x0 in (0..width).step_by(block_size as usize) {
+            let x1 = std::cmp::min(x0 + block_size, width);
+
+            for (c, channel) in p_out.iter_mut().enumerate() {
+                *channel = util::mean((y0..y1)
+                    .flat_map(|y| (x0..x1).map(move |x| input.get_pixel(x, y)[c])));
+            }
+
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    output.set_pixel(x, y, &p_out);
+                }
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+////////////////////
+// Distortions
+////////////////////
+
+/// Simulates lateral chromatic aberration by displacing the red channel radially outward and
+/// the blue channel radially inward from the center of the image, while the green channel is
+/// unchanged. `shift` is the displacement in pixels at the corners of the image and scales
+/// linearly with the distance from the center; a negative `shift` reverses the directions,
+/// which can be used to correct existing aberration. The alpha channel, if present, is unchanged
+pub fn chromatic_aberration(input: &Image<u8>, shift: f32) -> ImgProcResult<Image<u8>> {
+    let channels = input.info().channels;
+    if channels != 3 && channels != 4 {
+        return Err(ImgProcError::InvalidArgError("input is not an RGB or RGBA image".to_string()));
+    }
+
+    let (width, height) = input.info().wh();
+    let center_x = (width as f32 - 1.0) / 2.0;
+    let center_y = (height as f32 - 1.0) / 2.0;
+    let max_dist = (center_x * center_x + center_y * center_y).sqrt();
+    let scale = if max_dist > 0.0 { shift / max_dist } else { 0.0 };
+
+    let input_f32: Image<f32> = input.clone().into();
+    let mut output = input.clone();
+
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 - center_x;
+            let dy = y as f32 - center_y;
+
+            let red = sample_bilinear(&input_f32, center_x + dx * (1.0 - scale),
+                                      center_y + dy * (1.0 - scale));
+            let blue = sample_bilinear(&input_f32, center_x + dx * (1.0 + scale),
+                                       center_y + dy * (1.0 + scale));
+
+            let p_out = output.get_pixel_mut(x, y);
+            p_out[0] = red[0].round().clamp(0.0, 255.0) as u8;
+            p_out[2] = blue[2].round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Applies the radial distortion model `r_src = r * (1 + k1 * r^2 + k2 * r^4)` to an image, where
+/// `r` is the distance from the center of the image normalized so that the corners lie at `r = 1`.
+/// Positive coefficients correct barrel distortion and negative coefficients correct pincushion
+/// distortion. Regions that map to outside of the input image are filled in as black
+pub fn lens_distortion(input: &Image<f32>, k1: f32, k2: f32) -> ImgProcResult<Image<f32>> {
+    let (width, height) = input.info().wh();
+    let center_x = (width as f32 - 1.0) / 2.0;
+    let center_y = (height as f32 - 1.0) / 2.0;
+    let norm = (center_x * center_x + center_y * center_y).sqrt().max(1.0);
+    let mut output = Image::blank(input.info());
+
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 - center_x;
+            let dy = y as f32 - center_y;
+            let r2 = (dx * dx + dy * dy) / (norm * norm);
+            let factor = 1.0 + k1 * r2 + k2 * r2 * r2;
+
+            let src_x = center_x + dx * factor;
+            let src_y = center_y + dy * factor;
+
+            if src_x >= 0.0 && src_x <= (width - 1) as f32 && src_y >= 0.0 && src_y <= (height - 1) as f32 {
+                output.set_pixel(x, y, &sample_bilinear(input, src_x, src_y));
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Swirls an image around its center. Each pixel within `radius` of the center is rotated
+/// by an angle of `strength` radians at the center, decreasing linearly to zero at
+/// `radius`. Pixels outside of `radius` are unchanged
+pub fn swirl(input: &Image<f32>, strength: f32, radius: f32) -> ImgProcResult<Image<f32>> {
+    if radius <= 0.0 {
+        return Err(ImgProcError::InvalidArgError("radius must be positive".to_string()));
+    }
+
+    let (width, height) = input.info().wh();
+    let center_x = (width as f32 - 1.0) / 2.0;
+    let center_y = (height as f32 - 1.0) / 2.0;
+    let mut output = input.clone();
+
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 - center_x;
+            let dy = y as f32 - center_y;
+            let dist = (dx * dx + dy * dy).sqrt();
+
+            if dist < radius {
+                // Inverse mapping: rotate back by the angle applied at this distance
+                let (sin, cos) = (strength * (1.0 - dist / radius)).sin_cos();
+                let src_x = center_x + dx * cos - dy * sin;
+                let src_y = center_y + dx * sin + dy * cos;
+
+                output.set_pixel(x, y, &sample_bilinear(input, src_x, src_y));
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Remaps an image using the coordinate maps `map_x` and `map_y`: each output pixel `(x, y)` is
+/// sampled from `input` at `(map_x[x, y], map_y[x, y])` using the specified interpolation `method`.
+/// Coordinates outside the input image are clamped to the nearest edge pixel
+///
+/// # Arguments
+///
+/// * `map_x` - Must be a single-channel image with the same dimensions as `map_y`, which
+///   determines the dimensions of the output
+pub fn remap(input: &Image<f32>, map_x: &Image<f32>, map_y: &Image<f32>, method: Scale) -> ImgProcResult<Image<f32>> {
+    error::check_channel_count(1, map_x.info().channels as usize)?;
+    error::check_channel_count(1, map_y.info().channels as usize)?;
+    error::check_equal(map_x.info().wh(), map_y.info().wh(), "map dimensions")?;
+
+    let (width, height) = map_x.info().wh();
+    let mut output = Image::blank(ImageInfo::new(width, height, input.info().channels, input.info().alpha));
+
+    for y in 0..height {
+        for x in 0..width {
+            let src_x = map_x.get_pixel(x, y)[0];
+            let src_y = map_y.get_pixel(x, y)[0];
+
+            let pixel = match method {
+                Scale::NearestNeighbor => sample_nearest_neighbor(input, src_x, src_y).to_vec(),
+                Scale::Bilinear => sample_bilinear(input, src_x, src_y),
+                Scale::Bicubic => sample_kernel(input, src_x, src_y, 2, util::cubic_weighting_fn),
+                Scale::Lanczos => sample_kernel(input, src_x, src_y, 3, |d| util::lanczos_kernel(d, 3.0)),
+                Scale::Mitchell => sample_kernel(input, src_x, src_y, 2, util::mitchell_fn),
+                Scale::CatmullRom => sample_kernel(input, src_x, src_y, 2, util::catmull_rom_fn),
+            };
+
+            output.set_pixel(x, y, &pixel);
+        }
+    }
+
+    Ok(output)
+}
+
+//////////////////
+// Registration
+//////////////////
+
+/// Estimates the translation `(dx, dy)` that maps `b` onto `a` (so that `a(x, y)` is approximately
+/// `b(x - dx, y - dy)`) using phase correlation, with sub-pixel precision. The non-alpha channels
+/// are averaged, and the images are treated as periodic
+///
+/// # Arguments
+///
+/// * `a` - Must have the same dimensions and channels as `b`
+#[cfg(feature = "fft")]
+pub fn phase_correlate(a: &Image<f32>, b: &Image<f32>) -> ImgProcResult<(f32, f32)> {
+    error::check_info(a.info(), b.info())?;
+
+    let (width, height) = a.info().wh();
+    let (w, h) = (width as usize, height as usize);
+    let channels = a.info().channels_non_alpha() as usize;
+
+    let to_complex = |img: &Image<f32>| -> Vec<Complex<f32>> {
+        (0..(w * h)).map(|i| {
+            let p = &img[i];
+            Complex::new(p[..channels].iter().sum::<f32>() / channels as f32, 0.0)
+        }).collect()
+    };
+
+    let mut fft_a = to_complex(a);
+    let mut fft_b = to_complex(b);
+    util::fft_2d(&mut fft_a, w, h, false);
+    util::fft_2d(&mut fft_b, w, h, false);
+
+    // Normalized cross-power spectrum
+    for (val_a, val_b) in fft_a.iter_mut().zip(fft_b.iter()) {
+        let cross = *val_a * val_b.conj();
+        let norm = cross.norm();
+        *val_a = if norm > f32::EPSILON { cross / norm } else { Complex::new(0.0, 0.0) };
+    }
+    util::fft_2d(&mut fft_a, w, h, true);
+
+    let peak = (0..(w * h)).max_by(|&i, &j| fft_a[i].re.partial_cmp(&fft_a[j].re).unwrap()).unwrap();
+    let (peak_x, peak_y) = (peak % w, peak / w);
+    let corr = |x: usize, y: usize| fft_a[(y % h) * w + (x % w)].re;
+
+    // Refine the peak by fitting a parabola through it and its neighbors in each direction
+    let refine = |prev: f32, curr: f32, next: f32| -> f32 {
+        let denom = prev - 2.0 * curr + next;
+        if denom.abs() > f32::EPSILON { 0.5 * (prev - next) / denom } else { 0.0 }
+    };
+    let offset_x = refine(corr(peak_x + w - 1, peak_y), corr(peak_x, peak_y), corr(peak_x + 1, peak_y));
+    let offset_y = refine(corr(peak_x, peak_y + h - 1), corr(peak_x, peak_y), corr(peak_x, peak_y + 1));
+
+    // Peaks past the halfway point correspond to negative shifts
+    let wrap = |peak: usize, len: usize| -> f32 {
+        if peak > len / 2 { peak as f32 - len as f32 } else { peak as f32 }
+    };
+
+    Ok((wrap(peak_x, w) + offset_x, wrap(peak_y, h) + offset_y))
+}
+
 ///////////////////////
 // Scaling Algorithms
 ///////////////////////
@@ -374,17 +1309,19 @@ fn scale_bilinear(input: &Image<f32>, info: &ImageInfo, x_factor: f32, y_factor:
 }
 
 #[cfg(not(feature = "rayon"))]
-fn scale_bicubic(input: &Image<f32>, output: &mut Image<f32>, x_factor: f32, y_factor: f32) {
+fn scale_cubic<F>(input: &Image<f32>, output: &mut Image<f32>, x_factor: f32, y_factor: f32, weight_fn: F)
+    where F: Fn(f32) -> f32 {
     for y in 0..output.info().height {
         for x in 0..output.info().width {
-            let p_out = interpolate_bicubic(input, x_factor, y_factor, x, y);
+            let p_out = interpolate_cubic(input, x_factor, y_factor, &weight_fn, x, y);
             output.set_pixel(x, y, &p_out);
         }
     }
 }
 
 #[cfg(feature = "rayon")]
-fn scale_bicubic(input: &Image<f32>, info: &ImageInfo, x_factor: f32, y_factor: f32) -> Image<f32> {
+fn scale_cubic<F>(input: &Image<f32>, info: &ImageInfo, x_factor: f32, y_factor: f32, weight_fn: F) -> Image<f32>
+    where F: Fn(f32) -> f32 + Sync {
     let size = info.size();
     let (width, height, channels) = info.whc();
 
@@ -392,7 +1329,7 @@ fn scale_bicubic(input: &Image<f32>, info: &ImageInfo, x_factor: f32, y_factor:
         .into_par_iter()
         .map(|i: u32| -> Vec<f32> {
             let (x, y) = util::get_2d_coords(i, width);
-            interpolate_bicubic(input, x_factor, y_factor, x, y)
+            interpolate_cubic(input, x_factor, y_factor, &weight_fn, x, y)
         })
         .collect();
 
@@ -459,7 +1396,14 @@ fn interpolate_bilinear(input: &Image<f32>, x_factor: f32, y_factor: f32, x: u32
     p_out
 }
 
-fn interpolate_bicubic(input: &Image<f32>, x_factor: f32, y_factor: f32, x: u32, y: u32) -> Vec<f32> {
+/// Interpolates the output pixel `(x, y)` of a scaled image using the cubic kernel `weight_fn`,
+/// which is non-zero within 2 pixels
+fn interpolate_cubic<F>(input: &Image<f32>, x_factor: f32, y_factor: f32, weight_fn: &F, x: u32, y: u32) -> Vec<f32>
+    where F: Fn(f32) -> f32 {
+    if x_factor < 1.0 || y_factor < 1.0 {
+        return interpolate_downscale(input, x_factor, y_factor, 2.0, weight_fn, x, y);
+    }
+
     let x_in = (x as f32) / x_factor;
     let y_in = (y as f32) / y_factor;
     let delta_x = x_in - x_in.floor();
@@ -472,8 +1416,7 @@ fn interpolate_bicubic(input: &Image<f32>, x_factor: f32, y_factor: f32, x: u32,
         for n in -1..3 {
             let y_clamp = (y_in + (n as f32)).clamp(0.0, input.info().height as f32 - 1.0) as u32;
             let p_in = input.get_pixel_unchecked(x_clamp, y_clamp);
-            let r = util::cubic_weighting_fn((m as f32) - delta_x)
-                * util::cubic_weighting_fn(delta_y - (n as f32));
+            let r = weight_fn((m as f32) - delta_x) * weight_fn(delta_y - (n as f32));
 
             for c in 0..(input.info().channels as usize) {
                 p_out[c] += p_in[c] * r;
@@ -485,6 +1428,11 @@ fn interpolate_bicubic(input: &Image<f32>, x_factor: f32, y_factor: f32, x: u32,
 }
 
 fn interpolate_lanczos(input: &Image<f32>, x_factor: f32, y_factor: f32, size: u32, x: u32, y: u32) -> Vec<f32> {
+    if x_factor < 1.0 || y_factor < 1.0 {
+        return interpolate_downscale(input, x_factor, y_factor, size as f32,
+                                     |d| util::lanczos_kernel(d, size as f32), x, y);
+    }
+
     let x_in = (x as f32) / x_factor;
     let y_in = (y as f32) / y_factor;
     let delta_x = x_in - x_in.floor();
@@ -507,4 +1455,149 @@ fn interpolate_lanczos(input: &Image<f32>, x_factor: f32, y_factor: f32, size: u
     }
 
     p_out
-}
\ No newline at end of file
+}
+
+/// Interpolates the output pixel `(x, y)` of a scaled image using the interpolation kernel
+/// `weight_fn`, which is non-zero within `support` pixels. Along each axis that is reduced, the
+/// kernel is stretched by `1 / factor` so that it also acts as a low-pass filter, which prevents
+/// aliasing. Pixel centers are aligned, and the weights are normalized
+fn interpolate_downscale<F>(input: &Image<f32>, x_factor: f32, y_factor: f32, support: f32, weight_fn: F,
+                            x: u32, y: u32) -> Vec<f32>
+    where F: Fn(f32) -> f32 {
+    let (width, height) = input.info().wh();
+    let weights_x = resample_weights(x, x_factor, width, support, &weight_fn);
+    let weights_y = resample_weights(y, y_factor, height, support, &weight_fn);
+
+    let mut p_out = vec![0.0; input.info().channels as usize];
+    for &(y_in, weight_y) in weights_y.iter() {
+        for &(x_in, weight_x) in weights_x.iter() {
+            let weight = weight_x * weight_y;
+
+            for (out, val) in p_out.iter_mut().zip(input.get_pixel(x_in, y_in)) {
+                *out += val * weight;
+            }
+        }
+    }
+
+    p_out
+}
+
+/// Returns the input positions along an axis of length `len` that contribute to the output
+/// position `pos` when the axis is scaled by `factor`, along with their normalized weights under
+/// the kernel `weight_fn`, which is non-zero within `support` pixels. When `factor` is less than
+/// 1, the kernel is stretched by `1 / factor`. Pixel centers are aligned, and positions outside
+/// the axis are clamped to the nearest edge
+fn resample_weights<F>(pos: u32, factor: f32, len: u32, support: f32, weight_fn: &F) -> Vec<(u32, f32)>
+    where F: Fn(f32) -> f32 {
+    let scale = (1.0 / factor).max(1.0);
+    let center = (pos as f32 + 0.5) / factor - 0.5;
+    let radius = support * scale;
+
+    let mut weights: Vec<(u32, f32)> = ((center - radius).ceil() as i32..=(center + radius).floor() as i32)
+        .map(|i| (i.clamp(0, len as i32 - 1) as u32, weight_fn((i as f32 - center) / scale)))
+        .collect();
+
+    let weight_sum: f32 = weights.iter().map(|&(_, weight)| weight).sum();
+    if weight_sum != 0.0 {
+        weights.iter_mut().for_each(|(_, weight)| *weight /= weight_sum);
+    }
+
+    weights
+}
+
+/// Returns the ranges of input positions (as inclusive start and end positions) along an axis of
+/// length `len` that are covered by the output position `pos` when the axis is scaled by `factor`,
+/// along with the fraction of each position in the range that is covered. Fully covered positions
+/// are grouped into a single range
+fn area_coverage(pos: u32, factor: f32, len: u32) -> Vec<(u32, u32, f32)> {
+    let hi = ((pos + 1) as f32 / factor).min(len as f32);
+    let lo = (pos as f32 / factor).min(hi);
+    let (full_lo, full_hi) = (lo.ceil(), hi.floor());
+
+    // The output position lies within a single input position
+    if full_lo > full_hi {
+        return vec![(lo as u32, lo as u32, 1.0)];
+    }
+
+    let mut ranges = Vec::with_capacity(3);
+    if lo < full_lo {
+        ranges.push((lo as u32, lo as u32, full_lo - lo));
+    }
+    if full_lo < full_hi {
+        ranges.push((full_lo as u32, full_hi as u32 - 1, 1.0));
+    }
+    if hi > full_hi {
+        ranges.push((full_hi as u32, full_hi as u32, hi - full_hi));
+    }
+    if ranges.is_empty() {
+        let last = std::cmp::min(lo as u32, len - 1);
+        ranges.push((last, last, 1.0));
+    }
+
+    ranges
+}
+
+/// Samples `input` at the pixel nearest to the (possibly fractional) coordinates `(x, y)`.
+/// Coordinates outside the image are clamped to the nearest edge pixel
+fn sample_nearest_neighbor(input: &Image<f32>, x: f32, y: f32) -> &[f32] {
+    let x = x.round().clamp(0.0, input.info().width as f32 - 1.0) as u32;
+    let y = y.round().clamp(0.0, input.info().height as f32 - 1.0) as u32;
+
+    input.get_pixel(x, y)
+}
+
+/// Samples `input` at the (possibly fractional) coordinates `(x, y)` by applying the separable
+/// interpolation kernel `weight_fn`, which is non-zero within `radius` pixels, to the surrounding
+/// pixels. Coordinates outside the image are clamped to the nearest edge pixel
+fn sample_kernel<F>(input: &Image<f32>, x: f32, y: f32, radius: i32, weight_fn: F) -> Vec<f32>
+    where F: Fn(f32) -> f32 {
+    let (width, height) = input.info().wh();
+    let x = x.clamp(0.0, width as f32 - 1.0);
+    let y = y.clamp(0.0, height as f32 - 1.0);
+    let (x_floor, y_floor) = (x.floor(), y.floor());
+
+    let mut p_out = vec![0.0; input.info().channels as usize];
+    for j in (1 - radius)..=radius {
+        let y_in = (y_floor as i32 + j).clamp(0, height as i32 - 1) as u32;
+        let y_weight = weight_fn(y - y_floor - j as f32);
+
+        for i in (1 - radius)..=radius {
+            let x_in = (x_floor as i32 + i).clamp(0, width as i32 - 1) as u32;
+            let weight = weight_fn(x - x_floor - i as f32) * y_weight;
+
+            for (out, val) in p_out.iter_mut().zip(input.get_pixel(x_in, y_in)) {
+                *out += val * weight;
+            }
+        }
+    }
+
+    p_out
+}
+
+/// Samples `input` at the (possibly fractional) coordinates `(x, y)` using bilinear
+/// interpolation. Coordinates outside the image are clamped to the nearest edge pixel
+fn sample_bilinear(input: &Image<f32>, x: f32, y: f32) -> Vec<f32> {
+    let x = x.clamp(0.0, input.info().width as f32 - 1.0);
+    let y = y.clamp(0.0, input.info().height as f32 - 1.0);
+    let x_1 = x.floor() as u32;
+    let y_1 = y.floor() as u32;
+    let x_2 = std::cmp::min(x_1 + 1, input.info().width - 1);
+    let y_2 = std::cmp::min(y_1 + 1, input.info().height - 1);
+    let x_weight = x - (x_1 as f32);
+    let y_weight = y - (y_1 as f32);
+
+    let p1 = input.get_pixel(x_1, y_1);
+    let p2 = input.get_pixel(x_2, y_1);
+    let p3 = input.get_pixel(x_1, y_2);
+    let p4 = input.get_pixel(x_2, y_2);
+
+    let mut p_out = Vec::with_capacity(input.info().channels as usize);
+    for c in 0..(input.info().channels as usize) {
+        p_out.push(p1[c] * (1.0 - x_weight) * (1.0 - y_weight)
+            + p2[c] * x_weight * (1.0 - y_weight)
+            + p3[c] * (1.0 - x_weight) * y_weight
+            + p4[c] * x_weight * y_weight);
+    }
+
+    p_out
+}
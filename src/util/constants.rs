@@ -17,6 +17,15 @@ pub const XYZ_TO_SRGB_MAT: [f32; 9] = [3.2404542, -1.5371385, -0.4985314,
                                        -0.9692660, 1.8760108, 0.0415560,
                                        0.0556434, -0.2040259, 1.0572252];
 
+/////////////////////////
+// Colormap constants
+/////////////////////////
+
+/// Evenly spaced RGB control points of the viridis colormap, from 0 to 1
+pub const VIRIDIS: [[u8; 3]; 11] = [[68, 1, 84], [72, 36, 117], [65, 68, 135], [53, 95, 141],
+                                    [42, 120, 142], [33, 145, 140], [34, 168, 132], [68, 191, 112],
+                                    [122, 209, 81], [189, 223, 38], [253, 231, 37]];
+
 /////////////////////////////
 // Image filtering kernels
 /////////////////////////////
@@ -0,0 +1,30 @@
+use rustfft::FftPlanner;
+use rustfft::num_complex::Complex;
+
+/// Computes the 2D FFT of `data`, a `width x height` matrix stored row by row, in place. The
+/// inverse transform is not normalized
+pub(crate) fn fft_2d(data: &mut [Complex<f32>], width: usize, height: usize, inverse: bool) {
+    let mut planner = FftPlanner::new();
+    let (row_fft, col_fft) = if inverse {
+        (planner.plan_fft_inverse(width), planner.plan_fft_inverse(height))
+    } else {
+        (planner.plan_fft_forward(width), planner.plan_fft_forward(height))
+    };
+
+    for row in data.chunks_exact_mut(width) {
+        row_fft.process(row);
+    }
+
+    let mut col = vec![Complex::new(0.0, 0.0); height];
+    for x in 0..width {
+        for (y, val) in col.iter_mut().enumerate() {
+            *val = data[y * width + x];
+        }
+
+        col_fft.process(&mut col);
+
+        for (y, val) in col.iter().enumerate() {
+            data[y * width + x] = *val;
+        }
+    }
+}
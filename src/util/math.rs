@@ -241,6 +241,31 @@ pub fn cubic_weighting_fn(x: f32) -> f32 {
         - 4.0 * clamp_zero(x - 1.0).powf(3.0))
 }
 
+/// Mitchell-Netravali cubic filter with parameters `b` and `c`, which is non-zero within 2 pixels
+pub fn mitchell_netravali_fn(x: f32, b: f32, c: f32) -> f32 {
+    let x = x.abs();
+
+    if x < 1.0 {
+        ((12.0 - 9.0 * b - 6.0 * c) * x.powi(3) + (-18.0 + 12.0 * b + 6.0 * c) * x.powi(2)
+            + (6.0 - 2.0 * b)) / 6.0
+    } else if x < 2.0 {
+        ((-b - 6.0 * c) * x.powi(3) + (6.0 * b + 30.0 * c) * x.powi(2) + (-12.0 * b - 48.0 * c) * x
+            + (8.0 * b + 24.0 * c)) / 6.0
+    } else {
+        0.0
+    }
+}
+
+/// Mitchell-Netravali filter with the recommended parameters `B = C = 1/3`
+pub fn mitchell_fn(x: f32) -> f32 {
+    mitchell_netravali_fn(x, 1.0 / 3.0, 1.0 / 3.0)
+}
+
+/// Catmull-Rom spline, the Mitchell-Netravali filter with parameters `B = 0` and `C = 1/2`
+pub fn catmull_rom_fn(x: f32) -> f32 {
+    mitchell_netravali_fn(x, 0.0, 0.5)
+}
+
 /// A helper function for the colorspace conversion from CIE XYZ to CIELAB
 pub fn xyz_to_lab_fn(num: f32) -> f32 {
     let d: f32 = 6.0 / 29.0;
@@ -290,4 +315,33 @@ pub fn lanczos_kernel(x: f32, a: f32) -> f32 {
     }
 
     0.0
-}
\ No newline at end of file
+}
+
+/// Computes the mean of `values`, truncated for integer types. Integer values are summed as
+/// `i128` and floating point values as `f64`, so that the sum cannot overflow `T`
+pub(crate) fn mean<T: Number, I: Iterator<Item = T>>(values: I) -> T {
+    weighted_mean(values.map(|val| (val, 1)))
+}
+
+/// Computes the mean of the values in `values`, each counted `weight` times, truncated for integer
+/// types. Integer values are summed as `i128` and floating point values as `f64`, so that the sum
+/// cannot overflow `T`
+pub(crate) fn weighted_mean<T: Number, I: Iterator<Item = (T, u32)>>(values: I) -> T {
+    let zero: T = 0.into();
+    let one: T = 1.into();
+
+    // Only floating point types have a non-zero quotient for 1 / 2
+    let val = if one / 2.into() > zero {
+        let (sum, n) = values.fold((0.0, 0.0), |(sum, n), (val, weight)| {
+            (sum + val.to_f64().unwrap() * weight as f64, n + weight as f64)
+        });
+        num_traits::cast(sum / n)
+    } else {
+        let (sum, n) = values.fold((0i128, 0i128), |(sum, n), (val, weight)| {
+            (sum + val.to_i128().unwrap() * weight as i128, n + weight as i128)
+        });
+        num_traits::cast(sum / n)
+    };
+
+    val.unwrap()
+}
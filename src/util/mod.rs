@@ -1,16 +1,23 @@
 //! A module for image utility functions
 
 pub use self::math::*;
+#[cfg(feature = "fft")]
+pub(crate) use self::fft::*;
 
 mod math;
+#[cfg(feature = "fft")]
+mod fft;
 
 use std::collections::{BTreeMap, HashMap};
 use std::f32::consts::{E, PI};
 
-use crate::enums::White;
-use crate::error;
-use crate::error::ImgProcResult;
-use crate::image::{BaseImage, Image, Number};
+use crate::{colorspace, error, filter};
+use crate::enums::{ColorMetric, White};
+use crate::error::{ImgProcError, ImgProcResult};
+use crate::image::{BaseImage, Image, ImageInfo, Number};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 pub mod constants;
 
@@ -225,10 +232,512 @@ pub fn rectangular_intensity_sum(summed_area_table: &Image<f32>, x_0: u32, y_0:
     sum
 }
 
+/// Returns a grayscale image visualizing the difference between `a` and `b`, where each output
+/// pixel is the largest absolute difference among the non-alpha channels of the corresponding
+/// input pixels, multiplied by `amplify` and clamped to 255
+///
+/// # Arguments
+///
+/// * `amplify` - Must be non-negative
+pub fn visualize_diff(a: &Image<u8>, b: &Image<u8>, amplify: f32) -> ImgProcResult<Image<u8>> {
+    error::check_info(a.info(), b.info())?;
+    error::check_non_neg(amplify, "amplify")?;
+
+    let (width, height) = a.info().wh();
+    let channels = a.info().channels_non_alpha() as usize;
+    let mut output = Image::blank(ImageInfo::new(width, height, 1, false));
+
+    for i in 0..(a.info().size() as usize) {
+        let mut diff = 0;
+        for c in 0..channels {
+            diff = std::cmp::max(diff, (a[i][c] as i16 - b[i][c] as i16).abs());
+        }
+
+        output.set_pixel_indexed(i, &[(diff as f32 * amplify).round().clamp(0.0, 255.0) as u8]);
+    }
+
+    Ok(output)
+}
+
+/// Returns a single-channel map of the CIE76 color difference between each pair of corresponding
+/// pixels of `a` and `b` in CIELAB (D65). Alpha channels are ignored. A difference of about 2.3
+/// is the smallest that is noticeable to a human
+///
+/// # Arguments
+///
+/// * `a` - Must be an RGB(A) image with the same dimensions as `b`
+pub fn delta_e_image(a: &Image<u8>, b: &Image<u8>) -> ImgProcResult<Image<f32>> {
+    error::check_info(a.info(), b.info())?;
+    error::check_channel_count(3, a.info().channels_non_alpha() as usize)?;
+
+    let lab_a = colorspace::srgb_to_lab_f32(a, &White::D65);
+    let lab_b = colorspace::srgb_to_lab_f32(b, &White::D65);
+    let data = (0..(a.info().size() as usize))
+        .map(|i| delta_e_76(&lab_a[i][..3], &lab_b[i][..3]))
+        .collect();
+
+    Ok(Image::from_vec(a.info().width, a.info().height, 1, false, data))
+}
+
+/// Packs three single-channel images into the red, green, and blue channels of an RGB image, which
+/// is useful for viewing several feature maps at once
+///
+/// # Arguments
+///
+/// * `r` - Must be a single-channel image with the same dimensions as `g` and `b`
+pub fn pack_rgb(r: &Image<u8>, g: &Image<u8>, b: &Image<u8>) -> ImgProcResult<Image<u8>> {
+    error::check_channel_count(1, r.info().channels as usize)?;
+    error::check_info(r.info(), g.info())?;
+    error::check_info(r.info(), b.info())?;
+
+    let data = r.data().iter().zip(g.data()).zip(b.data())
+        .flat_map(|((&r, &g), &b)| vec![r, g, b])
+        .collect();
+
+    Ok(Image::from_vec(r.info().width, r.info().height, 3, false, data))
+}
+
+/// Returns the index of the entry of `palette` closest to `pixel` under `metric`. If several
+/// entries are equally close, the first one is returned
+///
+/// # Panics
+///
+/// Panics if `palette` is empty, if any entry of `palette` has a different length than `pixel`,
+/// or if `metric` is `ColorMetric::DeltaE` and `pixel` is not an RGB pixel
+pub fn nearest_color(pixel: &[u8], palette: &[Vec<u8>], metric: ColorMetric) -> usize {
+    assert!(!palette.is_empty(), "palette must not be empty");
+    assert!(palette.iter().all(|color| color.len() == pixel.len()),
+            "palette colors must have one value for each channel of pixel");
+
+    let distances: Vec<f32> = match metric {
+        ColorMetric::EuclideanRGB => {
+            palette.iter()
+                .map(|color| {
+                    pixel.iter().zip(color.iter())
+                        .map(|(&a, &b)| (a as f32 - b as f32).powi(2))
+                        .sum()
+                })
+                .collect()
+        },
+        ColorMetric::DeltaE => {
+            assert_eq!(3, pixel.len(), "pixel must be an RGB pixel");
+
+            let pixel = srgb_to_lab_pixel(pixel);
+            palette.iter().map(|color| delta_e_76(&pixel, &srgb_to_lab_pixel(color))).collect()
+        },
+    };
+
+    distances.iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(i, _)| i)
+        .unwrap()
+}
+
+/// Converts a single sRGB pixel to CIELAB (D65)
+fn srgb_to_lab_pixel(pixel: &[u8]) -> Vec<f32> {
+    let img = Image::from_vec(1, 1, 3, false, pixel.to_vec());
+    colorspace::srgb_to_lab_f32(&img, &White::D65).data().to_vec()
+}
+
+/// Returns the CIE76 color difference between two CIELAB pixels
+fn delta_e_76(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt()
+}
+
+/// Returns the variance of the Laplacian of an image, a measure of its sharpness (higher values
+/// indicate a sharper image). Color images are converted to grayscale first
+pub fn sharpness_laplacian(input: &Image<f32>) -> ImgProcResult<f64> {
+    let laplacian = filter::laplacian(&sharpness_gray(input))?;
+    let n = laplacian.info().size() as f64;
+
+    let (mut sum, mut sum_sq) = (0.0, 0.0);
+    for i in 0..(laplacian.info().size() as usize) {
+        let val = laplacian[i][0] as f64;
+        sum += val;
+        sum_sq += val * val;
+    }
+
+    let mean = sum / n;
+    Ok(sum_sq / n - mean * mean)
+}
+
+/// Returns the Tenengrad measure of an image, the mean squared magnitude of its Sobel gradient, a
+/// measure of its sharpness (higher values indicate a sharper image). Color images are converted
+/// to grayscale first
+pub fn sharpness_tenengrad(input: &Image<f32>) -> ImgProcResult<f64> {
+    let sobel = filter::sobel(&sharpness_gray(input))?;
+
+    let mut sum = 0.0;
+    for i in 0..(sobel.info().size() as usize) {
+        let val = sobel[i][0] as f64;
+        sum += val * val;
+    }
+
+    Ok(sum / sobel.info().size() as f64)
+}
+
+/// Returns a grayscale copy of `input` for the sharpness measures
+fn sharpness_gray(input: &Image<f32>) -> Image<f32> {
+    if input.info().channels_non_alpha() == 1 {
+        input.clone()
+    } else {
+        colorspace::rgb_to_grayscale_f32(input)
+    }
+}
+
+/// Renders the histograms of the non-alpha channels of an image as an `out_width x out_height`
+/// RGB image of overlaid bars, scaled so that the most frequent value fills the height. For RGB
+/// images, the red, green, and blue channels are drawn in the corresponding colors; for other
+/// images, the channels are drawn in white
+pub fn render_histogram(input: &Image<u8>, out_width: u32, out_height: u32) -> Image<u8> {
+    let channels = input.info().channels_non_alpha() as usize;
+    let histograms = input.fold_pixels(vec![[0u32; 256]; channels], |mut histograms, p| {
+        for (c, histogram) in histograms.iter_mut().enumerate() {
+            histogram[p[c] as usize] += 1;
+        }
+        histograms
+    });
+
+    let max_count = histograms.iter().flat_map(|h| h.iter()).copied().max().unwrap_or(0).max(1);
+    let mut output = Image::blank(ImageInfo::new(out_width, out_height, 3, false));
+
+    for x in 0..out_width {
+        // Range of histogram bins covered by this column
+        let bin_start = (x as usize * 256) / out_width as usize;
+        let bin_end = (((x + 1) as usize * 256) / out_width as usize).max(bin_start + 1);
+
+        for (c, histogram) in histograms.iter().enumerate() {
+            let count = histogram[bin_start..bin_end].iter().copied().max().unwrap_or(0);
+            let bar_height = ((count as f32 / max_count as f32) * out_height as f32).round() as u32;
+
+            for y in (out_height - bar_height)..out_height {
+                let p_out = output.get_pixel_mut(x, y);
+                if channels == 3 {
+                    p_out[c] = 255;
+                } else {
+                    p_out.iter_mut().for_each(|val| *val = 255);
+                }
+            }
+        }
+    }
+
+    output
+}
+
+/// Returns the `(x, y, width, height)` of the smallest rectangle containing every pixel that
+/// differs from `background` by more than `tolerance` in any channel, or `None` if there are no
+/// such pixels
+///
+/// # Panics
+///
+/// Panics if the length of `background` is not equal to the number of channels in the image
+pub fn content_bounds(input: &Image<u8>, background: &[u8], tolerance: u8) -> Option<(u32, u32, u32, u32)> {
+    error::check_channels(input.info().channels, background.len());
+
+    let (width, height) = input.info().wh();
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+
+    for y in 0..height {
+        for x in 0..width {
+            let is_content = input.get_pixel(x, y).iter().zip(background.iter())
+                .any(|(&a, &b)| (a as i16 - b as i16).unsigned_abs() > tolerance as u16);
+
+            if is_content {
+                bounds = Some(match bounds {
+                    Some((x_0, y_0, x_1, y_1)) => (x_0.min(x), y_0.min(y), x_1.max(x), y_1.max(y)),
+                    None => (x, y, x, y),
+                });
+            }
+        }
+    }
+
+    bounds.map(|(x_0, y_0, x_1, y_1)| (x_0, y_0, x_1 - x_0 + 1, y_1 - y_0 + 1))
+}
+
+/// Returns the horizontal projection profile of an image: the mean intensity of each row, where
+/// the intensity of a pixel is the mean of its non-alpha channels
+pub fn row_profile(input: &Image<u8>) -> Vec<f64> {
+    let (width, height) = input.info().wh();
+    let mut profile = vec![0.0; height as usize];
+
+    for (i, val) in pixel_intensities(input).enumerate() {
+        profile[i / width as usize] += val;
+    }
+
+    profile.iter_mut().for_each(|sum| *sum /= width as f64);
+    profile
+}
+
+/// Returns the vertical projection profile of an image: the mean intensity of each column, where
+/// the intensity of a pixel is the mean of its non-alpha channels
+pub fn col_profile(input: &Image<u8>) -> Vec<f64> {
+    let (width, height) = input.info().wh();
+    let mut profile = vec![0.0; width as usize];
+
+    for (i, val) in pixel_intensities(input).enumerate() {
+        profile[i % width as usize] += val;
+    }
+
+    profile.iter_mut().for_each(|sum| *sum /= height as f64);
+    profile
+}
+
+/// Returns the trimmed mean of each channel of `input`: the mean of the values remaining after
+/// discarding the lowest and highest `trim_fraction` of the values. This is more robust than the
+/// plain mean against outliers such as specular highlights and deep shadows
+///
+/// # Panics
+///
+/// Panics if `trim_fraction` is not in the range [0, 0.5)
+pub fn trimmed_mean(input: &Image<f32>, trim_fraction: f32) -> Vec<f32> {
+    if !(0.0..0.5).contains(&trim_fraction) {
+        panic!("invalid trim_fraction: must be in the range [0, 0.5), but is {}", trim_fraction);
+    }
+
+    let channels = input.info().channels as usize;
+    let size = input.info().size_usize();
+    let trim = (size as f32 * trim_fraction).floor() as usize;
+
+    (0..channels).map(|c| {
+        let mut vals: Vec<f32> = input.data().iter().skip(c).step_by(channels).copied().collect();
+        vals.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let kept = &vals[trim..(size - trim)];
+        kept.iter().sum::<f32>() / kept.len() as f32
+    }).collect()
+}
+
+/// Returns an iterator over the mean of the non-alpha channels of each pixel, in row-major order
+fn pixel_intensities(input: &Image<u8>) -> impl Iterator<Item = f64> + '_ {
+    let channels = input.info().channels as usize;
+    let non_alpha = input.info().channels_non_alpha() as usize;
+
+    input.data().chunks_exact(channels).map(move |p| {
+        p[..non_alpha].iter().map(|&c| c as u32).sum::<u32>() as f64 / non_alpha as f64
+    })
+}
+
+/// Splits an image into tiles of at most `tile_w x tile_h` pixels, where neighboring tiles share
+/// `overlap` rows or columns. Tiles along the right and bottom edges are cropped to the image.
+/// Returns the tiles in row-major order, along with the `(x, y)` coordinates of their upper left
+/// corners
+///
+/// # Panics
+///
+/// Panics if `overlap` is not less than both `tile_w` and `tile_h`
+pub fn tile_image<T: Number>(input: &Image<T>, tile_w: u32, tile_h: u32, overlap: u32) -> Vec<(u32, u32, Image<T>)> {
+    if overlap >= tile_w || overlap >= tile_h {
+        panic!("invalid overlap: tiles are {}x{}, but overlap is {}", tile_w, tile_h, overlap);
+    }
+
+    let (width, height) = input.info().wh();
+    let origins = |len: u32, tile_len: u32| -> Vec<u32> {
+        let mut starts = vec![0];
+        while starts[starts.len() - 1] + tile_len < len {
+            starts.push(starts[starts.len() - 1] + tile_len - overlap);
+        }
+        starts
+    };
+
+    let mut tiles = Vec::new();
+    for &y in origins(height, tile_h).iter() {
+        for &x in origins(width, tile_w).iter() {
+            let w = tile_w.min(width - x);
+            let h = tile_h.min(height - y);
+            let mut data = Vec::with_capacity((w * h * input.info().channels as u32) as usize);
+
+            for j in y..(y + h) {
+                let start = input.index(x, j);
+                data.extend_from_slice(&input.data()[start..(start + (w * input.info().channels as u32) as usize)]);
+            }
+
+            tiles.push((x, y, Image::from_vec(w, h, input.info().channels, input.info().alpha, data)));
+        }
+    }
+
+    tiles
+}
+
+/// Reassembles tiles produced by [`tile_image()`](fn.tile_image.html) into a `width x height`
+/// image. Where tiles overlap, the output is a weighted mean of the tiles, with weights ramping
+/// linearly across `overlap` pixels from the edges of each tile, so that seams are blended
+/// smoothly. Pixels not covered by any tile are left as 0
+///
+/// # Panics
+///
+/// Panics if `tiles` is empty, or if the tiles do not all have the same number of channels
+pub fn untile<T: Number>(tiles: &[(u32, u32, Image<T>)], width: u32, height: u32, overlap: u32) -> Image<T> {
+    let (channels, alpha) = (tiles[0].2.info().channels, tiles[0].2.info().alpha);
+    if tiles.iter().any(|(_, _, tile)| tile.info().channels != channels) {
+        panic!("invalid tiles: tiles do not all have {} channels", channels);
+    }
+
+    let mut output = Image::blank(ImageInfo::new(width, height, channels, alpha));
+
+    // Distance from the edges of a tile, where edges on the border of the image are ignored
+    let edge_dist = |pos: u32, start: u32, len: u32, max: u32| -> u32 {
+        let before = if start == 0 { overlap } else { pos - start };
+        let after = if start + len >= max { overlap } else { start + len - 1 - pos };
+        before.min(after).min(overlap)
+    };
+
+    for y in 0..height {
+        let row_tiles: Vec<&(u32, u32, Image<T>)> = tiles.iter()
+            .filter(|(_, t_y, tile)| y >= *t_y && y < t_y + tile.info().height)
+            .collect();
+
+        for x in 0..width {
+            let covering: Vec<(u32, &[T])> = row_tiles.iter()
+                .filter(|(t_x, _, tile)| x >= *t_x && x < t_x + tile.info().width)
+                .map(|(t_x, t_y, tile)| {
+                    let weight = edge_dist(x, *t_x, tile.info().width, width)
+                        .min(edge_dist(y, *t_y, tile.info().height, height)) + 1;
+                    (weight, tile.get_pixel(x - t_x, y - t_y))
+                })
+                .collect();
+
+            if covering.is_empty() {
+                continue;
+            }
+
+            for (c, channel) in output.get_pixel_mut(x, y).iter_mut().enumerate() {
+                *channel = weighted_mean(covering.iter().map(|&(weight, p)| (p[c], weight)));
+            }
+        }
+    }
+
+    output
+}
+
+/// Combines a stack of aligned images into one by taking the per-pixel mean across the stack
+///
+/// # Arguments
+///
+/// * `imgs` - Must be non-empty, and all images must have the same `ImageInfo`
+pub fn mean_stack(imgs: &[&Image<f32>]) -> ImgProcResult<Image<f32>> {
+    reduce_stack(imgs, |vals| vals.iter().sum::<f32>() / vals.len() as f32)
+}
+
+/// Combines a stack of aligned images into one by taking the per-pixel median across the stack.
+/// For an even number of images, the median is the mean of the two middle values
+///
+/// # Arguments
+///
+/// * `imgs` - Must be non-empty, and all images must have the same `ImageInfo`
+pub fn median_stack(imgs: &[&Image<f32>]) -> ImgProcResult<Image<f32>> {
+    reduce_stack(imgs, |vals| {
+        let mut sorted = vals.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let mid = sorted.len() / 2;
+        if sorted.len() % 2 == 0 {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        }
+    })
+}
+
+/// Combines a stack of aligned images into one by taking the per-pixel maximum across the stack
+///
+/// # Arguments
+///
+/// * `imgs` - Must be non-empty, and all images must have the same `ImageInfo`
+pub fn max_stack(imgs: &[&Image<f32>]) -> ImgProcResult<Image<f32>> {
+    reduce_stack(imgs, |vals| vals.iter().copied().fold(f32::MIN, f32::max))
+}
+
+/// Combines a stack of aligned images into one by taking the per-pixel minimum across the stack
+///
+/// # Arguments
+///
+/// * `imgs` - Must be non-empty, and all images must have the same `ImageInfo`
+pub fn min_stack(imgs: &[&Image<f32>]) -> ImgProcResult<Image<f32>> {
+    reduce_stack(imgs, |vals| vals.iter().copied().fold(f32::MAX, f32::min))
+}
+
+/// Combines a stack of aligned images into one by calling `f` with the values of each pixel
+/// channel across the stack, in the order of `imgs`. This can be used to implement custom
+/// stacking rules, such as a trimmed mean or a percentile
+///
+/// # Arguments
+///
+/// * `imgs` - Must be non-empty, and all images must have the same `ImageInfo`
+pub fn reduce_stack<T: Number, F>(imgs: &[&Image<T>], f: F) -> ImgProcResult<Image<T>>
+    where F: Fn(&[T]) -> T {
+    if imgs.is_empty() {
+        return Err(ImgProcError::InvalidArgError("imgs must not be empty".to_string()));
+    }
+
+    let info = imgs[0].info();
+    for img in imgs.iter() {
+        error::check_info(info, img.info())?;
+    }
+
+    let mut output = Image::blank(info);
+    let mut vals = Vec::with_capacity(imgs.len());
+    for (i, channel) in output.data_mut().iter_mut().enumerate() {
+        vals.clear();
+        vals.extend(imgs.iter().map(|img| img.data()[i]));
+        *channel = f(&vals);
+    }
+
+    Ok(output)
+}
+
 /// Converts 1D vector index to 2D matrix coordinates
 pub fn get_2d_coords(i: u32, width: u32) -> (u32, u32) {
     let x = i % width;
     let y = (i - x) / width;
 
     (x, y)
-}
\ No newline at end of file
+}
+
+/// Builds an image described by `info` by splitting it into rectangular tiles of at most
+/// `tile_size x tile_size` pixels and processing the tiles in parallel
+///
+/// `f` is called with the `(x, y, width, height)` of each tile, where `(x, y)` is the upper left
+/// corner of the tile, and must return the pixel data of that tile row by row
+/// (`width * height * channels` values)
+///
+/// # Panics
+///
+/// Panics if `f` returns data of the wrong length for a tile
+#[cfg(feature = "rayon")]
+pub fn par_tiles<T: Number, F>(info: ImageInfo, tile_size: u32, f: F) -> Image<T>
+    where F: Fn(u32, u32, u32, u32) -> Vec<T> + Sync {
+    let (width, height, channels) = info.whc();
+    let tile_size = std::cmp::max(tile_size, 1);
+    let tiles_x = width.div_ceil(tile_size);
+    let tiles_y = height.div_ceil(tile_size);
+
+    let tiles: Vec<(u32, u32, u32, Vec<T>)> = (0..(tiles_x * tiles_y))
+        .into_par_iter()
+        .map(|i| {
+            let (t_x, t_y) = get_2d_coords(i, tiles_x);
+            let x = t_x * tile_size;
+            let y = t_y * tile_size;
+            let tile_w = std::cmp::min(tile_size, width - x);
+            let tile_h = std::cmp::min(tile_size, height - y);
+
+            let data = f(x, y, tile_w, tile_h);
+            error::check_equal(data.len(), (tile_w * tile_h * channels as u32) as usize,
+                               "tile data length").unwrap();
+
+            (x, y, tile_w, data)
+        })
+        .collect();
+
+    let mut output = Image::blank(info);
+    let row_len = |w: u32| (w * channels as u32) as usize;
+
+    for (x, y, tile_w, data) in tiles.iter() {
+        for (j, row) in data.chunks(row_len(*tile_w)).enumerate() {
+            let start = output.index(*x, *y + j as u32);
+            output.data_mut()[start..(start + row.len())].copy_from_slice(row);
+        }
+    }
+
+    output
+}
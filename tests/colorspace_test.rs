@@ -4,11 +4,11 @@ mod common;
 
 use common::setup;
 use imgproc_rs::{colorspace, convert};
-use imgproc_rs::image::Image;
+use imgproc_rs::image::{BaseImage, Image};
 use imgproc_rs::io::write;
 
 use std::time::SystemTime;
-use imgproc_rs::enums::White;
+use imgproc_rs::enums::{GrayMethod, White};
 
 const PATH: &str = "images/spectrum.jpg";
 
@@ -106,4 +106,58 @@ fn hsv_to_rgb_test() {
     println!("processing: {}", now.elapsed().unwrap().as_millis());
 
     write(&proc, "images/tests/colorspace/hsv_rgb.png").unwrap();
-}
\ No newline at end of file
+}
+
+#[test]
+fn in_range_test() {
+    let img: Image<u8> = Image::from_slice(3, 1, 3, false,
+                                           &[10, 200, 30, 10, 100, 30, 50, 200, 30]);
+
+    let mask = colorspace::in_range(&img, &[0, 150, 0], &[20, 255, 40]).unwrap();
+    assert_eq!(1, mask.info().channels);
+    assert_eq!(&[255, 0, 0], mask.data());
+
+    assert!(colorspace::in_range(&img, &[0, 150], &[20, 255]).is_err());
+    assert!(colorspace::in_range(&img, &[30, 0, 0], &[20, 255, 40]).is_err());
+}
+
+#[test]
+fn to_grayscale_test() {
+    let img: Image<u8> = Image::from_slice(2, 1, 4, true, &[200, 100, 0, 7, 10, 20, 30, 9]);
+
+    let gray = |method| colorspace::to_grayscale(&img, method);
+    assert_eq!(&[100, 7, 20, 9], gray(GrayMethod::Average).data());
+    assert_eq!(&[114, 7, 19, 9], gray(GrayMethod::Luminance709).data());
+    assert_eq!(&[119, 7, 18, 9], gray(GrayMethod::Luminance601).data());
+    assert_eq!(&[100, 7, 20, 9], gray(GrayMethod::Lightness).data());
+    assert_eq!(&[100, 7, 20, 9], gray(GrayMethod::Desaturate).data());
+    assert_eq!(&[100, 7, 20, 9], gray(GrayMethod::SingleChannel(1)).data());
+    assert_eq!(2, gray(GrayMethod::Average).info().channels);
+}
+
+#[test]
+#[should_panic]
+fn to_grayscale_invalid_channel_test() {
+    let img: Image<u8> = Image::from_slice(1, 1, 3, false, &[1, 2, 3]);
+    colorspace::to_grayscale(&img, GrayMethod::SingleChannel(3));
+}
+
+#[test]
+fn srgb_linear_u8_test() {
+    let img: Image<u8> = Image::from_slice(2, 2, 2, true, &[0, 10, 12, 20, 188, 30, 255, 40]);
+
+    let linear = colorspace::srgb_to_linear_u8(&img);
+    assert_eq!(&[0, 10, 1, 20, 136, 30, 255, 40], linear.data());
+
+    // Matches the f32 conversion, rounded to 8 bits
+    let linear_f32 = colorspace::linearize_srgb_f32(&img);
+    for (i, (&a, &b)) in linear.data().iter().zip(linear_f32.data()).enumerate() {
+        if i % 2 == 0 {
+            assert_eq!((b * 255.0).round() as u8, a);
+        }
+    }
+
+    // Round trips lose precision in the shadows only
+    let output = colorspace::linear_to_srgb_u8(&linear);
+    assert_eq!(&[0, 10, 8, 20, 188, 30, 255, 40], output.data());
+}
@@ -0,0 +1,17 @@
+use imgproc_rs::convert;
+use imgproc_rs::convert::ClipStats;
+use imgproc_rs::image::{BaseImage, Image};
+
+#[test]
+fn to_u8_checked_test() {
+    let img: Image<f32> = Image::from_vec(3, 1, 2, true, vec![-1.0, 0.0, 12.4, 255.0, 255.5, f32::NAN]);
+
+    let (output, stats) = convert::to_u8_checked(&img);
+    assert_eq!(img.info(), output.info());
+    assert_eq!(&[0, 0, 12, 255, 255, 0], output.data());
+    assert_eq!(ClipStats { below: 2, above: 1 }, stats);
+    assert_eq!(3, stats.total());
+
+    let img: Image<f32> = Image::from_vec(1, 1, 1, false, vec![128.0]);
+    assert_eq!(0, convert::to_u8_checked(&img).1.total());
+}
@@ -0,0 +1,122 @@
+use imgproc_rs::draw;
+use imgproc_rs::image::{BaseImage, Image, ImageInfo};
+
+fn blank(width: u32, height: u32) -> Image<u8> {
+    Image::blank(ImageInfo::new(width, height, 1, false))
+}
+
+#[test]
+fn line_test() {
+    let mut img = blank(5, 3);
+    draw::line(&mut img, 0, 0, 4, 2, &[255]).unwrap();
+    assert_eq!(&[255, 0, 0, 0, 0,
+                 0, 255, 255, 0, 0,
+                 0, 0, 0, 255, 255], img.data());
+
+    // Lines are clipped at the image boundaries
+    let mut img = blank(3, 3);
+    draw::line(&mut img, -2, 1, 10, 1, &[255]).unwrap();
+    assert_eq!(&[0, 0, 0, 255, 255, 255, 0, 0, 0], img.data());
+
+    assert!(draw::line(&mut img, 0, 0, 1, 1, &[255, 255]).is_err());
+}
+
+#[test]
+fn line_aa_test() {
+    let mut img = blank(5, 5);
+    draw::line_aa(&mut img, 0.0, 2.0, 4.0, 2.0, &[200], 1.0).unwrap();
+    assert_eq!(&[200; 5], &img.data()[10..15]);
+    assert!(img.data()[..10].iter().chain(img.data()[15..].iter()).all(|&v| v == 0));
+
+    // A line between two rows covers each of them halfway
+    let mut img = blank(5, 5);
+    draw::line_aa(&mut img, 0.0, 1.5, 4.0, 1.5, &[200], 1.0).unwrap();
+    assert_eq!(&[100], img.get_pixel(2, 1));
+    assert_eq!(&[100], img.get_pixel(2, 2));
+    assert_eq!(&[0], img.get_pixel(2, 3));
+
+    // Thicker lines cover more rows, and lines are clipped at the image boundaries
+    let mut img = blank(5, 5);
+    draw::line_aa(&mut img, -3.0, 2.0, 10.0, 2.0, &[200], 3.0).unwrap();
+    assert!(img.data()[5..20].iter().all(|&v| v == 200));
+    assert_eq!(&[0], img.get_pixel(0, 0));
+
+    assert!(draw::line_aa(&mut img, 0.0, 0.0, 1.0, 1.0, &[255], 0.0).is_err());
+    assert!(draw::line_aa(&mut img, 0.0, 0.0, 1.0, 1.0, &[255, 255], 1.0).is_err());
+}
+
+#[test]
+fn rect_test() {
+    let mut img = blank(4, 4);
+    draw::rect(&mut img, 0, 0, 4, 3, &[1]).unwrap();
+    assert_eq!(&[1, 1, 1, 1,
+                 1, 0, 0, 1,
+                 1, 1, 1, 1,
+                 0, 0, 0, 0], img.data());
+
+    let mut img = blank(4, 4);
+    draw::rect_filled(&mut img, 2, -1, 5, 3, &[1]).unwrap();
+    assert_eq!(&[0, 0, 1, 1,
+                 0, 0, 1, 1,
+                 0, 0, 0, 0,
+                 0, 0, 0, 0], img.data());
+
+    assert!(draw::rect(&mut img, 0, 0, 1, 1, &[]).is_err());
+    assert!(draw::rect_filled(&mut img, 0, 0, 1, 1, &[]).is_err());
+}
+
+#[test]
+fn circle_test() {
+    let mut img = blank(7, 7);
+    draw::circle(&mut img, 3, 3, 2, &[1]).unwrap();
+    assert_eq!(&[0, 0, 0, 0, 0, 0, 0,
+                 0, 0, 1, 1, 1, 0, 0,
+                 0, 1, 0, 0, 0, 1, 0,
+                 0, 1, 0, 0, 0, 1, 0,
+                 0, 1, 0, 0, 0, 1, 0,
+                 0, 0, 1, 1, 1, 0, 0,
+                 0, 0, 0, 0, 0, 0, 0], img.data());
+
+    // Circles partly outside of the image are clipped
+    let mut img = blank(3, 3);
+    draw::circle(&mut img, 0, 0, 2, &[1]).unwrap();
+    assert_eq!(1, img.get_pixel(2, 0)[0]);
+    assert_eq!(1, img.get_pixel(0, 2)[0]);
+}
+
+#[test]
+fn polygon_test() {
+    let square = [(1, 1), (4, 1), (4, 4), (1, 4)];
+
+    let mut img = blank(5, 5);
+    draw::polygon(&mut img, &square, &[1], true).unwrap();
+    assert_eq!(&[0, 0, 0, 0, 0,
+                 0, 1, 1, 1, 0,
+                 0, 1, 1, 1, 0,
+                 0, 1, 1, 1, 0,
+                 0, 0, 0, 0, 0], img.data());
+
+    let mut img = blank(5, 5);
+    draw::polygon(&mut img, &[(0, 0), (3, 0), (3, 3), (0, 3)], &[1], false).unwrap();
+    assert_eq!(&[1, 1, 1, 1, 0,
+                 1, 0, 0, 1, 0,
+                 1, 0, 0, 1, 0,
+                 1, 1, 1, 1, 0,
+                 0, 0, 0, 0, 0], img.data());
+
+    // Filled polygons are clipped at the image boundaries
+    let mut img = blank(3, 3);
+    draw::polygon(&mut img, &[(-5, -5), (10, -5), (10, 10), (-5, 10)], &[1], true).unwrap();
+    assert_eq!(&[1; 9], img.data());
+}
+
+#[test]
+fn rect_alpha_test() {
+    let mut img: Image<u8> = Image::from_vec(3, 2, 2, false, vec![100; 12]);
+    draw::rect_alpha(&mut img, 1, -1, 5, 2, &[200, 0], 0.25).unwrap();
+    assert_eq!(&[100, 100, 125, 75, 125, 75,
+                 100, 100, 100, 100, 100, 100], img.data());
+
+    assert!(draw::rect_alpha(&mut img, 0, 0, 1, 1, &[0, 0], 1.5).is_err());
+    assert!(draw::rect_alpha(&mut img, 0, 0, 1, 1, &[0], 0.5).is_err());
+}
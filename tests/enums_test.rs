@@ -0,0 +1,67 @@
+use imgproc_rs::enums::{Bilateral, Border, ColorMetric, Colormap, ConvMode, FreqMode, GrayMethod, Refl, Scale, Subsampling, Thresh, White};
+
+#[test]
+fn enums_from_str_test() {
+    assert_eq!(Scale::Bilinear, "bilinear".parse().unwrap());
+    assert_eq!(Scale::NearestNeighbor, "Nearest-Neighbor".parse().unwrap());
+    assert_eq!(Refl::Horizontal, "HORIZONTAL".parse().unwrap());
+    assert_eq!(Thresh::BinaryInv, "binary_inv".parse().unwrap());
+    assert_eq!(White::D65, "d65".parse().unwrap());
+    assert_eq!(Bilateral::Direct, "Direct".parse().unwrap());
+    assert_eq!(Colormap::Viridis, "viridis".parse().unwrap());
+    assert_eq!(ConvMode::Valid, "valid".parse().unwrap());
+
+    assert!("trilinear".parse::<Scale>().is_err());
+    assert!("".parse::<Refl>().is_err());
+}
+
+#[test]
+fn enums_display_test() {
+    assert_eq!("lanczos", Scale::Lanczos.to_string());
+    assert_eq!("vertical", Refl::Vertical.to_string());
+    assert_eq!("to_zero_inv", Thresh::ToZeroInv.to_string());
+    assert_eq!("d50", White::D50.to_string());
+    assert_eq!("direct", Bilateral::Direct.to_string());
+
+    // Round trip
+    for method in [Scale::NearestNeighbor, Scale::Bilinear, Scale::Bicubic, Scale::Lanczos,
+                   Scale::Mitchell, Scale::CatmullRom].iter() {
+        assert_eq!(*method, method.to_string().parse().unwrap());
+    }
+}
+
+#[test]
+fn gray_method_str_test() {
+    assert_eq!(GrayMethod::Luminance709, "Luminance709".parse().unwrap());
+    assert_eq!(GrayMethod::SingleChannel(2), "single_channel(2)".parse().unwrap());
+    assert_eq!("single_channel(1)", GrayMethod::SingleChannel(1).to_string());
+    assert_eq!(GrayMethod::Desaturate, GrayMethod::Desaturate.to_string().parse().unwrap());
+
+    assert!("single_channel".parse::<GrayMethod>().is_err());
+    assert!("single_channel(x)".parse::<GrayMethod>().is_err());
+}
+
+#[test]
+fn subsampling_str_test() {
+    assert_eq!(Subsampling::S420, "4:2:0".parse().unwrap());
+    assert_eq!("4:2:2", Subsampling::S422.to_string());
+    assert!("4:1:1".parse::<Subsampling>().is_err());
+}
+
+#[test]
+fn freq_mode_str_test() {
+    assert_eq!(FreqMode::ButterworthBandPass, "butterworth_band_pass".parse().unwrap());
+    assert_eq!("ideal_low_pass", FreqMode::IdealLowPass.to_string());
+}
+
+#[test]
+fn border_str_test() {
+    assert_eq!(Border::Reflect, "reflect".parse().unwrap());
+    assert_eq!("wrap", Border::Wrap.to_string());
+}
+
+#[test]
+fn color_metric_str_test() {
+    assert_eq!(ColorMetric::EuclideanRGB, "euclidean-rgb".parse().unwrap());
+    assert_eq!("delta_e", ColorMetric::DeltaE.to_string());
+}
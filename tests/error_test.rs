@@ -0,0 +1,55 @@
+use imgproc_rs::error::ImgProcError;
+use imgproc_rs::filter;
+use imgproc_rs::image::{BaseImage, Image, ImageInfo};
+use imgproc_rs::transform;
+
+#[test]
+fn dimension_mismatch_test() {
+    let a: Image<u8> = Image::blank(ImageInfo::new(2, 2, 1, false));
+    let b: Image<u8> = Image::blank(ImageInfo::new(3, 2, 1, false));
+
+    match filter::residual(&a, &b) {
+        Err(ImgProcError::DimensionMismatch { expected, found }) => {
+            assert_eq!(a.info(), expected);
+            assert_eq!(b.info(), found);
+        },
+        _ => panic!("expected DimensionMismatch"),
+    }
+
+    let err = filter::residual(&a, &b).unwrap_err();
+    assert_eq!("image dimensions must be equal: expected 2x2 with 1 channels, found 3x2 with 1 channels",
+               err.to_string());
+}
+
+#[test]
+fn channel_mismatch_test() {
+    let img: Image<f32> = Image::blank(ImageInfo::new(2, 2, 3, false));
+
+    match transform::rotate_in_place(&img, 45.0, &[0.0]) {
+        Err(ImgProcError::ChannelMismatch { expected, found }) => {
+            assert_eq!(3, expected);
+            assert_eq!(1, found);
+        },
+        _ => panic!("expected ChannelMismatch"),
+    }
+
+    let err = transform::rotate_in_place(&img, 45.0, &[0.0]).unwrap_err();
+    assert_eq!("invalid number of channels: expected 3, found 1", err.to_string());
+}
+
+#[test]
+fn io_error_test() {
+    use imgproc_rs::error::ImgIoError;
+    use std::error::Error;
+
+    let err = imgproc_rs::io::read("does/not/exist.png").unwrap_err();
+    assert!(matches!(err, ImgIoError::IoError(_)));
+    assert!(err.source().is_some());
+
+    // ImgIoError converts into a boxed error with `?`
+    fn read_boxed() -> Result<(), Box<dyn Error>> {
+        imgproc_rs::io::read("does/not/exist.tiff")?;
+        Ok(())
+    }
+    assert!(read_boxed().is_err());
+}
@@ -3,12 +3,16 @@
 mod common;
 
 use common::setup;
-use imgproc_rs::{filter, colorspace};
-use imgproc_rs::image::Image;
+use imgproc_rs::{draw, filter, colorspace};
+use imgproc_rs::filter::Filter;
+use imgproc_rs::image::{BaseImage, Image, ImageInfo};
 use imgproc_rs::io::write;
 
 use std::time::SystemTime;
-use imgproc_rs::enums::{Bilateral, Thresh};
+use imgproc_rs::enums::{Bilateral, Border, ConvMode, Thresh};
+use imgproc_rs::error::ImgProcError;
+#[cfg(feature = "fft")]
+use imgproc_rs::enums::FreqMode;
 
 const PATH: &str = "images/yosemite.jpg";
 
@@ -184,4 +188,521 @@ fn threshold_test() {
     write(&trunc.into(), "images/tests/filter/thresh_trunc.png").unwrap();
     write(&zero.into(), "images/tests/filter/thresh_to_zero.png").unwrap();
     write(&zero_inv.into(), "images/tests/filter/thresh_to_zero_inv.png").unwrap();
-}
\ No newline at end of file
+}
+
+#[test]
+fn optical_flow_lk_test() {
+    let pattern = |x: f32, y: f32| (x * 0.4).sin() * 50.0 + (y * 0.3).cos() * 50.0;
+    let mut frame1 = Vec::new();
+    let mut frame2 = Vec::new();
+    for y in 0..20 {
+        for x in 0..20 {
+            frame1.push(pattern(x as f32, y as f32));
+            frame2.push(pattern(x as f32 - 1.0, y as f32));
+        }
+    }
+    let frame1 = Image::from_vec(20, 20, 1, false, frame1);
+    let frame2 = Image::from_vec(20, 20, 1, false, frame2);
+
+    let (u, v) = filter::optical_flow_lk(&frame1, &frame2, 7).unwrap();
+    assert!((u.get_pixel(10, 10)[0] - 1.0).abs() < 0.2);
+    assert!(v.get_pixel(10, 10)[0].abs() < 0.2);
+
+    assert!(filter::optical_flow_lk(&frame1, &frame2, 4).is_err());
+}
+
+#[test]
+fn threshold_color_test() {
+    let img: Image<f32> = Image::from_slice(2, 1, 4, true,
+                                            &[10.0, 50.0, 90.0, 1.0, 60.0, 20.0, 40.0, 0.5]);
+
+    let output = filter::threshold_color(&img, &[30.0, 30.0, 50.0], 255.0, Thresh::Binary).unwrap();
+    assert_eq!(&[0.0, 255.0, 255.0, 1.0, 255.0, 0.0, 0.0, 0.5], output.data());
+
+    match filter::threshold_color(&img, &[30.0, 30.0], 255.0, Thresh::Binary) {
+        Err(ImgProcError::ChannelMismatch { expected, found }) => assert_eq!((3, 2), (expected, found)),
+        _ => panic!("expected ChannelMismatch"),
+    }
+}
+
+#[test]
+fn threshold_luma_test() {
+    let img: Image<f32> = Image::from_slice(2, 1, 3, false,
+                                            &[0.0, 200.0, 0.0, 0.0, 0.0, 200.0]);
+
+    let output = filter::threshold_luma(&img, 100.0, 1.0, Thresh::Binary).unwrap();
+    assert_eq!(1, output.info().channels);
+    assert_eq!(&[1.0, 0.0], output.data());
+}
+
+#[test]
+fn separable_filter_lengths_test() {
+    // Impulse response of a 3-tall, 9-wide box blur
+    let mut data = vec![0.0; 121];
+    data[5 * 11 + 5] = 27.0;
+    let img: Image<f32> = Image::from_vec(11, 11, 1, false, data);
+
+    let output = filter::separable_filter(&img, &[1.0 / 3.0; 3], &[1.0 / 9.0; 9]).unwrap();
+    for y in 0..11 {
+        for x in 0..11 {
+            let expected = if (4..=6).contains(&y) && (1..=9).contains(&x) { 1.0 } else { 0.0 };
+            assert!((output.get_pixel(x, y)[0] - expected).abs() < 1e-5);
+        }
+    }
+
+    assert!(filter::separable_filter(&img, &[1.0; 2], &[1.0; 9]).is_err());
+    assert!(filter::separable_filter(&img, &[1.0; 3], &[1.0; 4]).is_err());
+}
+
+#[test]
+fn separable_filter_border_test() {
+    let img: Image<f32> = Image::from_vec(8, 1, 1, false, (0..8).map(|i| i as f32).collect())
+        .with_border(Border::Wrap);
+
+    // Both passes wrap around the edges of the image
+    let output = filter::separable_filter(&img, &[1.0], &[1.0 / 3.0; 3]).unwrap();
+    assert_eq!(Border::Wrap, output.border_mode());
+    assert!((output.get_pixel(0, 0)[0] - 8.0 / 3.0).abs() < 1e-5);
+    assert!((output.get_pixel(7, 0)[0] - 13.0 / 3.0).abs() < 1e-5);
+
+    let output = filter::separable_filter(&img.with_border(Border::Reflect), &[1.0], &[1.0 / 3.0; 3]).unwrap();
+    assert!((output.get_pixel(0, 0)[0] - 2.0 / 3.0).abs() < 1e-5);
+}
+
+#[test]
+fn convolve_mode_test() {
+    let img: Image<f32> = Image::from_vec(3, 2, 1, false, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    let kernel = [1.0, 10.0];
+
+    let full = filter::convolve_mode(&img, &kernel, 2, 1, ConvMode::Full).unwrap();
+    assert_eq!(ImageInfo::new(4, 2, 1, false), full.info());
+    assert_eq!(&[1.0, 12.0, 23.0, 30.0, 4.0, 45.0, 56.0, 60.0], full.data());
+
+    let valid = filter::convolve_mode(&img, &kernel, 2, 1, ConvMode::Valid).unwrap();
+    assert_eq!(ImageInfo::new(2, 2, 1, false), valid.info());
+    assert_eq!(&[12.0, 23.0, 45.0, 56.0], valid.data());
+
+    // Same mode repeats edge pixels
+    let same = filter::convolve_mode(&img, &[0.0, 1.0, 2.0], 3, 1, ConvMode::Same).unwrap();
+    assert_eq!(img.info(), same.info());
+    assert_eq!(&[3.0, 4.0, 7.0, 12.0, 13.0, 16.0], same.data());
+
+    assert!(filter::convolve_mode(&img, &kernel, 2, 1, ConvMode::Same).is_err());
+    assert!(filter::convolve_mode(&img, &[1.0; 4], 4, 1, ConvMode::Valid).is_err());
+    assert!(filter::convolve_mode(&img, &kernel, 3, 1, ConvMode::Full).is_err());
+}
+
+#[test]
+fn convolve_2d_test() {
+    let data = (0..35).map(|i| ((i * 7) % 11) as f32).collect();
+    let img: Image<f32> = Image::from_vec(7, 5, 1, false, data);
+    let kernel: Vec<f32> = (0..15).map(|i| i as f32 - 4.0).collect();
+
+    let expected = filter::convolve_mode(&img, &kernel, 5, 3, ConvMode::Same).unwrap();
+    assert_eq!(expected, filter::convolve_2d(&img, &kernel, 5, 3).unwrap());
+
+    // Large kernels are applied in the frequency domain, so allow for roundoff
+    let kernel: Vec<f32> = (0..225).map(|i| ((i * 13) % 17) as f32 / 17.0).collect();
+    let expected = filter::convolve_mode(&img, &kernel, 15, 15, ConvMode::Same).unwrap();
+    let proc = filter::convolve_2d(&img, &kernel, 15, 15).unwrap();
+    for (a, b) in expected.data().iter().zip(proc.data().iter()) {
+        assert!((a - b).abs() < 1e-2);
+    }
+}
+
+#[cfg(feature = "fft")]
+#[test]
+fn convolve_fft_test() {
+    let data = (0..70).map(|i| ((i * 7) % 11) as f32).collect();
+    let img: Image<f32> = Image::from_vec(7, 5, 2, false, data);
+    let kernel: Vec<f32> = (0..15).map(|i| i as f32 - 4.0).collect();
+
+    let expected = filter::convolve_mode(&img, &kernel, 5, 3, ConvMode::Same).unwrap();
+    let proc = filter::convolve_fft(&img, &kernel, 5, 3).unwrap();
+    assert_eq!(img.info(), proc.info());
+    for (a, b) in expected.data().iter().zip(proc.data().iter()) {
+        assert!((a - b).abs() < 1e-3);
+    }
+
+    assert!(filter::convolve_fft(&img, &kernel, 3, 5).is_ok());
+    assert!(filter::convolve_fft(&img, &[1.0; 4], 2, 2).is_err());
+}
+
+#[test]
+fn deconvolve_richardson_lucy_test() {
+    let mut data = vec![10.0; 81];
+    data[4 * 9 + 4] = 200.0;
+    data[2 * 9 + 6] = 120.0;
+    let img: Image<f32> = Image::from_vec(9, 9, 1, false, data);
+    let psf = [1.0, 2.0, 1.0, 2.0, 4.0, 2.0, 1.0, 2.0, 1.0];
+
+    let blurred = filter::convolve_2d(&img, &psf.iter().map(|v| v / 16.0).collect::<Vec<f32>>(), 3, 3).unwrap();
+    let proc = filter::deconvolve_richardson_lucy(&blurred, &psf, 3, 3, 50).unwrap();
+
+    let err = |a: &Image<f32>| -> f32 {
+        a.data().iter().zip(img.data().iter()).map(|(x, y)| (x - y).abs()).sum()
+    };
+    assert!(err(&proc) < 0.75 * err(&blurred));
+    assert!(proc.get_pixel(4, 4)[0] > blurred.get_pixel(4, 4)[0]);
+
+    assert!(filter::deconvolve_richardson_lucy(&blurred, &[1.0, -1.0, 1.0], 3, 1, 1).is_err());
+    assert!(filter::deconvolve_richardson_lucy(&blurred, &[1.0; 4], 2, 2, 1).is_err());
+}
+
+#[test]
+fn mean_shift_test() {
+    // Two noisy flat regions separated by a strong edge
+    let mut data = Vec::new();
+    for y in 0..6 {
+        for x in 0..6 {
+            let noise = ((x + y) % 2) as u8 * 6;
+            let base = if x < 3 { 40 } else { 200 };
+            data.extend_from_slice(&[base + noise, base + noise, base + noise]);
+        }
+    }
+    let img: Image<u8> = Image::from_vec(6, 6, 3, false, data);
+
+    let output = filter::mean_shift(&img, 2, 10.0, 10).unwrap();
+    assert_eq!(img.info(), output.info());
+
+    // The noise is flattened within each region, and the edge is kept
+    let left = output.get_pixel(1, 2)[0] as i32;
+    let right = output.get_pixel(4, 2)[0] as i32;
+    assert!((left - output.get_pixel(0, 0)[0] as i32).abs() <= 1);
+    assert!((right - output.get_pixel(5, 5)[0] as i32).abs() <= 1);
+    assert!(right - left > 100);
+
+    assert!(filter::mean_shift(&img, 0, 10.0, 10).is_err());
+    assert!(filter::mean_shift(&img, 2, 0.0, 10).is_err());
+    assert!(filter::mean_shift(&img, 2, 10.0, 0).is_err());
+}
+
+#[test]
+fn hough_circles_test() {
+    let mut img: Image<u8> = Image::blank(ImageInfo::new(40, 40, 1, false));
+    draw::circle(&mut img, 20, 18, 10, &[255]).unwrap();
+
+    let circles = filter::hough_circles(&img, 6, 14, 20).unwrap();
+    assert!(!circles.is_empty());
+
+    let (x, y, r) = circles[0];
+    assert!((x as i32 - 20).abs() <= 1 && (y as i32 - 18).abs() <= 1);
+    assert!((r as i32 - 10).abs() <= 1);
+
+    assert!(filter::hough_circles(&img, 10, 5, 20).is_err());
+}
+
+#[test]
+fn vector_median_filter_test() {
+    // A red image with a single green impulse
+    let mut data = [255, 0, 0].repeat(9);
+    data[12..15].copy_from_slice(&[0, 255, 0]);
+    let img: Image<u8> = Image::from_vec(3, 3, 3, false, data);
+
+    let proc = filter::vector_median_filter(&img, 1).unwrap();
+    assert_eq!(&[255, 0, 0].repeat(9)[..], proc.data());
+
+    // Output colors always come from the input
+    let img: Image<u8> = Image::from_vec(3, 1, 3, false, vec![255, 0, 0, 0, 255, 0, 0, 0, 255]);
+    let proc = filter::vector_median_filter(&img, 1).unwrap();
+    for i in 0..3 {
+        assert!(img.data().chunks(3).any(|p| p == &proc[i]));
+    }
+
+    // Radii larger than the image use the whole image as the kernel
+    let proc = filter::vector_median_filter(&img, 2).unwrap();
+    assert_eq!(proc, filter::vector_median_filter(&img, u32::MAX).unwrap());
+    assert!(filter::vector_median_filter(&img, 0).is_err());
+}
+
+#[cfg(feature = "fft")]
+#[test]
+fn saliency_spectral_test() {
+    // A small bright square on a flat background
+    let mut data = vec![0.2; 32 * 32];
+    for y in 20..24 {
+        for x in 6..10 {
+            data[y * 32 + x] = 1.0;
+        }
+    }
+    let img: Image<f32> = Image::from_vec(32, 32, 1, false, data);
+
+    let proc = filter::saliency_spectral(&img).unwrap();
+    assert_eq!(img.info(), proc.info());
+    assert!(proc.data().iter().all(|&val| (0.0..=1.0).contains(&val)));
+
+    // The most salient pixel lies around the square
+    let max_i = (0..proc.data().len()).max_by(|&i, &j| proc[i][0].partial_cmp(&proc[j][0]).unwrap()).unwrap();
+    let (x, y) = (max_i % 32, max_i / 32);
+    assert!((3..13).contains(&x) && (17..27).contains(&y));
+}
+
+#[test]
+fn filter_1d_u8_test() {
+    let img: Image<u8> = Image::from_vec(4, 2, 1, false, vec![0, 100, 200, 250, 10, 20, 30, 40]);
+
+    let blur = filter::filter_1d_u8(&img, &[1, 2, 1], 4, false).unwrap();
+    assert_eq!(&[25, 100, 188, 238, 13, 20, 30, 38], blur.data());
+
+    let vert = filter::filter_1d_u8(&img, &[1, 1, 0], 2, true).unwrap();
+    assert_eq!(&[0, 100, 200, 250, 5, 60, 115, 145], vert.data());
+
+    // Results are clamped
+    let sharp = filter::filter_1d_u8(&img, &[-1, 3, -1], 1, false).unwrap();
+    assert_eq!(&[0, 100, 250, 255, 0, 20, 30, 50], sharp.data());
+
+    assert!(filter::filter_1d_u8(&img, &[1, 1], 2, false).is_err());
+    assert!(filter::filter_1d_u8(&img, &[1], 0, false).is_err());
+}
+
+#[cfg(feature = "fft")]
+#[test]
+fn frequency_filter_test() {
+    // A flat image plus a checkerboard at the highest frequency, with a constant alpha channel
+    let data = (0..64).flat_map(|i| {
+        let checker = if (i % 8 + i / 8) % 2 == 0 { 0.25 } else { -0.25 };
+        vec![0.5 + checker, 1.0]
+    }).collect();
+    let img: Image<f32> = Image::from_vec(8, 8, 2, true, data);
+
+    let low = filter::frequency_filter(&img, FreqMode::IdealLowPass, 0.25).unwrap();
+    assert_eq!(img.info(), low.info());
+    assert!(low.data().chunks(2).all(|p| (p[0] - 0.5).abs() < 1e-4 && p[1] == 1.0));
+
+    let high = filter::frequency_filter(&img, FreqMode::ButterworthHighPass, 0.1).unwrap();
+    assert!(high.data().chunks(2).zip(img.data().chunks(2))
+        .all(|(p, q)| (p[0] - (q[0] - 0.5)).abs() < 0.01 && p[1] == 1.0));
+
+    let band = filter::frequency_filter(&img, FreqMode::IdealBandPass, 0.1).unwrap();
+    assert!(band.data().chunks(2).all(|p| p[0].abs() < 1e-4));
+
+    assert!(filter::frequency_filter(&img, FreqMode::IdealLowPass, 0.0).is_err());
+}
+
+#[cfg(feature = "fft")]
+#[test]
+fn remove_periodic_noise_test() {
+    // A slow vertical wave plus vertical stripes with a period of 4 pixels
+    let data = (0..256).map(|i| {
+        let (x, y) = ((i % 16) as f32, (i / 16) as f32);
+        0.5 + 0.1 * (std::f32::consts::FRAC_PI_8 * y).sin() + 0.2 * (std::f32::consts::FRAC_PI_2 * x).cos()
+    }).collect();
+    let img: Image<f32> = Image::from_vec(16, 16, 1, false, data);
+
+    let proc = filter::remove_periodic_noise(&img, &[(0.25, 0.0, 0.02)]).unwrap();
+    assert_eq!(img.info(), proc.info());
+    for i in 0..256 {
+        let stripe = 0.2 * (std::f32::consts::FRAC_PI_2 * (i % 16) as f32).cos();
+        assert!((proc[i][0] - (img[i][0] - stripe)).abs() < 1e-4);
+    }
+
+    assert!(filter::remove_periodic_noise(&img, &[(0.25, 0.0, 0.0)]).is_err());
+}
+
+#[test]
+fn filter_chain_test() {
+    let data = (0..75).map(|i| ((i * 13) % 17) as f32).collect();
+    let img: Image<f32> = Image::from_vec(5, 5, 3, false, data);
+
+    let filters: Vec<Box<dyn Filter>> = vec![
+        Box::new(filter::BoxFilter { size: 3 }),
+        Box::new(filter::Sharpen),
+    ];
+    let expected = filter::sharpen(&filter::box_filter(&img, 3).unwrap()).unwrap();
+    assert_eq!(expected, filter::apply_chain(&img, &filters).unwrap());
+    assert_eq!(filter::box_filter(&img, 3).unwrap(), filters[0].apply(&img).unwrap());
+
+    assert_eq!(img, filter::apply_chain(&img, &[]).unwrap());
+
+    let filters: Vec<Box<dyn Filter>> = vec![Box::new(filter::BoxFilter { size: 2 })];
+    assert!(filter::apply_chain(&img, &filters).is_err());
+}
+
+#[test]
+fn gradient_test() {
+    // Intensity increases to the right and decreases downward
+    let data = (0..25).map(|i| ((i % 5) * 2) as f32 - (i / 5) as f32).collect();
+    let img: Image<f32> = Image::from_vec(5, 5, 1, false, data);
+
+    let grad_x = filter::gradient_x(&img).unwrap();
+    let grad_y = filter::gradient_y(&img).unwrap();
+    assert_eq!(img.info(), grad_x.info());
+    assert_eq!(&[16.0], grad_x.get_pixel(2, 2));
+    assert_eq!(&[-8.0], grad_y.get_pixel(2, 2));
+
+    // The magnitude matches the Sobel operator
+    let sobel = filter::sobel(&img).unwrap();
+    assert!((sobel.get_pixel(2, 2)[0] - (16.0f32 * 16.0 + 8.0 * 8.0).sqrt()).abs() < 1e-4);
+
+    let rgb: Image<f32> = Image::from_vec(1, 1, 3, false, vec![0.0; 3]);
+    assert!(filter::gradient_x(&rgb).is_err());
+}
+
+#[test]
+fn sharpen_luma_test() {
+    // Gray step edge
+    let mut data = Vec::new();
+    for _ in 0..8 {
+        for x in 0..8 {
+            let val = if x < 4 { 80 } else { 170 };
+            data.extend_from_slice(&[val, val, val]);
+        }
+    }
+    let img: Image<u8> = Image::from_vec(8, 8, 3, false, data);
+
+    let output = filter::sharpen_luma(&img, 1.0, 1.0).unwrap();
+    assert_eq!(img.info(), output.info());
+    assert!(output.get_pixel(3, 4)[0] < 80);
+    assert!(output.get_pixel(4, 4)[0] > 170);
+
+    // Gray stays gray
+    let p = output.get_pixel(4, 4);
+    assert!((p[0] as i32 - p[1] as i32).abs() <= 1 && (p[1] as i32 - p[2] as i32).abs() <= 1);
+
+    // Flat images are unchanged, and alpha is preserved
+    let flat: Image<u8> = Image::from_vec(4, 4, 4, true, [200, 30, 40, 77].repeat(16));
+    let output = filter::sharpen_luma(&flat, 2.0, 1.0).unwrap();
+    for i in 0..16 {
+        for c in 0..3 {
+            assert!((output[i][c] as i32 - flat[i][c] as i32).abs() <= 1);
+        }
+        assert_eq!(77, output[i][3]);
+    }
+
+    assert!(filter::sharpen_luma(&img, 1.0, 0.0).is_err());
+    assert!(filter::sharpen_luma(&img, -1.0, 1.0).is_err());
+    let gray: Image<u8> = Image::from_vec(2, 2, 1, false, vec![0; 4]);
+    assert!(filter::sharpen_luma(&gray, 1.0, 1.0).is_err());
+}
+
+#[test]
+fn local_mean_variance_test() {
+    let img: Image<f32> = Image::from_slice(3, 3, 1, false,
+                                            &[1.0, 2.0, 3.0,
+                                              4.0, 5.0, 6.0,
+                                              7.0, 8.0, 9.0]);
+
+    let (mean, variance) = filter::local_mean_variance(&img, 3).unwrap();
+    assert_eq!(img.info(), mean.info());
+    assert_eq!(img.info(), variance.info());
+    assert!((mean.get_pixel(1, 1)[0] - 5.0).abs() < 1e-4);
+    assert!((variance.get_pixel(1, 1)[0] - 60.0 / 9.0).abs() < 1e-4);
+
+    // Windows are clipped at the boundaries
+    assert!((mean.get_pixel(0, 0)[0] - 3.0).abs() < 1e-4);
+    assert!((variance.get_pixel(0, 0)[0] - 2.5).abs() < 1e-4);
+
+    // Channels are independent, and constant channels have no variance
+    let data = (0..16).flat_map(|i| vec![i as f32, 100.0]).collect();
+    let img: Image<f32> = Image::from_vec(4, 4, 2, true, data);
+    let (mean, variance) = filter::local_mean_variance(&img, 1).unwrap();
+    assert_eq!(img, mean);
+    assert!(variance.data().iter().all(|val| val.abs() < 1e-4));
+
+    assert!(filter::local_mean_variance(&img, 2).is_err());
+}
+
+#[test]
+fn threshold_local_test() {
+    // Dark marks on a background that brightens from left to right
+    let mut img: Image<u8> = Image::blank(ImageInfo::new(32, 8, 1, false));
+    for y in 0..8 {
+        for x in 0..32 {
+            let background = 100 + 5 * x as u8;
+            let is_mark = x % 8 == 3 && (3..6).contains(&y);
+            img.set_pixel(x, y, &[if is_mark { background - 60 } else { background }]);
+        }
+    }
+
+    let output = filter::threshold_sauvola(&img, 7, 0.2, 128.0).unwrap();
+    assert_eq!(img.info(), output.info());
+    for y in 0..8 {
+        for x in 0..32 {
+            let is_mark = x % 8 == 3 && (3..6).contains(&y);
+            assert_eq!(&[if is_mark { 0 } else { 255 }], output.get_pixel(x, y));
+        }
+    }
+
+    let img: Image<u8> = Image::from_vec(3, 3, 2, true, vec![0, 7, 0, 7, 0, 7, 0, 7, 10, 7, 0, 7, 0, 7, 0, 7, 0, 7]);
+    let output = filter::threshold_niblack(&img, 3, -0.2).unwrap();
+    assert_eq!(&[255, 7], output.get_pixel(1, 1));
+    assert_eq!(&[0, 7], output.get_pixel(0, 0));
+
+    assert!(filter::threshold_niblack(&img, 2, -0.2).is_err());
+    assert!(filter::threshold_sauvola(&img, 3, 0.2, 0.0).is_err());
+    let rgb: Image<u8> = Image::blank(ImageInfo::new(2, 2, 3, false));
+    assert!(filter::threshold_sauvola(&rgb, 3, 0.2, 128.0).is_err());
+}
+
+#[test]
+fn dehaze_test() {
+    // Sky on the left, and a scene whose pixels each have a zero channel on the right, seen
+    // through haze with a transmission of 0.5
+    let (atmosphere, t) = (220.0, 0.5);
+    let scene = |x: u32, y: u32| [(x * 6) as f32, 0.0, (y * 10) as f32];
+    let mut img: Image<u8> = Image::blank(ImageInfo::new(40, 20, 3, false));
+    for y in 0..20 {
+        for x in 15..40 {
+            let p: Vec<u8> = scene(x, y).iter().map(|val| (val * t + atmosphere * (1.0 - t)).round() as u8).collect();
+            img.set_pixel(x, y, &p);
+        }
+        for x in 0..15 {
+            img.set_pixel(x, y, &[220, 220, 220]);
+        }
+    }
+
+    let output = filter::dehaze(&img, 1.0).unwrap();
+    assert_eq!(img.info(), output.info());
+    assert_eq!(&[220, 220, 220], output.get_pixel(2, 10));
+    for y in 0..20 {
+        for x in 23..40 {
+            for (&val, expected) in output.get_pixel(x, y).iter().zip(scene(x, y).iter()) {
+                assert!((val as f32 - expected).abs() <= 2.0);
+            }
+        }
+    }
+
+    // No haze is removed with an omega of 0
+    let output = filter::dehaze(&img, 0.0).unwrap();
+    assert_eq!(img, output);
+
+    assert!(filter::dehaze(&img, 1.5).is_err());
+    let gray: Image<u8> = Image::blank(ImageInfo::new(2, 2, 1, false));
+    assert!(filter::dehaze(&gray, 0.5).is_err());
+}
+
+#[test]
+fn smart_sharpen_test() {
+    // Slightly noisy flat regions on either side of a step edge
+    let data = (0..(12 * 6)).map(|i| {
+        let x = i % 12;
+        let noise = if x % 2 == 0 { 1.0 } else { -1.0 };
+        if x < 6 { 50.0 + noise } else { 150.0 + noise }
+    }).collect();
+    let img: Image<f32> = Image::from_vec(12, 6, 1, false, data);
+
+    let output = filter::smart_sharpen(&img, 1.0, 1.0, 100.0).unwrap();
+    assert_eq!(img.info(), output.info());
+    assert!(output.get_pixel(5, 3)[0] < img.get_pixel(5, 3)[0]);
+    assert!(output.get_pixel(6, 3)[0] > img.get_pixel(6, 3)[0]);
+    for x in (0..3).chain(9..12) {
+        assert_eq!(img.get_pixel(x, 3), output.get_pixel(x, 3));
+    }
+
+    // Blanket unsharp masking would change the noise
+    let everywhere = filter::smart_sharpen(&img, 1.0, 1.0, -1.0).unwrap();
+    assert_ne!(img.get_pixel(1, 3), everywhere.get_pixel(1, 3));
+
+    // Alpha is unchanged
+    let data = (0..(12 * 6)).flat_map(|i| {
+        let val = if i % 12 < 6 { 0.2 } else { 0.8 };
+        vec![val, val, val, 0.5]
+    }).collect();
+    let rgba: Image<f32> = Image::from_vec(12, 6, 4, true, data);
+    let output = filter::smart_sharpen(&rgba, 1.0, 1.0, 0.5).unwrap();
+    assert!(output.get_pixel(6, 3)[0] > 0.8);
+    assert!(output.data().chunks(4).all(|p| p[3] == 0.5));
+
+    assert!(filter::smart_sharpen(&img, -1.0, 1.0, 1.0).is_err());
+    assert!(filter::smart_sharpen(&img, 1.0, 0.0, 1.0).is_err());
+}
@@ -1,4 +1,6 @@
+use imgproc_rs::enums::Border;
 use imgproc_rs::image::{Image, ImageInfo, SubImage, BaseImage, Pixel};
+use imgproc_rs::transform;
 
 #[test]
 fn image_general_test() {
@@ -130,6 +132,22 @@ fn image_map_test() {
     assert_eq!(&[1, 2, 8, 4, 2, 3, 9, 5, 6, 5, 9, 3, 5, 4, 8, 2], img1.data());
 }
 
+#[test]
+fn image_map_neighborhood_test() {
+    let img: Image<u8> = Image::from_slice(3, 2, 1, false, &[1, 2, 3, 4, 5, 6]);
+
+    // Maximum of each 3x3 neighborhood
+    let max = img.map_neighborhood(3, |neighborhood, p_out| {
+        let mut max = 0;
+        for i in 0..(neighborhood.info().size() as usize) {
+            max = std::cmp::max(max, neighborhood[i][0]);
+        }
+        p_out.push(max);
+    });
+    assert_eq!(&[5, 6, 6, 5, 6, 6], max.data());
+    assert_eq!(img.info(), max.info());
+}
+
 #[test]
 fn image_apply_test() {
     let mut img1: Image<u8> = Image::from_slice(2, 2, 4, true,
@@ -179,3 +197,211 @@ fn subimage_test() {
     assert_eq!(pixel, subimg[2]);
     assert_eq!(pixel, subimg.get_pixel(0, 1));
 }
+
+#[cfg(feature = "image-interop")]
+#[test]
+fn image_dynamic_image_test() {
+    let img: Image<u8> = Image::from_vec(2, 1, 3, false, vec![1, 2, 3, 4, 5, 6]);
+    let dyn_img: image::DynamicImage = img.clone().into();
+    assert_eq!(image::ColorType::Rgb8, dyn_img.color());
+    assert_eq!(img, Image::from(dyn_img));
+
+    let img_gray: Image<u8> = Image::from_vec(1, 2, 2, true, vec![10, 255, 20, 128]);
+    let dyn_img: image::DynamicImage = img_gray.clone().into();
+    assert_eq!(image::ColorType::La8, dyn_img.color());
+    assert_eq!(img_gray, Image::from(dyn_img));
+
+    // Non-8-bit images are converted to 8 bits
+    let dyn_img = image::DynamicImage::ImageLuma16(image::ImageBuffer::from_raw(1, 1, vec![u16::MAX]).unwrap());
+    assert_eq!(&[255], Image::from(dyn_img).get_pixel(0, 0));
+}
+
+#[test]
+fn image_get_pixel_checked_test() {
+    let img: Image<u8> = Image::from_slice(2, 2, 2, false, &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+    assert_eq!(Some(&[7, 8][..]), img.get_pixel_checked(1, 1));
+    assert_eq!(None, img.get_pixel_checked(2, 0));
+    assert_eq!(None, img.get_pixel_checked(0, 2));
+
+    assert_eq!(&[3, 4], img.get_pixel_or(1, 0, &[0, 0]));
+    assert_eq!(&[0, 0], img.get_pixel_or(5, 5, &[0, 0]));
+}
+
+#[test]
+fn image_set_region_test() {
+    let mut img: Image<u8> = Image::blank(ImageInfo::new(3, 3, 1, false));
+
+    img.set_region(1, 1, 2, 2, &[1, 2, 3, 4]).unwrap();
+    assert_eq!(&[0, 0, 0, 0, 1, 2, 0, 3, 4], img.data());
+
+    img.set_region(0, 0, 3, 1, &[5, 6, 7]).unwrap();
+    assert_eq!(&[5, 6, 7, 0, 1, 2, 0, 3, 4], img.data());
+
+    // Region out of bounds
+    assert!(img.set_region(2, 2, 2, 1, &[1, 2]).is_err());
+    assert!(img.set_region(u32::MAX, 0, 1, 1, &[1]).is_err());
+    assert!(img.set_region(0, u32::MAX, 1, 1, &[1]).is_err());
+    // Data length does not match region size
+    assert!(img.set_region(0, 0, 2, 2, &[1, 2, 3]).is_err());
+}
+
+#[test]
+fn image_gray_to_rgb_test() {
+    let gray: Image<u8> = Image::from_slice(2, 1, 1, false, &[10, 20]);
+    let gray_alpha: Image<u8> = Image::from_slice(2, 1, 2, true, &[10, 100, 20, 200]);
+
+    let rgb = gray.gray_to_rgb().unwrap();
+    assert_eq!(ImageInfo::new(2, 1, 3, false), rgb.info());
+    assert_eq!(&[10, 10, 10, 20, 20, 20], rgb.data());
+
+    let rgba = gray_alpha.gray_to_rgb().unwrap();
+    assert_eq!(ImageInfo::new(2, 1, 4, true), rgba.info());
+    assert_eq!(&[10, 10, 10, 100, 20, 20, 20, 200], rgba.data());
+
+    let rgba = gray.gray_to_rgba(255).unwrap();
+    assert_eq!(ImageInfo::new(2, 1, 4, true), rgba.info());
+    assert_eq!(&[10, 10, 10, 255, 20, 20, 20, 255], rgba.data());
+    assert_eq!(&[10, 10, 10, 100, 20, 20, 20, 200], gray_alpha.gray_to_rgba(255).unwrap().data());
+
+    assert!(rgb.gray_to_rgb().is_err());
+    assert!(rgb.gray_to_rgba(0).is_err());
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn image_par_map_test() {
+    let data: Vec<u8> = (0..=255).collect();
+    let img: Image<u8> = Image::from_vec(16, 8, 2, true, data);
+
+    assert_eq!(img.map_channels(|c| c as f32 / 2.0), img.par_map_channels(|c| c as f32 / 2.0));
+
+    let f = |p: &[u8], p_out: &mut Vec<u16>| {
+        p_out.push(p[0] as u16 + p[1] as u16);
+    };
+    assert_eq!(img.map_pixels(f), img.par_map_pixels(f));
+}
+
+#[test]
+fn image_subsample_test() {
+    let data: Vec<u8> = (0..20).collect();
+    let img: Image<u8> = Image::from_vec(5, 4, 1, false, data);
+
+    let output = img.subsample(2, 3);
+    assert_eq!(ImageInfo::new(3, 2, 1, false), output.info());
+    assert_eq!(&[0, 2, 4, 15, 17, 19], output.data());
+
+    assert_eq!(img, img.subsample(1, 1));
+}
+
+#[test]
+#[should_panic]
+fn image_subsample_zero_step_test() {
+    let img: Image<u8> = Image::blank(ImageInfo::new(2, 2, 1, false));
+    img.subsample(0, 1);
+}
+
+#[test]
+fn image_fold_test() {
+    let img: Image<u8> = Image::from_slice(3, 1, 2, true, &[1, 255, 200, 0, 3, 255]);
+
+    assert_eq!(714, img.fold_channels(0u32, |acc, c| acc + c as u32));
+    assert_eq!(Some(0), img.fold_channels(None, |acc: Option<u8>, c| Some(acc.map_or(c, |a| a.min(c)))));
+
+    // Count the fully opaque pixels
+    assert_eq!(2, img.fold_pixels(0, |acc, p| if p[1] == 255 { acc + 1 } else { acc }));
+}
+
+#[test]
+fn image_crop_in_place_test() {
+    let data: Vec<u8> = (0..40).collect();
+    let mut img: Image<u8> = Image::from_vec(5, 4, 2, true, data);
+    let expected = transform::crop(&img, 1, 1, 3, 2).unwrap();
+
+    img.crop_in_place(1, 1, 3, 2).unwrap();
+    assert_eq!(expected, img);
+
+    assert!(img.crop_in_place(1, 0, 3, 1).is_err());
+    assert!(img.crop_in_place(0, 0, 3, 3).is_err());
+    assert!(img.crop_in_place(u32::MAX, 0, 1, 1).is_err());
+    assert!(img.crop_in_place(0, u32::MAX, 1, 1).is_err());
+}
+
+#[test]
+fn image_len_test() {
+    let img: Image<u8> = Image::from_vec(3, 2, 4, true, vec![0; 24]);
+    assert_eq!(6, img.info().size_usize());
+    assert_eq!(24, img.info().full_size_usize());
+    assert_eq!(6, img.len());
+    assert!(!img.is_empty());
+
+    let empty: Image<u8> = Image::from_vec(0, 5, 3, false, Vec::new());
+    assert_eq!(0, empty.len());
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn image_clamp_test() {
+    let mut img: Image<f32> = Image::from_vec(2, 1, 2, true, vec![-3.5, 128.0, 300.0, 255.0]);
+    img.clamp(0.0, 255.0);
+    assert_eq!(&[0.0, 128.0, 255.0, 255.0], img.data());
+
+    img.clamp(10.0, 100.0);
+    assert_eq!(&[10.0, 100.0, 100.0, 100.0], img.data());
+}
+
+#[test]
+fn image_swap_rb_in_place_test() {
+    let mut img: Image<u8> = Image::from_vec(2, 1, 4, true, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    img.swap_rb_in_place().unwrap();
+    assert_eq!(&[3, 2, 1, 4, 7, 6, 5, 8], img.data());
+
+    let mut img: Image<u8> = Image::from_vec(1, 1, 3, false, vec![10, 20, 30]);
+    img.swap_rb_in_place().unwrap();
+    assert_eq!(&[30, 20, 10], img.data());
+
+    let mut gray: Image<u8> = Image::from_vec(1, 1, 2, true, vec![10, 20]);
+    assert!(gray.swap_rb_in_place().is_err());
+}
+
+#[test]
+fn image_border_test() {
+    let img: Image<u8> = Image::from_vec(4, 1, 1, false, vec![1, 2, 3, 4]);
+    assert_eq!(Border::Replicate, img.border_mode());
+    assert_eq!(vec![1, 1, 1, 2, 3], img.get_neighborhood_1d(0, 0, 5, false).to_vec());
+
+    let img = img.with_border(Border::Reflect);
+    assert_eq!(vec![3, 2, 1, 2, 3], img.get_neighborhood_1d(0, 0, 5, false).to_vec());
+    assert_eq!(vec![2, 3, 4, 3, 2], img.get_neighborhood_1d(3, 0, 5, false).to_vec());
+
+    // The border mode carries over to mapped images
+    let img = img.map_channels(|c| c * 10).with_border(Border::Wrap);
+    assert_eq!(Border::Wrap, img.map_channels(|c| c).border_mode());
+    assert_eq!(vec![30, 40, 10, 20, 30], img.get_neighborhood_1d(0, 0, 5, false).to_vec());
+    assert_eq!(vec![40, 10, 20, 40, 10, 20, 40, 10, 20], img.get_neighborhood_2d(0, 0, 3).to_vec());
+
+    // The border mode does not affect equality
+    assert_eq!(img.clone().with_border(Border::Replicate), img);
+}
+
+#[test]
+fn zip_pixels_test() {
+    let a: Image<u8> = Image::from_vec(2, 2, 2, true, vec![10, 1, 20, 2, 30, 3, 40, 4]);
+    let b: Image<f32> = Image::from_vec(2, 2, 1, false, vec![0.5, 1.0, 1.5, 2.0]);
+
+    let output: Image<f32> = imgproc_rs::image::zip_pixels(&a, &b, |p_a, p_b, p_out| {
+        p_out.push(p_a[0] as f32 * p_b[0]);
+        p_out.push(p_a[1] as f32);
+    }).unwrap();
+    assert_eq!(ImageInfo::new(2, 2, 2, true), output.info());
+    assert_eq!(&[5.0, 1.0, 20.0, 2.0, 45.0, 3.0, 80.0, 4.0], output.data());
+
+    let c: Image<f32> = Image::from_vec(4, 1, 1, false, vec![0.0; 4]);
+    match imgproc_rs::image::zip_pixels(&a, &c, |_, _, p_out: &mut Vec<u8>| p_out.push(0)) {
+        Err(imgproc_rs::error::ImgProcError::DimensionMismatch { expected, found }) => {
+            assert_eq!(ImageInfo::new(2, 2, 1, false), expected);
+            assert_eq!(c.info(), found);
+        },
+        _ => panic!("expected DimensionMismatch"),
+    }
+}
@@ -0,0 +1,267 @@
+use imgproc_rs::image::Image;
+use imgproc_rs::io;
+
+#[test]
+fn decode_webp_test() {
+    use image_webp::{ColorType, WebPEncoder};
+
+    let encode = |data: &[u8], color: ColorType| -> Vec<u8> {
+        let mut out = Vec::new();
+        WebPEncoder::new(&mut out).encode(data, 2, 2, color).unwrap();
+        out
+    };
+
+    // Lossless RGB images decode to 3 channels
+    let rgb: Vec<u8> = (0..12).map(|i| i * 20).collect();
+    let img = io::decode_webp(&encode(&rgb, ColorType::Rgb8)).unwrap();
+    assert_eq!(Image::from_vec(2, 2, 3, false, rgb), img);
+
+    // Alpha is detected
+    let rgba: Vec<u8> = (0..16).map(|i| i * 15).collect();
+    let img = io::decode_webp(&encode(&rgba, ColorType::Rgba8)).unwrap();
+    assert_eq!(Image::from_vec(2, 2, 4, true, rgba), img);
+
+    // Not a RIFF container
+    assert!(io::decode_webp(&[0, 1, 2, 3]).is_err());
+}
+
+#[test]
+fn read_tiff_test() {
+    use tiff::encoder::{colortype, TiffEncoder};
+
+    let path = std::env::temp_dir().join("imgproc_rs_read_tiff_test.tif");
+    let filename = path.to_str().unwrap();
+    {
+        let mut encoder = TiffEncoder::new(std::fs::File::create(&path).unwrap()).unwrap();
+        encoder.write_image::<colortype::RGB8>(2, 1, &[1, 2, 3, 4, 5, 6]).unwrap();
+        encoder.write_image::<colortype::Gray16>(1, 2, &[0xFFFF, 0x0100]).unwrap();
+        encoder.write_image::<colortype::RGBA8>(1, 1, &[1, 2, 3, 4]).unwrap();
+    }
+
+    let pages = io::read_tiff(filename).unwrap();
+    assert_eq!(3, pages.len());
+    assert_eq!(Image::from_vec(2, 1, 3, false, vec![1, 2, 3, 4, 5, 6]), pages[0]);
+    assert_eq!(Image::from_vec(1, 2, 1, false, vec![255, 1]), pages[1]);
+    assert_eq!(Image::from_vec(1, 1, 4, true, vec![1, 2, 3, 4]), pages[2]);
+
+    // io::read returns the first page
+    assert_eq!(pages[0], io::read(filename).unwrap());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn read_gif_test() {
+    use image::codecs::gif::GifEncoder;
+    use image::{Delay, Frame, ImageBuffer};
+
+    let path = std::env::temp_dir().join("imgproc_rs_read_gif_test.gif");
+    let filename = path.to_str().unwrap();
+    {
+        let red = ImageBuffer::from_raw(2, 1, vec![255, 0, 0, 255, 255, 0, 0, 255]).unwrap();
+        let blue = ImageBuffer::from_raw(1, 1, vec![0, 0, 255, 255]).unwrap();
+
+        let mut encoder = GifEncoder::new(std::fs::File::create(&path).unwrap());
+        encoder.encode_frames(vec![
+            Frame::from_parts(red, 0, 0, Delay::from_numer_denom_ms(100, 1)),
+            Frame::from_parts(blue, 0, 0, Delay::from_numer_denom_ms(50, 1)),
+        ]).unwrap();
+    }
+
+    let frames = io::read_gif(filename).unwrap();
+    assert_eq!(2, frames.len());
+    assert_eq!(10, frames[0].1);
+    assert_eq!(5, frames[1].1);
+
+    // The second frame only covers the left pixel, but is returned as a complete image
+    assert_eq!(Image::from_vec(2, 1, 4, true, vec![0, 0, 255, 255, 255, 0, 0, 255]), frames[1].0);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn read_cube_lut_test() {
+    let path = std::env::temp_dir().join("imgproc_rs_read_cube_lut_test.cube");
+    let filename = path.to_str().unwrap();
+
+    std::fs::write(&path, "# Comment\nTITLE \"Test\"\nLUT_3D_SIZE 2\nDOMAIN_MIN 0 0 0\nDOMAIN_MAX 1 1 1\n\n\
+        0 0 0\n1 0 0\n0 1 0\n1 1 0\n0 0 1\n1 0 1\n0 1 1\n1 1 1\n").unwrap();
+    let lut = io::read_cube_lut(filename).unwrap();
+    assert_eq!(imgproc_rs::tone::CubeLut::identity(2).unwrap(), lut);
+
+    std::fs::write(&path, "LUT_3D_SIZE 2\n0 0 0\n1 0 0\n").unwrap();
+    assert!(io::read_cube_lut(filename).is_err());
+
+    std::fs::write(&path, "LUT_1D_SIZE 2\n0 0 0\n1 1 1\n").unwrap();
+    assert!(io::read_cube_lut(filename).is_err());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn process_directory_test() {
+    use imgproc_rs::error::ImgProcError;
+    use imgproc_rs::image::BaseImage;
+
+    let in_dir = std::env::temp_dir().join("imgproc_rs_process_directory_test_in");
+    let out_dir = std::env::temp_dir().join("imgproc_rs_process_directory_test_out");
+    let _ = std::fs::remove_dir_all(&in_dir);
+    let _ = std::fs::remove_dir_all(&out_dir);
+    std::fs::create_dir_all(&in_dir).unwrap();
+
+    let img = Image::from_vec(2, 1, 1, false, vec![10, 20]);
+    io::write(&img, in_dir.join("a.png").to_str().unwrap()).unwrap();
+    io::write(&Image::from_vec(1, 1, 1, false, vec![0]), in_dir.join("b.png").to_str().unwrap()).unwrap();
+    std::fs::write(in_dir.join("notes.txt"), "not an image").unwrap();
+
+    // Fails on 1x1 images, but still processes the others
+    let result = io::process_directory(in_dir.to_str().unwrap(), out_dir.to_str().unwrap(), |img| {
+        if img.info().size() == 1 {
+            return Err(ImgProcError::InvalidArgError("image is too small".to_string()));
+        }
+        Ok(img.map_channels(|c| 255 - c))
+    });
+
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("b.png") && err.contains("image is too small"));
+    assert!(!err.contains("notes.txt"));
+
+    let output = io::read(out_dir.join("a.png").to_str().unwrap()).unwrap();
+    assert_eq!(&[245, 235], output.data());
+    assert!(!out_dir.join("b.png").exists());
+    assert!(!out_dir.join("notes.txt").exists());
+}
+
+#[test]
+fn write_jpg_opts_test() {
+    use imgproc_rs::enums::Subsampling;
+    use imgproc_rs::image::BaseImage;
+
+    let path = std::env::temp_dir().join("imgproc_rs_write_jpg_opts_test.jpg");
+    let filename = path.to_str().unwrap();
+
+    // Smooth gradients, with dimensions that are not a whole number of MCUs
+    let (width, height) = (37, 21);
+    let data = (0..(width * height)).flat_map(|i| {
+        let (x, y) = (i % width, i / width);
+        vec![(x * 6) as u8, (y * 10) as u8, (200 - x * 2 - y * 3) as u8, 255]
+    }).collect();
+    let img: Image<u8> = Image::from_vec(width, height, 4, true, data);
+    let rgb = Image::from_vec(width, height, 3, false, img.data().chunks(4).flat_map(|p| p[..3].to_vec()).collect());
+    let gray = Image::from_vec(width, height, 1, false, img.data().chunks(4).map(|p| p[0]).collect());
+
+    // Returns the SOF marker and the sampling factors of each component of a JPEG file
+    let frame = |bytes: &[u8]| {
+        let i = bytes.windows(2).position(|m| m[0] == 0xFF && (m[1] == 0xC0 || m[1] == 0xC2)).unwrap();
+        let factors: Vec<u8> = bytes[(i + 10)..].chunks(3).take(bytes[i + 9] as usize).map(|c| c[1]).collect();
+        (bytes[i + 1], factors)
+    };
+
+    // Returns the largest difference between the samples of two images
+    let max_diff = |a: &Image<u8>, b: &Image<u8>| {
+        a.data().iter().zip(b.data().iter()).map(|(&a, &b)| (a as i32 - b as i32).abs()).max().unwrap()
+    };
+
+    for &(subsampling, luma_factors) in [(Subsampling::S444, 0x11), (Subsampling::S422, 0x21), (Subsampling::S420, 0x22)].iter() {
+        for &progressive in [false, true].iter() {
+            let sof = if progressive { 0xC2 } else { 0xC0 };
+            let opts = io::JpegOptions { quality: 95, subsampling, progressive };
+
+            io::write_jpg_opts(&img, filename, &opts).unwrap();
+            assert_eq!((sof, vec![luma_factors, 0x11, 0x11]), frame(&std::fs::read(filename).unwrap()));
+            let output = io::read(filename).unwrap();
+            assert_eq!(rgb.info(), output.info());
+            assert!(max_diff(&rgb, &output) <= 8);
+
+            io::write_jpg_opts(&gray, filename, &opts).unwrap();
+            assert_eq!((sof, vec![0x11]), frame(&std::fs::read(filename).unwrap()));
+            let output = io::read(filename).unwrap();
+            assert_eq!(gray.info(), output.info());
+            assert!(max_diff(&gray, &output) <= 2);
+        }
+    }
+
+    let opts = io::JpegOptions { quality: 0, ..Default::default() };
+    assert!(io::write_jpg_opts(&img, filename, &opts).is_err());
+    let opts = io::JpegOptions { quality: 101, ..Default::default() };
+    assert!(io::write_jpg_opts(&img, filename, &opts).is_err());
+}
+
+#[test]
+fn read_write_with_meta_test() {
+    use imgproc_rs::image::BaseImage;
+
+    let path = std::env::temp_dir().join("imgproc_rs_read_write_with_meta_test.png");
+    let filename = path.to_str().unwrap();
+    let img = Image::from_vec(4, 2, 4, true, (0..32).collect());
+
+    let meta = io::ImageMeta { dpi_x: Some(300.0), dpi_y: Some(150.0), color_type: image::ColorType::Rgba8 };
+    io::write_with_meta(&img, filename, &meta).unwrap();
+    assert_eq!(img, io::read(filename).unwrap());
+
+    let (output, output_meta) = io::read_with_meta(filename).unwrap();
+    assert_eq!(img, output);
+    assert_eq!(image::ColorType::Rgba8, output_meta.color_type);
+    assert!((output_meta.dpi_x.unwrap() - 300.0).abs() < 0.05);
+    assert!((output_meta.dpi_y.unwrap() - 150.0).abs() < 0.05);
+
+    let meta = io::ImageMeta { dpi_x: None, ..meta };
+    io::write_with_meta(&img, filename, &meta).unwrap();
+    let (_, output_meta) = io::read_with_meta(filename).unwrap();
+    assert_eq!(None, output_meta.dpi_x);
+    assert_eq!(None, output_meta.dpi_y);
+
+    // Set the JFIF density of a JPEG file to 72 x 96 dots per centimeter
+    let path = std::env::temp_dir().join("imgproc_rs_read_write_with_meta_test.jpg");
+    let filename = path.to_str().unwrap();
+    let img = Image::from_vec(8, 8, 1, false, vec![50; 64]);
+    io::write(&img, filename).unwrap();
+    let mut bytes = std::fs::read(filename).unwrap();
+    assert_eq!(b"JFIF\0", &bytes[6..11]);
+    bytes[13..18].copy_from_slice(&[2, 0, 72, 0, 96]);
+    std::fs::write(filename, bytes).unwrap();
+
+    let (output, output_meta) = io::read_with_meta(filename).unwrap();
+    assert_eq!(img.info(), output.info());
+    assert_eq!(image::ColorType::L8, output_meta.color_type);
+    assert!((output_meta.dpi_x.unwrap() - 182.88).abs() < 0.01);
+    assert!((output_meta.dpi_y.unwrap() - 243.84).abs() < 0.01);
+}
+
+#[test]
+fn rotate_jpeg_lossless_test() {
+    use imgproc_rs::image::BaseImage;
+
+    let in_path = std::env::temp_dir().join("imgproc_rs_rotate_jpeg_lossless_test_in.jpg");
+    let out_path = std::env::temp_dir().join("imgproc_rs_rotate_jpeg_lossless_test_out.jpg");
+    let (in_file, out_file) = (in_path.to_str().unwrap(), out_path.to_str().unwrap());
+
+    // The bottom 4 rows are a partial MCU
+    let img = Image::from_vec(24, 12, 3, false, (0..864).map(|i| ((i * 7) % 251) as u8).collect());
+    io::write(&img, in_file).unwrap();
+    let decoded = io::read(in_file).unwrap();
+
+    // The pixels match a rotation of the decoded input, up to rounding in the inverse DCT, and
+    // the partial MCU that would become the left edge is trimmed
+    io::rotate_jpeg_lossless(in_file, out_file, 1).unwrap();
+    let rotated = io::read(out_file).unwrap();
+    assert_eq!((8, 24), rotated.info().wh());
+    for y in 0..24 {
+        for x in 0..8 {
+            for (a, b) in rotated.get_pixel(x, y).iter().zip(decoded.get_pixel(y, 7 - x)) {
+                assert!((*a as i32 - *b as i32).abs() <= 4);
+            }
+        }
+    }
+
+    // Rotating back and forth restores the coefficients exactly
+    io::rotate_jpeg_lossless(out_file, out_file, 3).unwrap();
+    assert_eq!(&decoded.data()[..(24 * 8 * 3)], io::read(out_file).unwrap().data());
+    io::rotate_jpeg_lossless(in_file, out_file, 6).unwrap();
+    io::rotate_jpeg_lossless(out_file, out_file, 2).unwrap();
+    assert_eq!(&decoded.data()[..(24 * 8 * 3)], io::read(out_file).unwrap().data());
+
+    let png_path = std::env::temp_dir().join("imgproc_rs_rotate_jpeg_lossless_test.png");
+    io::write(&img, png_path.to_str().unwrap()).unwrap();
+    assert!(io::rotate_jpeg_lossless(png_path.to_str().unwrap(), out_file, 1).is_err());
+}
@@ -5,6 +5,7 @@ mod common;
 use common::setup;
 use imgproc_rs::{morphology, colorspace};
 use imgproc_rs::io::write;
+use imgproc_rs::image::{Image, BaseImage};
 
 use std::time::SystemTime;
 
@@ -74,4 +75,52 @@ fn gradient_test() {
     println!("processing: {}", now.elapsed().unwrap().as_millis());
 
     write(&proc, "images/tests/morphology/gradient.png").unwrap();
-}
\ No newline at end of file
+}
+
+#[test]
+fn skeletonize_test() {
+    // A 3-pixel-thick horizontal bar
+    let mut data = vec![0; 9 * 5];
+    for y in 1..4 {
+        for x in 1..8 {
+            data[y * 9 + x] = 255;
+        }
+    }
+    let img = Image::from_vec(9, 5, 1, false, data);
+
+    let proc = morphology::skeletonize(&img).unwrap();
+
+    // Skeleton is non-empty, contained in the input, and at most 1 pixel thick
+    assert!(proc.data().contains(&255));
+    for x in 0..9 {
+        let mut count = 0;
+        for y in 0..5 {
+            if proc.get_pixel(x, y)[0] == 255 {
+                assert_eq!(255, img.get_pixel(x, y)[0]);
+                count += 1;
+            }
+        }
+        assert!(count <= 1);
+    }
+}
+
+#[test]
+fn find_contours_test() {
+    // A 3x3 square and a single isolated pixel
+    let mut data = vec![0; 6 * 5];
+    for y in 1..4 {
+        for x in 1..4 {
+            data[y * 6 + x] = 255;
+        }
+    }
+    data[6 + 5] = 255;
+    let img = Image::from_vec(6, 5, 1, false, data);
+
+    let contours = morphology::find_contours(&img).unwrap();
+    assert_eq!(2, contours.len());
+    assert_eq!(vec![(1, 1), (2, 1), (3, 1), (3, 2), (3, 3), (2, 3), (1, 3), (1, 2)], contours[0]);
+    assert_eq!(vec![(5, 1)], contours[1]);
+
+    let img = Image::from_vec(2, 1, 1, false, vec![0, 128]);
+    assert!(morphology::find_contours(&img).is_err());
+}
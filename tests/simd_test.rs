@@ -0,0 +1,32 @@
+#![cfg(feature = "simd")]
+
+use imgproc_rs::image::{BaseImage, Image};
+use imgproc_rs::simd;
+
+#[test]
+fn adds_rgb_256_u8_test() {
+    // Large enough to exercise both the vectorized and the remaining bytes
+    for &(channels, alpha) in [(3, false), (4, true)].iter() {
+        let data: Vec<u8> = (0..(37 * channels as u32)).map(|i| ((i * 37) % 256) as u8).collect();
+        let img = Image::from_vec(37, 1, channels, alpha, data);
+        let deltas = [100, -50, 300];
+
+        let output = simd::adds_rgb_256_u8(&img, deltas);
+        assert_eq!(img.info(), output.info());
+        for (p, p_out) in img.data().chunks(channels as usize).zip(output.data().chunks(channels as usize)) {
+            assert_eq!((p[0] as i16 + 100).min(255) as u8, p_out[0]);
+            assert_eq!((p[1] as i16 - 50).max(0) as u8, p_out[1]);
+            assert_eq!(255, p_out[2]);
+            if alpha {
+                assert_eq!(p[3], p_out[3]);
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn adds_rgb_256_u8_gray_test() {
+    let img: Image<u8> = Image::from_vec(2, 1, 2, true, vec![0; 4]);
+    simd::adds_rgb_256_u8(&img, [1, 1, 1]);
+}
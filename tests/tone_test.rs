@@ -5,6 +5,7 @@ mod common;
 use common::setup;
 use imgproc_rs::tone;
 use imgproc_rs::io::write;
+use imgproc_rs::image::Image;
 
 use std::time::SystemTime;
 use imgproc_rs::enums::White;
@@ -75,3 +76,162 @@ fn histogram_equalization_test() {
 
     write(&proc, "images/tests/tone/histogram.png").unwrap();
 }
+
+#[test]
+fn color_balance_test() {
+    let img: Image<u8> = Image::from_slice(2, 1, 4, true, &[10, 100, 200, 50, 250, 0, 20, 60]);
+
+    let output = tone::color_balance(&img, &[2.0, 1.0, 0.5], &[0, -10, 5]).unwrap();
+    assert_eq!(&[20, 90, 105, 50, 255, 0, 15, 60], output.data());
+
+    assert!(tone::color_balance(&img, &[1.0, 1.0], &[0, 0]).is_err());
+    assert!(tone::color_balance(&img, &[1.0, -1.0, 1.0], &[0, 0, 0]).is_err());
+}
+
+#[test]
+fn auto_exposure_test() {
+    use imgproc_rs::colorspace;
+    use imgproc_rs::image::BaseImage;
+
+    let data = (0..16).flat_map(|i| vec![20 + 4 * i as u8, 30 + 3 * i as u8, 10 + 5 * i as u8, 200]).collect();
+    let img: Image<u8> = Image::from_vec(4, 4, 4, true, data);
+
+    let output = tone::auto_exposure(&img, 0.18);
+    assert_eq!(img.info(), output.info());
+    assert!(output.data().chunks(4).all(|p| p[3] == 200));
+
+    let linear = colorspace::linearize_srgb_f32(&output);
+    let mean = linear.data().chunks(4)
+        .map(|p| 0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2])
+        .sum::<f32>() / 16.0;
+    assert!((mean - 0.18).abs() < 0.005);
+
+    // Channels are clipped
+    let output = tone::auto_exposure(&img, 1.0);
+    assert_eq!(&[255, 255, 255, 200], output.get_pixel(3, 3));
+
+    let black: Image<u8> = Image::from_vec(2, 2, 1, false, vec![0; 4]);
+    assert_eq!(black, tone::auto_exposure(&black, 0.5));
+}
+
+#[test]
+#[should_panic]
+fn auto_exposure_invalid_test() {
+    let img: Image<u8> = Image::from_vec(2, 2, 1, false, vec![10; 4]);
+    tone::auto_exposure(&img, 1.5);
+}
+
+#[test]
+fn retinex_msr_test() {
+    // A checkerboard under illumination that brightens from left to right
+    let data = (0..(64 * 16)).map(|i| {
+        let (x, y) = (i % 64, i / 64);
+        let reflectance = if (x / 2 + y / 2) % 2 == 0 { 0.5 } else { 1.0 };
+        (reflectance * (40.0 + 3.0 * x as f32)) as u8
+    }).collect();
+    let img: Image<u8> = Image::from_vec(64, 16, 1, false, data);
+    let mean = |img: &Image<u8>, x_0: usize| -> f32 {
+        img.data().chunks(64).flat_map(|row| row[x_0..(x_0 + 8)].iter()).map(|&v| v as f32).sum::<f32>() / 128.0
+    };
+
+    let output = tone::retinex_msr(&img, &[2.0, 4.0]).unwrap();
+    assert_eq!(img.data().len(), output.data().len());
+    assert_eq!(0, *output.data().iter().min().unwrap());
+    assert_eq!(255, *output.data().iter().max().unwrap());
+    assert!((mean(&img, 8) - mean(&img, 48)).abs() > 50.0);
+    assert!((mean(&output, 8) - mean(&output, 48)).abs() < 20.0);
+
+    // Flat images become mid-gray, and alpha is unchanged
+    let flat: Image<u8> = Image::from_vec(4, 4, 2, true, [90, 30].repeat(16));
+    let output = tone::retinex_msr(&flat, &[1.0]).unwrap();
+    assert_eq!([128, 30].repeat(16), output.data());
+
+    assert!(tone::retinex_msr(&img, &[]).is_err());
+    assert!(tone::retinex_msr(&img, &[1.0, 0.0]).is_err());
+}
+
+#[test]
+fn apply_colormap_test() {
+    use imgproc_rs::enums::Colormap;
+
+    let img: Image<u8> = Image::from_slice(3, 1, 1, false, &[0, 128, 255]);
+
+    let gray = tone::apply_colormap(&img, Colormap::Grayscale).unwrap();
+    assert_eq!(&[0, 0, 0, 128, 128, 128, 255, 255, 255], gray.data());
+
+    let hot = tone::apply_colormap(&img, Colormap::Hot).unwrap();
+    assert_eq!(&[0, 0, 0], &hot.data()[..3]);
+    assert_eq!(&[255, 255, 255], &hot.data()[6..]);
+
+    let viridis = tone::apply_colormap(&img, Colormap::Viridis).unwrap();
+    assert_eq!(&[68, 1, 84], &viridis.data()[..3]);
+    assert_eq!(&[253, 231, 37], &viridis.data()[6..]);
+
+    let jet = tone::apply_colormap(&img, Colormap::Jet).unwrap();
+    assert_eq!(&[0, 0, 128], &jet.data()[..3]);
+    assert_eq!(&[128, 0, 0], &jet.data()[6..]);
+
+    let rgb: Image<u8> = Image::from_slice(1, 1, 3, false, &[0, 0, 0]);
+    assert!(tone::apply_colormap(&rgb, Colormap::Jet).is_err());
+}
+
+#[test]
+fn tone_mapping_test() {
+    let img: Image<f32> = Image::from_slice(3, 1, 3, false,
+                                            &[0.0, 0.0, 0.0, 0.05, 0.1, 0.2, 40.0, 20.0, 10.0]);
+
+    for output in [tone::reinhard(&img, 0.18), tone::aces_filmic(&img)].iter() {
+        assert!(output.data().iter().all(|&c| (0.0..=1.0).contains(&c)));
+        assert_eq!(&[0.0, 0.0, 0.0], &output.data()[..3]);
+
+        // Color ratios are preserved for unclipped pixels
+        let p = &output.data()[3..6];
+        assert!((p[1] / p[0] - 2.0).abs() < 1e-4);
+        assert!((p[2] / p[0] - 4.0).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn recover_highlights_shadows_test() {
+    let img: Image<f32> = Image::from_slice(6, 1, 1, false, &[0.0, 0.1, 0.25, 0.6, 0.9, 1.5]);
+    let close = |a: &[f32], b: &[f32]| a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 1e-4);
+
+    let output = tone::recover_highlights(&img, 1.0).unwrap();
+    assert!(close(&[0.0, 0.1, 0.25, 0.5833, 0.7222, 0.8333], output.data()));
+    let output = tone::recover_highlights(&img, 0.0).unwrap();
+    assert!(close(&[0.0, 0.1, 0.25, 0.6, 0.9, 1.0], output.data()));
+
+    let output = tone::recover_shadows(&img, 1.0).unwrap();
+    assert!(close(&[0.0, 0.164, 0.3125, 0.6, 0.9, 1.0], output.data()));
+
+    // Color ratios are preserved
+    let rgb: Image<f32> = Image::from_slice(1, 1, 3, false, &[0.05, 0.1, 0.2]);
+    for output in [tone::recover_shadows(&rgb, 0.5).unwrap(), tone::recover_highlights(&rgb, 0.5).unwrap()].iter() {
+        let p = output.data();
+        assert!((p[1] / p[0] - 2.0).abs() < 1e-4);
+        assert!((p[2] / p[0] - 4.0).abs() < 1e-4);
+    }
+    assert!(tone::recover_shadows(&rgb, 0.5).unwrap().data()[0] > 0.05);
+
+    assert!(tone::recover_highlights(&img, 1.5).is_err());
+    assert!(tone::recover_shadows(&img, -0.5).is_err());
+}
+
+#[test]
+fn apply_cube_lut_test() {
+    let img: Image<u8> = Image::from_vec(2, 1, 4, true, vec![0, 64, 200, 10, 255, 128, 33, 20]);
+
+    // The identity LUT leaves the image unchanged
+    let identity = tone::CubeLut::identity(5).unwrap();
+    assert_eq!(img, tone::apply_cube_lut(&img, &identity).unwrap());
+
+    // An inverting LUT is linear, so trilinear interpolation is exact
+    let mut invert = identity.clone();
+    invert.data.iter_mut().for_each(|rgb| rgb.iter_mut().for_each(|c| *c = 1.0 - *c));
+    let output = tone::apply_cube_lut(&img, &invert).unwrap();
+    assert_eq!(&[255, 191, 55, 10, 0, 127, 222, 20], output.data());
+
+    let gray: Image<u8> = Image::from_vec(1, 1, 1, false, vec![0]);
+    assert!(tone::apply_cube_lut(&gray, &identity).is_err());
+    assert!(tone::CubeLut::new(2, vec![[0.0; 3]; 7]).is_err());
+}
@@ -4,11 +4,11 @@ mod common;
 
 use common::setup;
 use imgproc_rs::transform;
-use imgproc_rs::image::{BaseImage, Image};
+use imgproc_rs::image::{BaseImage, Image, ImageInfo};
 use imgproc_rs::io::write;
 
 use std::time::SystemTime;
-use imgproc_rs::enums::{Scale, Refl};
+use imgproc_rs::enums::{Anchor, BlendMode, Scale, Refl};
 
 const PATH: &str = "images/beach.jpg";
 
@@ -174,3 +174,562 @@ fn shear() {
     write(&output_pn.into(), "images/tests/transform/shear_py.png").unwrap();
     write(&output_nn.into(), "images/tests/transform/shear_ny.png").unwrap();
 }
+
+#[test]
+fn overlay_expand_test() {
+    let back: Image<u8> = Image::from_slice(2, 2, 1, false, &[1, 2, 3, 4]);
+    let front: Image<u8> = Image::from_slice(2, 1, 1, false, &[8, 9]);
+
+    let output = transform::overlay_expand(&back, &front, 1, 1, &[7]).unwrap();
+    assert_eq!((3, 2), output.info().wh());
+    assert_eq!(&[1, 2, 7, 3, 8, 9], output.data());
+}
+
+#[test]
+fn superimpose_linear_test() {
+    let back: Image<u8> = Image::from_vec(2, 1, 2, true, vec![0, 255, 100, 255]);
+    let front: Image<u8> = Image::from_vec(1, 1, 2, true, vec![255, 255]);
+
+    // A 50% blend of black and white is brighter than the nonlinear midpoint
+    let output = transform::superimpose_linear(&back, &front, 0, 0, 0.5).unwrap();
+    assert_eq!(back.info(), output.info());
+    assert!((180..=190).contains(&output[0][0]));
+    assert_eq!(255, output[0][1]);
+    assert_eq!(&[100, 255], output.get_pixel(1, 0));
+
+    assert!(transform::superimpose_linear(&back, &front, 0, 0, 1.5).is_err());
+}
+
+#[test]
+fn blend_weighted_test() {
+    let a: Image<u8> = Image::from_vec(4, 1, 3, false, vec![0; 12]);
+    let b: Image<u8> = Image::from_vec(4, 1, 3, false, vec![255; 12]);
+    let weights: Image<f32> = Image::from_vec(4, 1, 1, false, vec![0.0, 0.5, 1.0, 2.0]);
+
+    let output = transform::blend_weighted(&a, &b, &weights).unwrap();
+    assert_eq!(a.info(), output.info());
+    assert_eq!(&[0, 0, 0], output.get_pixel(0, 0));
+    assert!((180..=190).contains(&output.get_pixel(1, 0)[0]));
+    assert_eq!(&[255, 255, 255], output.get_pixel(2, 0));
+    assert_eq!(&[255, 255, 255], output.get_pixel(3, 0));
+
+    let bad_weights: Image<f32> = Image::from_vec(2, 2, 1, false, vec![0.0; 4]);
+    assert!(transform::blend_weighted(&a, &b, &bad_weights).is_err());
+    let bad_weights: Image<f32> = Image::from_vec(4, 1, 2, false, vec![0.0; 8]);
+    assert!(transform::blend_weighted(&a, &b, &bad_weights).is_err());
+    let gray: Image<u8> = Image::from_vec(4, 1, 1, false, vec![0; 4]);
+    assert!(transform::blend_weighted(&a, &gray, &weights).is_err());
+}
+
+#[test]
+fn seamless_clone_test() {
+    // A horizontal ramp with a constant alpha channel
+    let data = (0..64).flat_map(|i| vec![(i % 8) as f32 * 10.0, 255.0]).collect();
+    let dst: Image<f32> = Image::from_vec(8, 8, 2, true, data);
+    let mut mask: Image<u8> = Image::blank(ImageInfo::new(4, 4, 1, false));
+    for y in 1..3 {
+        for x in 1..3 {
+            mask.set_pixel(x, y, &[255]);
+        }
+    }
+
+    // A brighter copy of the ramp keeps its gradients, but is shifted to match the boundary
+    let src = transform::crop(&dst, 2, 3, 4, 4).unwrap().map_channels(|c| c + 100.0);
+    let output = transform::seamless_clone(&src, &dst, &mask, 2, 3).unwrap();
+    assert_eq!(dst.info(), output.info());
+    assert!(output.data().iter().zip(dst.data()).all(|(a, b)| (a - b).abs() < 0.05));
+
+    // A flat source takes on the smooth interpolation of the boundary, and pixels outside of the
+    // mask are unchanged
+    let src = Image::from_vec(4, 4, 2, true, vec![0.0; 32]);
+    let output = transform::seamless_clone(&src, &dst, &mask, 2, 3).unwrap();
+    assert_eq!(dst.get_pixel(2, 3), output.get_pixel(2, 3));
+    let (a, b) = (output.get_pixel(3, 4)[0], output.get_pixel(4, 4)[0]);
+    assert!(a > 20.0 && a < b && b < 50.0);
+    assert!(output.data().chunks(2).all(|p| p[1] == 255.0));
+
+    // A full mask still blends towards the `dst` pixels just outside of the pasted rectangle
+    let flat_dst: Image<f32> = Image::from_vec(4, 1, 1, false, vec![100.0; 4]);
+    let flat_src = Image::from_vec(2, 1, 1, false, vec![0.0; 2]);
+    let full_mask = Image::from_vec(2, 1, 1, false, vec![255; 2]);
+    let output = transform::seamless_clone(&flat_src, &flat_dst, &full_mask, 1, 0).unwrap();
+    assert!(output.data().iter().all(|val| (val - 100.0).abs() < 0.05));
+
+    assert!(transform::seamless_clone(&src, &dst, &Image::blank(ImageInfo::new(3, 4, 1, false)), 0, 0).is_err());
+    let rgb = Image::from_vec(4, 4, 3, false, vec![0.0; 48]);
+    assert!(transform::seamless_clone(&rgb, &dst, &mask, 0, 0).is_err());
+}
+
+#[test]
+fn superimpose_alpha_test() {
+    let back: Image<f32> = Image::from_slice(2, 1, 1, false, &[2.0, 4.0]);
+    let front: Image<f32> = Image::from_slice(1, 1, 1, false, &[6.0]);
+
+    let output = transform::superimpose(&back, &front, 1, 0, 0.25).unwrap();
+    assert_eq!(&[2.0, 5.5], output.data());
+    assert_eq!(&[2.0, 6.0], transform::superimpose(&back, &front, 1, 0, 0.0).unwrap().data());
+    assert_eq!(back, transform::superimpose(&back, &front, 1, 0, 1.0).unwrap());
+
+    assert!(transform::superimpose(&back, &front, 1, 0, 1.5).is_err());
+    assert!(transform::superimpose(&back, &front, 1, 0, -0.5).is_err());
+}
+
+#[test]
+fn superimpose_expand_test() {
+    let back: Image<f32> = Image::from_slice(2, 1, 1, false, &[2.0, 4.0]);
+    let front: Image<f32> = Image::from_slice(2, 2, 1, false, &[6.0, 8.0, 10.0, 12.0]);
+
+    let output = transform::superimpose_expand(&back, &front, 1, 0, 0.5, &[0.0]).unwrap();
+    assert_eq!((3, 2), output.info().wh());
+    assert_eq!(&[2.0, 5.0, 4.0, 0.0, 5.0, 6.0], output.data());
+}
+
+#[test]
+fn scale_downscale_antialias_test() {
+    // Alternating black and white columns, which alias badly when sampled at a lower rate
+    let data = (0..(64 * 4)).map(|i| if i % 2 == 0 { 0.0 } else { 255.0 }).collect();
+    let img: Image<f32> = Image::from_vec(64, 4, 1, false, data);
+    // The largest deviation from mid-gray, away from the left and right edges
+    let spread = |output: &Image<f32>| -> f32 {
+        output.data().chunks(16).flat_map(|row| row[2..14].iter())
+            .map(|val| (val - 127.5).abs()).fold(0.0, f32::max)
+    };
+
+    let aliased = transform::scale(&img, 0.25, 1.0, Scale::NearestNeighbor).unwrap();
+    assert!(spread(&aliased) > 100.0);
+
+    for &method in [Scale::Bicubic, Scale::Lanczos].iter() {
+        let output = transform::scale(&img, 0.25, 1.0, method).unwrap();
+        assert_eq!((16, 4), output.info().wh());
+        assert!(spread(&output) < 1.0);
+    }
+}
+
+#[test]
+fn scale_cubic_test() {
+    let data = (0..20).map(|i| (i * i % 7) as f32).collect();
+    let img: Image<f32> = Image::from_vec(5, 4, 1, false, data);
+
+    // Catmull-Rom interpolates, so it passes the input through unchanged at a factor of 1
+    let output = transform::scale(&img, 1.0, 1.0, Scale::CatmullRom).unwrap();
+    for (a, b) in img.data().iter().zip(output.data()) {
+        assert!((a - b).abs() < 1e-4);
+    }
+
+    // Both kernels preserve constant images, when upscaling and downscaling
+    let flat: Image<f32> = Image::from_vec(6, 6, 1, false, vec![50.0; 36]);
+    for &method in [Scale::Mitchell, Scale::CatmullRom].iter() {
+        for &factor in [2.0, 0.5].iter() {
+            let output = transform::scale(&flat, factor, factor, method).unwrap();
+            assert_eq!(((6.0 * factor) as u32, (6.0 * factor) as u32), output.info().wh());
+            assert!(output.data().iter().all(|val| (val - 50.0).abs() < 1e-3));
+        }
+    }
+}
+
+#[test]
+fn scale_kernel_test() {
+    let triangle = |d: f32| (1.0 - d.abs()).max(0.0);
+    let img: Image<f32> = Image::from_slice(4, 2, 1, false,
+                                            &[1.0, 3.0, 5.0, 7.0,
+                                              3.0, 5.0, 7.0, 9.0]);
+
+    let output = transform::scale_kernel(&img, 1.0, 1.0, triangle, 1.0).unwrap();
+    assert_eq!(img, output);
+
+    // Constant images stay constant, since the weights are normalized
+    let flat: Image<f32> = Image::from_vec(5, 3, 2, false, vec![10.0; 30]);
+    let output = transform::scale_kernel(&flat, 1.6, 0.7, triangle, 1.0).unwrap();
+    assert_eq!((8, 2), output.info().wh());
+    assert!(output.data().iter().all(|val| (val - 10.0).abs() < 1e-4));
+
+    // The kernel is widened when downscaling
+    let data = (0..(64 * 4)).map(|i| if i % 2 == 0 { 0.0 } else { 255.0 }).collect();
+    let stripes: Image<f32> = Image::from_vec(64, 4, 1, false, data);
+    let output = transform::scale_kernel(&stripes, 0.25, 1.0, triangle, 1.0).unwrap();
+    assert!(output.data().chunks(16).flat_map(|row| row[2..14].iter()).all(|val| (val - 127.5).abs() < 1.0));
+
+    assert!(transform::scale_kernel(&img, 2.0, 2.0, triangle, 0.0).is_err());
+    assert!(transform::scale_kernel(&img, -1.0, 2.0, triangle, 1.0).is_err());
+}
+
+#[test]
+fn scale_area_test() {
+    let img: Image<f32> = Image::from_slice(4, 2, 1, false,
+                                            &[1.0, 3.0, 5.0, 7.0,
+                                              3.0, 5.0, 7.0, 9.0]);
+
+    let output = transform::scale_area(&img, 0.5, 0.5).unwrap();
+    assert_eq!((2, 1), output.info().wh());
+    assert_eq!(&[3.0, 7.0], output.data());
+
+    // Each output pixel covers 2.5 x 2.5 input pixels, so the input pixels at index 2 along each
+    // axis are split between two output pixels. Along each axis, the weighted averages of the
+    // positions covered are (0 + 1 + 2 * 0.5) / 2.5 = 0.8 and (2 * 0.5 + 3 + 4) / 2.5 = 3.2
+    let data = (0..25).map(|i| (i % 5) as f32 + (i / 5) as f32 * 10.0).collect();
+    let img: Image<f32> = Image::from_vec(5, 5, 1, false, data);
+    let output = transform::scale_area(&img, 0.4, 0.4).unwrap();
+    assert_eq!((2, 2), output.info().wh());
+    for (val, expected) in output.data().iter().zip([8.8, 11.2, 32.8, 35.2].iter()) {
+        assert!((val - expected).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn chromatic_aberration_test() {
+    let mut data = Vec::new();
+    for i in 0..25 {
+        data.extend_from_slice(&[(i * 10) as u8, (i * 5) as u8, (250 - i * 10) as u8]);
+    }
+    let img: Image<u8> = Image::from_vec(5, 5, 3, false, data);
+
+    // No shift leaves the image unchanged
+    assert_eq!(img, transform::chromatic_aberration(&img, 0.0).unwrap());
+
+    let output = transform::chromatic_aberration(&img, 2.0).unwrap();
+    assert_eq!(img.get_pixel(2, 2), output.get_pixel(2, 2));
+    for i in 0..25 {
+        assert_eq!(img[i][1], output[i][1]);
+    }
+    assert_ne!(img.get_pixel(0, 0)[0], output.get_pixel(0, 0)[0]);
+
+    let gray: Image<u8> = Image::from_slice(1, 1, 1, false, &[0]);
+    assert!(transform::chromatic_aberration(&gray, 1.0).is_err());
+}
+
+#[test]
+fn pixelate_test() {
+    let img: Image<u8> = Image::from_vec(3, 3, 1, false,
+                                         vec![255, 255, 10, 255, 254, 20, 0, 1, 30]);
+    let output = transform::pixelate(&img, 2).unwrap();
+    assert_eq!(vec![254, 254, 15, 254, 254, 15, 0, 0, 30], output.data().to_vec());
+
+    let img_f32: Image<f32> = Image::from_vec(2, 1, 1, false, vec![1.0, 2.0]);
+    let output = transform::pixelate(&img_f32, 4).unwrap();
+    assert_eq!(vec![1.5, 1.5], output.data().to_vec());
+
+    // Blocks with more pixels than a u8 can count
+    let wide: Image<u8> = Image::from_vec(300, 2, 1, false,
+                                          (0..600).map(|i| if i % 300 < 100 { 255 } else { 1 }).collect());
+    let output = transform::pixelate(&wide, 300).unwrap();
+    assert!(output.data().iter().all(|&val| val == 85));
+
+    let signed: Image<i16> = Image::from_vec(3, 1, 1, false, vec![-32768, -32768, -32767]);
+    assert_eq!(vec![-32767; 3], transform::pixelate(&signed, 3).unwrap().data().to_vec());
+
+    assert_eq!(img, transform::pixelate(&img, 1).unwrap());
+    assert!(transform::pixelate(&img, 0).is_err());
+}
+
+#[test]
+fn swirl_test() {
+    let data: Vec<f32> = (0..49).map(|i| i as f32).collect();
+    let img: Image<f32> = Image::from_vec(7, 7, 1, false, data);
+
+    assert_eq!(img, transform::swirl(&img, 0.0, 3.0).unwrap());
+
+    // A half turn at the center maps each pixel near the center to its point reflection
+    let output = transform::swirl(&img, std::f32::consts::PI, 100.0).unwrap();
+    assert_eq!(img.get_pixel(3, 3), output.get_pixel(3, 3));
+    assert!((output.get_pixel(3, 2)[0] - img.get_pixel(3, 4)[0]).abs() < 1.0);
+
+    // Pixels beyond the radius are unchanged
+    let output = transform::swirl(&img, 1.0, 2.0).unwrap();
+    assert_eq!(img.get_pixel(0, 0), output.get_pixel(0, 0));
+    assert_ne!(img.get_pixel(3, 2), output.get_pixel(3, 2));
+
+    assert!(transform::swirl(&img, 1.0, 0.0).is_err());
+}
+
+#[test]
+fn lens_distortion_test() {
+    let data: Vec<f32> = (0..25).map(|i| i as f32 + 1.0).collect();
+    let img: Image<f32> = Image::from_vec(5, 5, 1, false, data);
+
+    assert_eq!(img, transform::lens_distortion(&img, 0.0, 0.0).unwrap());
+
+    // The center is fixed, and the corners map outside of the image
+    let output = transform::lens_distortion(&img, 0.5, 0.0).unwrap();
+    assert_eq!(img.get_pixel(2, 2), output.get_pixel(2, 2));
+    assert_eq!(&[0.0], output.get_pixel(0, 0));
+
+    // Pincushion correction pulls the corners inward
+    let output = transform::lens_distortion(&img, -0.5, 0.0).unwrap();
+    assert!(output.get_pixel(0, 0)[0] > img.get_pixel(0, 0)[0]);
+}
+
+#[test]
+fn remap_test() {
+    let data: Vec<f32> = (0..24).map(|i| i as f32).collect();
+    let img: Image<f32> = Image::from_vec(4, 3, 2, true, data);
+
+    // Identity maps reproduce the input
+    let map_x = Image::from_vec(4, 3, 1, false, (0..12).map(|i| (i % 4) as f32).collect());
+    let map_y = Image::from_vec(4, 3, 1, false, (0..12).map(|i| (i / 4) as f32).collect());
+    for &method in [Scale::NearestNeighbor, Scale::Bilinear, Scale::Lanczos].iter() {
+        let output = transform::remap(&img, &map_x, &map_y, method).unwrap();
+        assert!(output.data().iter().zip(img.data()).all(|(a, b)| (a - b).abs() < 1e-4));
+    }
+
+    // A 2x1 output sampled halfway between pixels, and beyond the right edge
+    let map_x = Image::from_vec(2, 1, 1, false, vec![0.5, 7.0]);
+    let map_y = Image::from_vec(2, 1, 1, false, vec![1.0, 0.0]);
+    let output = transform::remap(&img, &map_x, &map_y, Scale::Bilinear).unwrap();
+    assert_eq!(ImageInfo::new(2, 1, 2, true), output.info());
+    assert_eq!(&[9.0, 10.0, 6.0, 7.0], output.data());
+
+    let bicubic = transform::remap(&img, &map_x, &map_y, Scale::Bicubic).unwrap();
+    assert!((bicubic.get_pixel(0, 0)[0] - 9.0).abs() < 1.0);
+
+    let bad_map = Image::from_vec(2, 1, 2, false, vec![0.0; 4]);
+    assert!(transform::remap(&img, &bad_map, &map_y, Scale::Bilinear).is_err());
+    let bad_map = Image::from_vec(1, 2, 1, false, vec![0.0; 2]);
+    assert!(transform::remap(&img, &map_x, &bad_map, Scale::Bilinear).is_err());
+}
+
+#[test]
+fn rotate_onto_test() {
+    let src: Image<f32> = Image::from_vec(3, 1, 1, false, vec![1.0, 2.0, 3.0]);
+    let mut dst: Image<f32> = Image::from_vec(5, 5, 1, false, vec![9.0; 25]);
+
+    // A quarter turn counterclockwise stands the row up, with its right end at the top
+    transform::rotate_onto(&src, &mut dst, 3.0, 2.0, 90.0).unwrap();
+    assert_eq!(&[3.0], dst.get_pixel(3, 1));
+    assert_eq!(&[2.0], dst.get_pixel(3, 2));
+    assert_eq!(&[1.0], dst.get_pixel(3, 3));
+    assert_eq!(22, dst.data().iter().filter(|&&c| c == 9.0).count());
+
+    // Placements can be accumulated, and may be clipped by the edges of `dst`
+    transform::rotate_onto(&src, &mut dst, 0.0, 0.0, 0.0).unwrap();
+    assert_eq!(&[2.0, 3.0], &dst.data()[..2]);
+    assert_eq!(&[3.0], dst.get_pixel(3, 1));
+
+    let rgb: Image<f32> = Image::from_vec(1, 1, 3, false, vec![0.0; 3]);
+    assert!(transform::rotate_onto(&rgb, &mut dst, 0.0, 0.0, 0.0).is_err());
+}
+
+#[test]
+fn rotate_in_place_test() {
+    let data: Vec<f32> = (1..9).map(|i| i as f32).collect();
+    let img: Image<f32> = Image::from_vec(4, 2, 1, false, data);
+
+    let output = transform::rotate_in_place(&img, 180.0, &[0.0]).unwrap();
+    assert_eq!(img.info(), output.info());
+    for (a, b) in output.data().iter().zip((1..9).rev()) {
+        assert!((a - b as f32).abs() < 1e-4);
+    }
+
+    // A quarter turn of a non-square image leaves the corners uncovered
+    let output = transform::rotate_in_place(&img, 90.0, &[-1.0]).unwrap();
+    assert_eq!(&[-1.0], output.get_pixel(0, 0));
+    assert!((output.get_pixel(1, 0)[0] - 3.0).abs() < 1e-4);
+
+    assert!(transform::rotate_in_place(&img, 90.0, &[0.0, 0.0]).is_err());
+}
+
+#[test]
+fn crop_test() {
+    let data: Vec<u8> = (0..12).collect();
+    let img: Image<u8> = Image::from_vec(4, 3, 1, false, data);
+
+    // Crops touching the right and bottom edges are valid
+    assert_eq!(img, transform::crop(&img, 0, 0, 4, 3).unwrap());
+    assert_eq!(&[6, 7, 10, 11], transform::crop(&img, 2, 1, 2, 2).unwrap().data());
+
+    assert!(transform::crop(&img, 1, 0, 4, 3).is_err());
+    assert!(transform::crop(&img, 0, 1, 4, 3).is_err());
+}
+
+#[test]
+fn crop_to_aspect_test() {
+    let data: Vec<u8> = (0..12).collect();
+    let img: Image<u8> = Image::from_vec(4, 3, 1, false, data);
+
+    let square = transform::crop_to_aspect(&img, 1, 1, Anchor::Left).unwrap();
+    assert_eq!(ImageInfo::new(3, 3, 1, false), square.info());
+    assert_eq!(&[0, 1, 2, 4, 5, 6, 8, 9, 10], square.data());
+
+    let square = transform::crop_to_aspect(&img, 1, 1, Anchor::Right).unwrap();
+    assert_eq!(&[1, 2, 3, 5, 6, 7, 9, 10, 11], square.data());
+
+    let wide = transform::crop_to_aspect(&img, 4, 1, Anchor::Bottom).unwrap();
+    assert_eq!(&[8, 9, 10, 11], wide.data());
+
+    let wide = transform::crop_to_aspect(&img, 4, 1, Anchor::Center).unwrap();
+    assert_eq!(&[4, 5, 6, 7], wide.data());
+
+    // Same aspect ratio as the input
+    assert_eq!(img, transform::crop_to_aspect(&img, 8, 6, Anchor::Top).unwrap());
+
+    assert!(transform::crop_to_aspect(&img, 0, 1, Anchor::Center).is_err());
+}
+
+#[test]
+fn blend_modes_test() {
+    let back: Image<u8> = Image::from_vec(2, 1, 2, true, vec![51, 255, 204, 100]);
+    let front: Image<u8> = Image::from_vec(1, 1, 2, true, vec![102, 0]);
+
+    let blend = |mode| transform::blend_modes(&back, &front, 1, 0, mode).unwrap().get_pixel(1, 0).to_vec();
+    assert_eq!(vec![82, 100], blend(BlendMode::Multiply));
+    assert_eq!(vec![224, 100], blend(BlendMode::Screen));
+    assert_eq!(vec![194, 100], blend(BlendMode::Overlay));
+    assert_eq!(vec![102, 100], blend(BlendMode::Darken));
+    assert_eq!(vec![204, 100], blend(BlendMode::Lighten));
+    assert_eq!(vec![102, 100], blend(BlendMode::Difference));
+    assert_eq!(vec![255, 100], blend(BlendMode::Add));
+
+    // Pixels outside of the overlapping region are unchanged
+    let output = transform::blend_modes(&back, &front, 1, 0, BlendMode::Add).unwrap();
+    assert_eq!(&[51, 255], output.get_pixel(0, 0));
+
+    let rgb: Image<u8> = Image::from_vec(1, 1, 3, false, vec![0, 0, 0]);
+    assert!(transform::blend_modes(&back, &rgb, 0, 0, BlendMode::Add).is_err());
+}
+
+#[test]
+fn flatten_test() {
+    let img: Image<u8> = Image::from_vec(3, 1, 2, true, vec![10, 255, 10, 0, 200, 51]);
+
+    let output = transform::flatten(&img, &[100]).unwrap();
+    assert_eq!(ImageInfo::new(3, 1, 1, false), output.info());
+    assert_eq!(&[10, 100, 120], output.data());
+
+    let rgb: Image<u8> = Image::from_vec(1, 1, 3, false, vec![0; 3]);
+    assert!(transform::flatten(&rgb, &[0, 0, 0]).is_err());
+    assert!(transform::flatten(&img, &[0, 0, 0]).is_err());
+}
+
+#[test]
+fn flatten_on_checkerboard_test() {
+    // Opaque red, fully transparent, and half transparent white pixels
+    let data = vec![255, 0, 0, 255, 9, 9, 9, 0, 255, 255, 255, 128, 0, 0, 0, 0];
+    let img: Image<u8> = Image::from_vec(4, 1, 4, true, data);
+
+    let output = transform::flatten_on_checkerboard(&img, 2).unwrap();
+    assert_eq!(ImageInfo::new(4, 1, 3, false), output.info());
+    assert_eq!(&[255, 0, 0, 204, 204, 204, 204, 204, 204, 153, 153, 153], output.data());
+
+    let output = transform::flatten_on_checkerboard(&img, 1).unwrap();
+    assert_eq!(&[153, 153, 153], output.get_pixel(1, 0));
+
+    let gray: Image<u8> = Image::from_vec(1, 1, 1, false, vec![0]);
+    assert!(transform::flatten_on_checkerboard(&gray, 2).is_err());
+    assert!(transform::flatten_on_checkerboard(&img, 0).is_err());
+}
+
+#[test]
+fn overlay_alpha_test() {
+    let back: Image<u8> = Image::from_vec(2, 1, 3, false, vec![0, 0, 0, 200, 100, 0]);
+    let front: Image<u8> = Image::from_vec(2, 1, 4, true, vec![255, 255, 255, 0, 0, 0, 200, 51]);
+
+    let output = transform::overlay_alpha(&back, &front, 0, 0).unwrap();
+    assert_eq!(back.info(), output.info());
+    assert_eq!(&[0, 0, 0, 160, 80, 40], output.data());
+
+    // Only the overlapping region is composited
+    let output = transform::overlay_alpha(&back, &front, 1, 0).unwrap();
+    assert_eq!(&[0, 0, 0, 200, 100, 0], output.data());
+
+    // A transparent back takes the color and alpha of the front
+    let back_alpha: Image<u8> = Image::from_vec(1, 1, 4, true, vec![10, 20, 30, 0]);
+    let output = transform::overlay_alpha(&back_alpha, &Image::from_vec(1, 1, 4, true, vec![0, 0, 200, 51]), 0, 0).unwrap();
+    assert_eq!(&[0, 0, 200, 51], output.get_pixel(0, 0));
+
+    assert!(transform::overlay_alpha(&front, &back, 0, 0).is_err());
+}
+
+#[cfg(feature = "fft")]
+#[test]
+fn phase_correlate_test() {
+    let (width, height) = (16, 12);
+    let pattern = |x: i32, y: i32| -> f32 {
+        let (x, y) = (x.rem_euclid(width), y.rem_euclid(height));
+        ((x * 7 + y * 13 + x * y) % 17) as f32
+    };
+
+    let mut data_a = Vec::new();
+    let mut data_b = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            data_a.push(pattern(x - 3, y + 2));
+            data_b.push(pattern(x, y));
+        }
+    }
+    let a: Image<f32> = Image::from_vec(width as u32, height as u32, 1, false, data_a);
+    let b: Image<f32> = Image::from_vec(width as u32, height as u32, 1, false, data_b);
+
+    let (dx, dy) = transform::phase_correlate(&a, &b).unwrap();
+    assert!((dx - 3.0).abs() < 0.1);
+    assert!((dy + 2.0).abs() < 0.1);
+
+    let c: Image<f32> = Image::blank(ImageInfo::new(8, 8, 1, false));
+    assert!(transform::phase_correlate(&a, &c).is_err());
+}
+
+#[test]
+fn resize_auto_test() {
+    let data = (0..48).map(|i| i as f32).collect();
+    let img: Image<f32> = Image::from_vec(8, 6, 1, false, data);
+
+    // Downscaling averages blocks of pixels
+    let proc = transform::resize_auto(&img, 4, 3).unwrap();
+    assert_eq!(ImageInfo::new(4, 3, 1, false), proc.info());
+    assert_eq!(transform::scale_area(&img, 0.5, 0.5).unwrap(), proc);
+
+    for &(width, height) in [(16, 12), (40, 30), (4, 12), (9, 5)].iter() {
+        let proc = transform::resize_auto(&img, width, height).unwrap();
+        assert_eq!(ImageInfo::new(width, height, 1, false), proc.info());
+    }
+
+    assert!(transform::resize_auto(&img, 0, 3).is_err());
+}
+
+#[test]
+fn deskew_test() {
+    // Horizontal dark lines on a white background, rotated by 3 degrees
+    let mut data = vec![255.0; 60 * 60];
+    for y in (10..50).step_by(8) {
+        for x in 8..52 {
+            data[y * 60 + x] = 0.0;
+        }
+    }
+    let straight: Image<f32> = Image::from_vec(60, 60, 1, false, data);
+    let skewed: Image<u8> = transform::rotate_in_place(&straight, -3.0, &[255.0]).unwrap().into();
+
+    let proc = transform::deskew(&skewed, 10.0).unwrap();
+    assert_eq!(skewed.info(), proc.info());
+
+    // Each line is straight again, so the dark pixels of a row are concentrated in a few rows
+    let dark_rows = (0..60).filter(|&y| (8..52).filter(|&x| proc.get_pixel(x, y)[0] < 128).count() > 30).count();
+    assert!(dark_rows >= 5);
+
+    assert!(transform::deskew(&skewed, 60.0).is_err());
+}
+
+#[test]
+fn round_corners_test() {
+    let img: Image<u8> = Image::from_vec(10, 8, 3, false, vec![50; 240]);
+
+    let output = transform::round_corners(&img, 3).unwrap();
+    assert_eq!(ImageInfo::new(10, 8, 4, true), output.info());
+    assert_eq!(&[50, 50, 50, 0], output.get_pixel(0, 0));
+    assert_eq!(&[50, 50, 50, 0], output.get_pixel(9, 7));
+    assert_eq!(&[50, 50, 50, 255], output.get_pixel(1, 1));
+    assert_eq!(&[50, 50, 50, 255], output.get_pixel(0, 3));
+    assert_eq!(&[50, 50, 50, 255], output.get_pixel(5, 4));
+
+    // Partially covered pixels are anti-aliased
+    let alpha = output.get_pixel(0, 1)[3];
+    assert!(alpha > 0 && alpha < 255);
+    assert_eq!(alpha, output.get_pixel(9, 6)[3]);
+
+    // Existing alpha is scaled, and grayscale images are expanded
+    let gray: Image<u8> = Image::from_vec(4, 4, 2, true, [20, 100].repeat(16));
+    let output = transform::round_corners(&gray, 0).unwrap();
+    assert_eq!([20, 20, 20, 100].repeat(16), output.data());
+    let output = transform::round_corners(&gray, 1).unwrap();
+    assert_eq!(&[20, 20, 20, 79], output.get_pixel(0, 0));
+    assert_eq!(&[20, 20, 20, 100], output.get_pixel(1, 0));
+
+    assert!(transform::round_corners(&img, 5).is_err());
+}
@@ -1,5 +1,6 @@
-use imgproc_rs::image::Image;
+use imgproc_rs::image::{BaseImage, Image, ImageInfo};
 use imgproc_rs::util;
+use imgproc_rs::enums::ColorMetric;
 
 #[test]
 fn summed_area_table_test() {
@@ -25,4 +26,259 @@ fn rectangular_intensity_sum_test() {
     assert_eq!(vec![154.0], util::rectangular_intensity_sum(&input, 1, 1, 4, 2));
     assert_eq!(vec![198.0], util::rectangular_intensity_sum(&input, 1, 0, 4, 2));
     assert_eq!(vec![254.0], util::rectangular_intensity_sum(&input, 0, 0, 4, 2));
-}
\ No newline at end of file
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn par_tiles_test() {
+    use imgproc_rs::image::ImageInfo;
+
+    let info = ImageInfo::new(5, 3, 2, false);
+    let output: Image<u32> = util::par_tiles(info, 2, |x_0, y_0, width, height| {
+        let mut data = Vec::new();
+        for y in y_0..(y_0 + height) {
+            for x in x_0..(x_0 + width) {
+                data.extend_from_slice(&[x, y]);
+            }
+        }
+        data
+    });
+
+    for (x, y, p) in output.into_iter() {
+        assert_eq!([x, y], p);
+    }
+}
+
+#[test]
+fn visualize_diff_test() {
+    let a: Image<u8> = Image::from_slice(2, 1, 3, false, &[10, 20, 30, 100, 100, 100]);
+    let b: Image<u8> = Image::from_slice(2, 1, 3, false, &[12, 15, 30, 0, 100, 100]);
+
+    let diff = util::visualize_diff(&a, &b, 4.0).unwrap();
+    assert_eq!(&[20, 255], diff.data());
+
+    let c: Image<u8> = Image::from_slice(1, 1, 3, false, &[0, 0, 0]);
+    assert!(util::visualize_diff(&a, &c, 1.0).is_err());
+}
+
+#[test]
+fn sharpness_test() {
+    // A sharp step edge and a blurred version of the same edge
+    let sharp: Vec<f32> = (0..64).map(|i| if i % 8 < 4 { 0.0 } else { 255.0 }).collect();
+    let blurred: Vec<f32> = (0..64).map(|i| [0.0, 0.0, 32.0, 96.0, 160.0, 224.0, 255.0, 255.0][i % 8]).collect();
+    let sharp: Image<f32> = Image::from_vec(8, 8, 1, false, sharp);
+    let blurred: Image<f32> = Image::from_vec(8, 8, 1, false, blurred);
+
+    assert!(util::sharpness_laplacian(&sharp).unwrap() > util::sharpness_laplacian(&blurred).unwrap());
+    assert!(util::sharpness_tenengrad(&sharp).unwrap() > util::sharpness_tenengrad(&blurred).unwrap());
+
+    // A flat image has no sharpness
+    let flat: Image<f32> = Image::from_vec(4, 4, 3, false, vec![0.5; 48]);
+    assert_eq!(0.0, util::sharpness_laplacian(&flat).unwrap());
+    assert_eq!(0.0, util::sharpness_tenengrad(&flat).unwrap());
+}
+
+#[test]
+fn render_histogram_test() {
+    // Three pixels with red 0, one with red 255; green and blue are always 128
+    let img: Image<u8> = Image::from_vec(4, 1, 3, false,
+                                         vec![0, 128, 128, 0, 128, 128, 0, 128, 128, 255, 128, 128]);
+    let output = util::render_histogram(&img, 4, 8);
+    assert_eq!(3, output.info().channels);
+    assert_eq!((4, 8), output.info().wh());
+
+    // Column 0 covers bin 0 (red count 3 of 4), column 2 covers bin 128 (green and blue count 4)
+    assert_eq!(&[0, 0, 0], output.get_pixel(0, 1));
+    assert_eq!(&[255, 0, 0], output.get_pixel(0, 2));
+    assert_eq!(&[0, 255, 255], output.get_pixel(2, 0));
+    assert_eq!(&[255, 0, 0], output.get_pixel(3, 7));
+    assert_eq!(&[0, 0, 0], output.get_pixel(3, 5));
+    assert_eq!(&[0, 0, 0], output.get_pixel(1, 7));
+
+    // Grayscale histograms are drawn in white
+    let gray: Image<u8> = Image::from_vec(1, 1, 1, false, vec![0]);
+    assert_eq!(&[255, 255, 255], util::render_histogram(&gray, 2, 2).get_pixel(0, 0));
+}
+
+#[test]
+fn content_bounds_test() {
+    let mut data = vec![250; 5 * 4];
+    data[6] = 0;
+    data[13] = 100;
+    data[3] = 245;
+    let img: Image<u8> = Image::from_vec(5, 4, 1, false, data);
+
+    assert_eq!(Some((1, 0, 3, 3)), util::content_bounds(&img, &[250], 0));
+    // The pixel within the tolerance is ignored
+    assert_eq!(Some((1, 1, 3, 2)), util::content_bounds(&img, &[250], 5));
+
+    let blank: Image<u8> = Image::from_vec(2, 2, 1, false, vec![250; 4]);
+    assert_eq!(None, util::content_bounds(&blank, &[250], 0));
+}
+
+#[test]
+fn projection_profile_test() {
+    let img: Image<u8> = Image::from_vec(3, 2, 2, true, vec![0, 255, 30, 255, 60, 0,
+                                                            90, 255, 120, 255, 150, 255]);
+
+    assert_eq!(vec![30.0, 120.0], util::row_profile(&img));
+    assert_eq!(vec![45.0, 75.0, 105.0], util::col_profile(&img));
+
+    let img: Image<u8> = Image::from_vec(2, 1, 3, false, vec![0, 3, 6, 3, 3, 3]);
+    assert_eq!(vec![3.0], util::row_profile(&img));
+    assert_eq!(vec![3.0, 3.0], util::col_profile(&img));
+}
+
+#[test]
+fn tile_image_test() {
+    let data: Vec<u8> = (0..140).map(|i| (i * 3 % 256) as u8).collect();
+    let img: Image<u8> = Image::from_vec(10, 7, 2, true, data);
+
+    let tiles = util::tile_image(&img, 4, 3, 1);
+    let origins: Vec<(u32, u32)> = tiles.iter().map(|(x, y, _)| (*x, *y)).collect();
+    assert_eq!(vec![(0, 0), (3, 0), (6, 0), (0, 2), (3, 2), (6, 2), (0, 4), (3, 4), (6, 4)], origins);
+    assert_eq!(ImageInfo::new(4, 3, 2, true), tiles[0].2.info());
+    assert_eq!(img.get_pixel(7, 5), tiles[8].2.get_pixel(1, 1));
+
+    // Unmodified tiles are reassembled exactly
+    assert_eq!(img, util::untile(&tiles, 10, 7, 1));
+
+    // Overlapping regions are blended
+    let img: Image<f32> = Image::blank(ImageInfo::new(6, 1, 1, false));
+    let mut tiles = util::tile_image(&img, 4, 3, 2);
+    assert_eq!(2, tiles.len());
+    tiles[1].2 = tiles[1].2.map_channels(|_| 30.0);
+    let output = util::untile(&tiles, 6, 1, 2);
+    assert_eq!(&[0.0, 0.0, 10.0, 20.0, 30.0, 30.0], output.data());
+}
+
+#[test]
+fn mean_median_stack_test() {
+    let a: Image<f32> = Image::from_vec(2, 1, 2, true, vec![1.0, 1.0, 0.0, 1.0]);
+    let b: Image<f32> = Image::from_vec(2, 1, 2, true, vec![2.0, 1.0, 9.0, 1.0]);
+    let c: Image<f32> = Image::from_vec(2, 1, 2, true, vec![6.0, 1.0, 3.0, 1.0]);
+
+    let mean = util::mean_stack(&[&a, &b, &c]).unwrap();
+    assert_eq!(a.info(), mean.info());
+    assert_eq!(&[3.0, 1.0, 4.0, 1.0], mean.data());
+
+    // The outlier at each pixel is rejected
+    let median = util::median_stack(&[&a, &b, &c]).unwrap();
+    assert_eq!(&[2.0, 1.0, 3.0, 1.0], median.data());
+    let median = util::median_stack(&[&a, &b]).unwrap();
+    assert_eq!(&[1.5, 1.0, 4.5, 1.0], median.data());
+
+    let d: Image<f32> = Image::from_vec(2, 1, 2, false, vec![0.0; 4]);
+    assert!(util::mean_stack(&[&a, &d]).is_err());
+    assert!(util::median_stack(&[]).is_err());
+}
+
+#[test]
+fn max_min_stack_test() {
+    let a: Image<f32> = Image::from_vec(3, 1, 1, false, vec![1.0, 5.0, -2.0]);
+    let b: Image<f32> = Image::from_vec(3, 1, 1, false, vec![4.0, 0.5, -3.0]);
+
+    let max = util::max_stack(&[&a, &b]).unwrap();
+    assert_eq!(a.info(), max.info());
+    assert_eq!(&[4.0, 5.0, -2.0], max.data());
+
+    let min = util::min_stack(&[&a, &b]).unwrap();
+    assert_eq!(&[1.0, 0.5, -3.0], min.data());
+    assert_eq!(a, util::min_stack(&[&a]).unwrap());
+
+    let c: Image<f32> = Image::from_vec(1, 3, 1, false, vec![0.0; 3]);
+    assert!(util::max_stack(&[&a, &c]).is_err());
+    assert!(util::min_stack(&[]).is_err());
+}
+
+#[test]
+fn reduce_stack_test() {
+    let a: Image<u8> = Image::from_vec(2, 1, 1, false, vec![10, 200]);
+    let b: Image<u8> = Image::from_vec(2, 1, 1, false, vec![20, 100]);
+    let c: Image<u8> = Image::from_vec(2, 1, 1, false, vec![90, 0]);
+
+    // Values are passed in the order of the stack
+    let first = util::reduce_stack(&[&c, &a, &b], |vals| vals[0]).unwrap();
+    assert_eq!(c, first);
+
+    let range = util::reduce_stack(&[&a, &b, &c], |vals| {
+        vals.iter().max().unwrap() - vals.iter().min().unwrap()
+    }).unwrap();
+    assert_eq!(&[80, 200], range.data());
+
+    let d: Image<u8> = Image::from_vec(2, 1, 2, false, vec![0; 4]);
+    assert!(util::reduce_stack(&[&a, &d], |vals| vals[0]).is_err());
+    assert!(util::reduce_stack::<u8, _>(&[], |vals| vals[0]).is_err());
+}
+
+#[test]
+fn pack_rgb_test() {
+    let r: Image<u8> = Image::from_vec(2, 1, 1, false, vec![1, 2]);
+    let g: Image<u8> = Image::from_vec(2, 1, 1, false, vec![3, 4]);
+    let b: Image<u8> = Image::from_vec(2, 1, 1, false, vec![5, 6]);
+
+    let output = util::pack_rgb(&r, &g, &b).unwrap();
+    assert_eq!(ImageInfo::new(2, 1, 3, false), output.info());
+    assert_eq!(&[1, 3, 5, 2, 4, 6], output.data());
+
+    let wide: Image<u8> = Image::from_vec(1, 2, 1, false, vec![0, 0]);
+    assert!(util::pack_rgb(&r, &wide, &b).is_err());
+    let alpha: Image<u8> = Image::from_vec(2, 1, 2, true, vec![0; 4]);
+    assert!(util::pack_rgb(&alpha, &alpha, &alpha).is_err());
+}
+
+#[test]
+fn trimmed_mean_test() {
+    // The outliers in the first channel are discarded
+    let data = vec![100.0, 1.0, 2.0, 2.0, 3.0, 3.0, 4.0, 4.0, -50.0, 5.0];
+    let img: Image<f32> = Image::from_vec(5, 1, 2, false, data);
+
+    assert_eq!(vec![3.0, 3.0], util::trimmed_mean(&img, 0.2));
+    assert_eq!(vec![11.8, 3.0], util::trimmed_mean(&img, 0.0));
+}
+
+#[test]
+#[should_panic]
+fn trimmed_mean_invalid_test() {
+    let img: Image<f32> = Image::from_vec(1, 1, 1, false, vec![0.0]);
+    util::trimmed_mean(&img, 0.5);
+}
+
+#[test]
+fn nearest_color_test() {
+    let palette = vec![vec![0, 0, 0], vec![0, 0, 255], vec![0, 255, 0], vec![128, 128, 128]];
+
+    for &metric in [ColorMetric::EuclideanRGB, ColorMetric::DeltaE].iter() {
+        assert_eq!(1, util::nearest_color(&[0, 0, 128], &palette, metric));
+        assert_eq!(2, util::nearest_color(&[0, 255, 0], &palette, metric));
+    }
+
+    // Dark green is closest to black in RGB, but perceptually closer to gray
+    assert_eq!(0, util::nearest_color(&[0, 100, 0], &palette, ColorMetric::EuclideanRGB));
+    assert_eq!(3, util::nearest_color(&[0, 100, 0], &palette, ColorMetric::DeltaE));
+
+    // Ties go to the first entry
+    assert_eq!(0, util::nearest_color(&[5], &[vec![0], vec![10]], ColorMetric::EuclideanRGB));
+}
+
+#[test]
+#[should_panic]
+fn nearest_color_empty_test() {
+    util::nearest_color(&[0, 0, 0], &[], ColorMetric::EuclideanRGB);
+}
+
+#[test]
+fn delta_e_image_test() {
+    let a: Image<u8> = Image::from_vec(2, 1, 4, true, vec![0, 0, 0, 255, 255, 255, 255, 0]);
+    let b: Image<u8> = Image::from_vec(2, 1, 4, true, vec![0, 0, 0, 0, 0, 0, 0, 255]);
+
+    let output = util::delta_e_image(&a, &b).unwrap();
+    assert_eq!(ImageInfo::new(2, 1, 1, false), output.info());
+    assert!(output[0][0].abs() < 1e-3);
+    assert!((output[1][0] - 100.0).abs() < 0.1);
+
+    let gray: Image<u8> = Image::from_vec(2, 1, 1, false, vec![0, 0]);
+    assert!(util::delta_e_image(&a, &gray).is_err());
+    let small: Image<u8> = Image::from_vec(1, 1, 4, true, vec![0; 4]);
+    assert!(util::delta_e_image(&a, &small).is_err());
+}